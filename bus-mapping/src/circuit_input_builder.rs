@@ -12,12 +12,14 @@ mod transaction;
 
 use self::access::gen_state_access_trace;
 use crate::error::Error;
-use crate::evm::opcodes::{gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops};
+use crate::evm::opcodes::{
+    check_gas_cost, gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops, is_opcode_supported,
+};
 use crate::operation::{CallContextField, RW};
 use crate::rpc::GethClient;
 use crate::state_db::{self, CodeDB, StateDB};
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
-pub use block::{Block, BlockContext};
+pub use block::{Block, BlockContext, EcrecoverEvent};
 pub use call::{Call, CallContext, CallKind};
 use core::fmt::Debug;
 use eth_types::{self, Address, GethExecStep, GethExecTrace, Word};
@@ -27,6 +29,36 @@ pub use input_state_ref::CircuitInputStateRef;
 use std::collections::HashMap;
 pub use transaction::{Transaction, TransactionContext};
 
+/// Addresses of the precompiled contracts.
+pub fn precompile_addresses() -> impl Iterator<Item = Address> {
+    (1..=9).map(Address::from_low_u64_be)
+}
+
+/// Granularity of the [`MemoryOp`](crate::operation::MemoryOp)s generated for a
+/// word read from memory by `MLOAD` or written to it by `MSTORE`.
+///
+/// Both come from the same trace, and convert to each other with the
+/// `memory_ops` and `from_memory_ops` methods of
+/// [`MemoryWordOp`](crate::operation::MemoryWordOp).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryOpGranularity {
+    /// One [`MemoryOp`](crate::operation::MemoryOp) per byte of the word. This
+    /// is the form expected by the circuits: the EVM circuit looks up memory
+    /// one byte at a time, and the state circuit range checks the value of
+    /// its memory rows to a byte.
+    Byte,
+    /// A single [`MemoryWordOp`](crate::operation::MemoryWordOp) for the
+    /// whole word. No circuit supports it yet, so the witness of a block built
+    /// with it can't be generated.
+    Word,
+}
+
+impl Default for MemoryOpGranularity {
+    fn default() -> Self {
+        Self::Byte
+    }
+}
+
 /// Builder to generate a complete circuit input from data gathered from a geth
 /// instance. This structure is the centre of the crate and is intended to be
 /// the only entry point to it. The `CircuitInputBuilder` works in several
@@ -55,6 +87,17 @@ pub struct CircuitInputBuilder {
     pub block: Block,
     /// Block Context
     pub block_ctx: BlockContext,
+    /// Stop with [`Error::UnsupportedOpcode`] at the first opcode whose
+    /// associated operations are not implemented, instead of generating a
+    /// dummy step for it.
+    pub stop_at_unsupported_opcode: bool,
+    /// Granularity of the memory operations generated for a word read from or
+    /// written to memory.
+    pub memory_op_granularity: MemoryOpGranularity,
+    /// Stop with [`Error::InvalidGethExecStep`] at the first step whose gas
+    /// consumed in the trace isn't its gas cost in the circuits, see
+    /// [`check_gas_cost`]. This needs the memory of the steps in the trace.
+    pub check_gas_cost: bool,
 }
 
 impl<'a> CircuitInputBuilder {
@@ -66,6 +109,9 @@ impl<'a> CircuitInputBuilder {
             code_db,
             block,
             block_ctx: BlockContext::new(),
+            stop_at_unsupported_opcode: false,
+            memory_op_granularity: MemoryOpGranularity::default(),
+            check_gas_cost: false,
         }
     }
 
@@ -84,6 +130,7 @@ impl<'a> CircuitInputBuilder {
             block_ctx: &mut self.block_ctx,
             tx,
             tx_ctx,
+            memory_op_granularity: self.memory_op_granularity,
         }
     }
 
@@ -175,13 +222,21 @@ impl<'a> CircuitInputBuilder {
         tx.steps_mut().push(begin_tx_step);
 
         for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
-            let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
             log::trace!("handle {}th opcode {:?} ", index, geth_step.op);
+            if self.stop_at_unsupported_opcode && !is_opcode_supported(&geth_step.op) {
+                return Err(Error::UnsupportedOpcode(index, geth_step.pc, geth_step.op));
+            }
+            let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
             let exec_steps = gen_associated_ops(
                 &geth_step.op,
                 &mut state_ref,
                 &geth_trace.struct_logs[index..],
             )?;
+            if self.check_gas_cost {
+                if let Some(exec_step) = exec_steps.first() {
+                    check_gas_cost(exec_step, &geth_trace.struct_logs[index..])?;
+                }
+            }
             tx.steps_mut().extend(exec_steps);
         }
 
@@ -1,11 +1,11 @@
 //! Block-related utility module
 
-use super::transaction::Transaction;
+use super::{precompile_addresses, transaction::Transaction};
 use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{Address, Hash, Word};
+use eth_types::{evm_types::HardFork, Address, Hash, ToWord, Word};
 use std::collections::HashMap;
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -36,6 +36,24 @@ impl BlockContext {
     }
 }
 
+/// Input and output of a call to the ecrecover precompile. The recovery is
+/// verified by a lookup into the signature table instead of in the EVM circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcrecoverEvent {
+    /// Id of the call to the precompile
+    pub call_id: usize,
+    /// Hash of the signed message
+    pub msg_hash: Word,
+    /// Recovery id of the signature
+    pub v: Word,
+    /// R component of the signature
+    pub r: Word,
+    /// S component of the signature
+    pub s: Word,
+    /// Recovered address, which is `None` if the signature is invalid
+    pub recovered_address: Option<Address>,
+}
+
 /// Circuit Input related to a block.
 #[derive(Debug)]
 pub struct Block {
@@ -56,10 +74,21 @@ pub struct Block {
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
+    /// prevrandao, i.e. the mix hash, which DIFFICULTY returns from the Merge
+    pub prevrandao: Word,
+    /// gas used by all the transactions
+    pub gas_used: u64,
     /// Container of operations done in this block.
     pub container: OperationContainer,
     /// Transactions contained in the block
     pub txs: Vec<Transaction>,
+    /// Calls to the ecrecover precompile in the block
+    pub ecrecover_events: Vec<EcrecoverEvent>,
+    /// Inputs of the SHA3 opcodes in the block, whose hashes are looked up in
+    /// the keccak table
+    pub sha3_inputs: Vec<Vec<u8>>,
+    /// Hard fork of the block, which decides its gas rules
+    pub fork: HardFork,
     code: HashMap<Hash, Vec<u8>>,
 }
 
@@ -90,12 +119,28 @@ impl Block {
             timestamp: eth_block.timestamp,
             difficulty: eth_block.difficulty,
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            prevrandao: eth_block.mix_hash.unwrap_or_default().to_word(),
+            gas_used: eth_block.gas_used.low_u64(),
             container: OperationContainer::new(),
             txs: Vec::new(),
+            ecrecover_events: Vec::new(),
+            sha3_inputs: Vec::new(),
+            fork: HardFork::default(),
             code: HashMap::new(),
         })
     }
 
+    /// Return the accounts which are in the access list from the start of each
+    /// transaction, besides its caller and callee: the precompiles (EIP-2929)
+    /// and the coinbase from Shanghai (EIP-3651).
+    pub fn prewarmed_addresses(&self) -> Vec<Address> {
+        let mut addresses: Vec<_> = precompile_addresses().collect();
+        if self.fork.is_coinbase_warm() {
+            addresses.push(self.coinbase);
+        }
+        addresses
+    }
+
     /// Return the list of transactions of this block.
     pub fn txs(&self) -> &[Transaction] {
         &self.txs
@@ -74,7 +74,8 @@ pub struct Call {
     pub code_source: CodeSource,
     /// Code Hash
     pub code_hash: Hash,
-    /// Depth
+    /// Depth in the call stack, which is 1 for the root call (same as geth's
+    /// trace) and increased by 1 for each nested call.
     pub depth: usize,
     /// Value
     pub value: Word,
@@ -108,6 +109,13 @@ pub struct CallContext {
     /// Call data (copy of tx input or caller's
     /// memory[call_data_offset..call_data_offset + call_data_length])
     pub call_data: Vec<u8>,
+    /// Return data of the last callee, which is set by its RETURN or REVERT
+    /// and empty otherwise
+    pub last_callee_return_data: Vec<u8>,
+    /// Call id of the last callee, which is set when it returns
+    pub last_callee_id: usize,
+    /// Offset of the return data of the last callee in its memory
+    pub last_callee_return_data_offset: Word,
 }
 
 /// A reversion group is the collection of calls and the operations which are
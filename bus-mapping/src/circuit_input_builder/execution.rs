@@ -103,6 +103,12 @@ pub enum ExecState {
     CopyToMemory,
     /// Virtal step Copy Code To Memory
     CopyCodeToMemory,
+    /// Virtual step Copy To Log
+    CopyToLog,
+    /// Virtual step Precompile Ecrecover
+    PrecompileEcrecover,
+    /// Virtual step Precompile Identity
+    PrecompileIdentity,
 }
 
 impl ExecState {
@@ -140,6 +146,9 @@ impl ExecState {
 pub enum CopyDetails {
     /// Origin of the copied bytes is or not the Tx CallData.
     TxCallData(bool),
+    /// Origin of the copied bytes is the memory of the call with the provided
+    /// call id.
+    Memory(usize),
     /// Origin of the copied bytes is bytecode. For which it's hash is provided.
     Code(U256),
     /// The bytes are being copied to a Log.
@@ -2,21 +2,22 @@
 
 use super::{
     get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, ExecState, ExecStep, Transaction, TransactionContext,
+    CallKind, CodeSource, ExecState, ExecStep, MemoryOpGranularity, Transaction,
+    TransactionContext,
 };
 use crate::{
     error::{get_step_reported_error, ExecError},
     exec_trace::OperationRef,
     operation::{
-        AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
-        StackOp, Target, RW,
+        AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, MemoryWordOp, Op,
+        OpEnum, Operation, StackOp, Target, RW,
     },
     state_db::{CodeDB, StateDB},
     Error,
 };
 use eth_types::{
-    evm_types::{Gas, MemoryAddress, OpcodeId, StackAddress},
-    Address, GethExecStep, ToAddress, ToBigEndian, Word, H256,
+    evm_types::{Gas, GasCost, MemoryAddress, OpcodeId, StackAddress},
+    Address, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256,
 };
 use ethers_core::utils::{get_contract_address, get_create2_address};
 
@@ -35,18 +36,22 @@ pub struct CircuitInputStateRef<'a> {
     pub tx: &'a mut Transaction,
     /// Transaction Context
     pub tx_ctx: &'a mut TransactionContext,
+    /// Granularity of the memory operations generated for a word
+    pub memory_op_granularity: MemoryOpGranularity,
 }
 
 impl<'a> CircuitInputStateRef<'a> {
     /// Create a new step from a `GethExecStep`
     pub fn new_step(&self, geth_step: &GethExecStep) -> Result<ExecStep, Error> {
         let call_ctx = self.tx_ctx.call_ctx()?;
-        Ok(ExecStep::new(
+        let mut exec_step = ExecStep::new(
             geth_step,
             call_ctx.index,
             self.block_ctx.rwc,
             call_ctx.reversible_write_counter,
-        ))
+        );
+        exec_step.log_id = self.tx_ctx.log_id;
+        Ok(exec_step)
     }
 
     /// Create a new BeginTx step
@@ -76,6 +81,7 @@ impl<'a> CircuitInputStateRef<'a> {
             } else {
                 0
             },
+            log_id: self.tx_ctx.log_id,
             ..Default::default()
         }
     }
@@ -215,6 +221,40 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Push a read type [`MemoryWordOp`] into the
+    /// [`OperationContainer`](crate::operation::OperationContainer) with the
+    /// next [`RWCounter`](crate::operation::RWCounter) and `call_id`, and then
+    /// adds a reference to the stored operation ([`OperationRef`]) inside
+    /// the bus-mapping instance of the current [`ExecStep`].  Then increase
+    /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter)  by one.
+    pub fn memory_word_read(
+        &mut self,
+        step: &mut ExecStep,
+        address: MemoryAddress,
+        value: Word,
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        self.push_op(step, RW::READ, MemoryWordOp::new(call_id, address, value));
+        Ok(())
+    }
+
+    /// Push a write type [`MemoryWordOp`] into the
+    /// [`OperationContainer`](crate::operation::OperationContainer) with the
+    /// next [`RWCounter`](crate::operation::RWCounter) and `call_id`, and then
+    /// adds a reference to the stored operation ([`OperationRef`]) inside
+    /// the bus-mapping instance of the current [`ExecStep`].  Then increase
+    /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter)  by one.
+    pub fn memory_word_write(
+        &mut self,
+        step: &mut ExecStep,
+        address: MemoryAddress,
+        value: Word,
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        self.push_op(step, RW::WRITE, MemoryWordOp::new(call_id, address, value));
+        Ok(())
+    }
+
     /// Push a write type [`StackOp`] into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter)  and `call_id`, and then
@@ -362,6 +402,11 @@ impl<'a> CircuitInputStateRef<'a> {
         self.tx_ctx.call_ctx_mut()
     }
 
+    /// Mutable reference to the CallContext of the caller of the current Call
+    pub fn caller_ctx_mut(&mut self) -> Result<&mut CallContext, Error> {
+        self.tx_ctx.caller_ctx_mut()
+    }
+
     /// Push a new [`Call`] into the [`Transaction`], and add its index and
     /// [`CallContext`] in the `call_stack` of the [`TransactionContext`]
     pub fn push_call(&mut self, call: Call, step: &GethExecStep) {
@@ -598,13 +643,69 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
-            OpEnum::AccountDestructed(_) => unimplemented!(),
+            OpEnum::AccountDestructed(op) => {
+                if !op.is_destructed_prev && op.is_destructed {
+                    self.sdb.destruct_account(op.address);
+                }
+                if op.is_destructed_prev && !op.is_destructed {
+                    self.sdb.remove_destructed_account(&op.address);
+                }
+            }
             _ => unreachable!(),
         };
     }
 
-    /// Handle a reversion group
-    fn handle_reversion(&mut self) {
+    /// Return the value of the state at the location written by the
+    /// reversible `op`, with the values before and after `op`. Returns `None`
+    /// for the ops whose location isn't tracked in the state.
+    fn reversible_write_values(&self, op: &OpEnum) -> Option<(Word, Word, Word)> {
+        let bool_word = |b: bool| Word::from(b as u64);
+        match op {
+            OpEnum::Storage(op) => Some((
+                *self.sdb.get_storage(&op.address, &op.key).1,
+                op.value_prev,
+                op.value,
+            )),
+            OpEnum::TxAccessListAccount(op) => Some((
+                bool_word(self.sdb.check_account_in_access_list(&op.address)),
+                bool_word(op.is_warm_prev),
+                bool_word(op.is_warm),
+            )),
+            OpEnum::TxAccessListAccountStorage(op) => Some((
+                bool_word(
+                    self.sdb
+                        .check_account_storage_in_access_list(&(op.address, op.key)),
+                ),
+                bool_word(op.is_warm_prev),
+                bool_word(op.is_warm),
+            )),
+            OpEnum::TxRefund(op) => Some((
+                self.sdb.refund().into(),
+                op.value_prev.into(),
+                op.value.into(),
+            )),
+            OpEnum::Account(op) => {
+                let (_, account) = self.sdb.get_account(&op.address);
+                let state = match op.field {
+                    AccountField::Nonce => account.nonce,
+                    AccountField::Balance => account.balance,
+                    AccountField::CodeHash => account.code_hash.to_word(),
+                };
+                Some((state, op.value_prev, op.value))
+            }
+            OpEnum::AccountDestructed(op) => Some((
+                bool_word(self.sdb.check_account_destructed(&op.address)),
+                bool_word(op.is_destructed_prev),
+                bool_word(op.is_destructed),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Handle a reversion group. Each reversion is checked to be written over
+    /// the value of the op it reverts and to restore the value before it, so
+    /// that the whole group restores the state before the call.
+    fn handle_reversion(&mut self) -> Result<(), Error> {
         let reversion_group = self
             .tx_ctx
             .reversion_groups
@@ -614,7 +715,17 @@ impl<'a> CircuitInputStateRef<'a> {
         // Apply reversions
         for (step_index, op_ref) in reversion_group.op_refs.iter().rev().copied() {
             if let Some(op) = self.get_rev_op_by_ref(&op_ref) {
+                if let Some((state, value_prev, _)) = self.reversible_write_values(&op) {
+                    if state != value_prev {
+                        return Err(Error::InconsistentReversion(op));
+                    }
+                }
                 self.apply_op(&op);
+                if let Some((state, _, value)) = self.reversible_write_values(&op) {
+                    if state != value {
+                        return Err(Error::InconsistentReversion(op));
+                    }
+                }
                 let rev_op_ref = self.block.container.insert_op_enum(
                     self.block_ctx.rwc.inc_pre(),
                     RW::WRITE,
@@ -633,6 +744,8 @@ impl<'a> CircuitInputStateRef<'a> {
             self.tx.calls_mut()[call_idx].rw_counter_end_of_reversion =
                 rwc - reversible_write_counter_offset;
         }
+
+        Ok(())
     }
 
     /// Handle a return step caused by any opcode that causes a return to the
@@ -657,7 +770,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
         // Handle reversion if this call doens't end successfully
         if !self.call()?.is_success {
-            self.handle_reversion();
+            self.handle_reversion()?;
         }
 
         self.tx_ctx.pop_call_ctx();
@@ -665,6 +778,49 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Handle the return of a step that fails with an [`ExecError`], which
+    /// consumes all the gas left, reverts the current call and leaves an empty
+    /// return data for the caller.
+    pub fn handle_error_return(
+        &mut self,
+        exec_step: &mut ExecStep,
+        geth_step: &GethExecStep,
+        error: ExecError,
+    ) -> Result<(), Error> {
+        exec_step.error = Some(error);
+        exec_step.gas_cost = GasCost(geth_step.gas.0);
+
+        let call = self.call()?.clone();
+        // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a
+        // placeholder, and later set the proper value in
+        // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+        for (field, value) in [
+            (CallContextField::RwCounterEndOfReversion, Word::zero()),
+            (
+                CallContextField::IsPersistent,
+                (call.is_persistent as u64).into(),
+            ),
+        ] {
+            self.call_context_read(exec_step, call.call_id, field, value);
+        }
+
+        if !call.is_root {
+            for (field, value) in [
+                (CallContextField::LastCalleeId, call.call_id.into()),
+                (CallContextField::LastCalleeReturnDataOffset, Word::zero()),
+                (CallContextField::LastCalleeReturnDataLength, Word::zero()),
+            ] {
+                self.call_context_write(exec_step, call.caller_id, field, value);
+            }
+            let caller_ctx = self.caller_ctx_mut()?;
+            caller_ctx.last_callee_id = call.call_id;
+            caller_ctx.last_callee_return_data_offset = Word::zero();
+            caller_ctx.last_callee_return_data.clear();
+        }
+
+        self.handle_return(geth_step)
+    }
+
     pub(crate) fn get_step_err(
         &self,
         step: &GethExecStep,
@@ -7,7 +7,7 @@ use crate::geth_errors::{
 };
 use crate::operation::RWCounter;
 use crate::state_db::Account;
-use eth_types::evm_types::{stack::Stack, Gas, OpcodeId};
+use eth_types::evm_types::{stack::Stack, Gas, OpcodeId, ProgramCounter};
 use eth_types::{
     address, bytecode, geth_types::GethData, word, Bytecode, Hash, ToAddress, ToWord, Word,
 };
@@ -1968,3 +1968,83 @@ fn test_gen_access_trace_create_push_call_stack() {
         }
     )
 }
+
+#[test]
+fn tracer_stop_at_unsupported_opcode() {
+    let code = bytecode! {
+        PUSH1(0x00)
+        POP
+        ADDRESS
+        STOP
+    };
+    let block: GethData = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+
+    // By default a dummy step is generated for the unsupported opcode
+    let mut builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+
+    let mut builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder.stop_at_unsupported_opcode = true;
+    match builder.handle_block(&block.eth_block, &block.geth_traces) {
+        Err(Error::UnsupportedOpcode(index, pc, opcode)) => {
+            assert_eq!(index, 2);
+            assert_eq!(pc, ProgramCounter(3));
+            assert_eq!(opcode, OpcodeId::ADDRESS);
+        }
+        result => panic!("expected UnsupportedOpcode, got {:?}", result.err()),
+    }
+}
+
+#[test]
+fn tracer_gas_cost_mismatch() {
+    let code = bytecode! {
+        PUSH1(0x01)
+        PUSH1(0x02)
+        ADD
+        STOP
+    };
+    let mut block: GethData = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+
+    let new_builder = |block: &GethData| {
+        let mut builder =
+            crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.check_gas_cost = true;
+        builder
+    };
+    new_builder(&block)
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+
+    // Make ADD consume one more gas than the 3 the circuits charge for it
+    let stop_step = &mut block.geth_traces[0].struct_logs[3];
+    assert_eq!(stop_step.op, OpcodeId::STOP);
+    stop_step.gas = Gas(stop_step.gas.0 - 1);
+    match new_builder(&block).handle_block(&block.eth_block, &block.geth_traces) {
+        Err(Error::InvalidGethExecStep(_, step)) => assert_eq!(step.op, OpcodeId::ADD),
+        result => panic!("expected InvalidGethExecStep, got {:?}", result.err()),
+    }
+
+    // The check is opt-in
+    crate::mock::BlockData::new_from_geth_data(block.clone())
+        .new_circuit_input_builder()
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+}
@@ -2,7 +2,7 @@
 
 use std::collections::BTreeMap;
 
-use eth_types::{Address, GethExecTrace, Word};
+use eth_types::{evm_types::GasCost, Address, GethExecTrace, ToWord, Word};
 use ethers_core::utils::get_contract_address;
 
 use crate::{
@@ -29,6 +29,8 @@ pub struct TransactionContext {
     /// in the inner most revert (which we track with the last element in
     /// the reversion groups stack), and skip it in the outer revert.
     pub(crate) reversion_groups: Vec<ReversionGroup>,
+    /// Number of persistent logs emitted so far in this transaction.
+    pub(crate) log_id: usize,
 }
 
 impl TransactionContext {
@@ -75,6 +77,7 @@ impl TransactionContext {
             call_is_success,
             calls: Vec::new(),
             reversion_groups: Vec::new(),
+            log_id: 0,
         };
         tx_ctx.push_call_ctx(0, eth_tx.input.to_vec());
 
@@ -118,6 +121,15 @@ impl TransactionContext {
         ))
     }
 
+    pub(crate) fn caller_ctx_mut(&mut self) -> Result<&mut CallContext, Error> {
+        let caller_idx = self.calls.len().checked_sub(2);
+        caller_idx
+            .and_then(|idx| self.calls.get_mut(idx))
+            .ok_or(Error::InvalidGethExecTrace(
+                "Call stack has no caller but caller is used",
+            ))
+    }
+
     /// Push a new call context and its index into the call stack.
     pub(crate) fn push_call_ctx(&mut self, call_idx: usize, call_data: Vec<u8>) {
         if !self.call_is_success[call_idx] {
@@ -146,6 +158,9 @@ impl TransactionContext {
             index: call_idx,
             reversible_write_counter: 0,
             call_data,
+            last_callee_return_data: Vec::new(),
+            last_callee_id: 0,
+            last_callee_return_data_offset: Word::zero(),
         });
     }
 
@@ -178,6 +193,9 @@ pub struct Transaction {
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>,
+    /// Accounts and their storage keys in the access list (EIP-2930), which
+    /// are warm from the start of the transaction
+    pub access_list: Vec<(Address, Vec<Word>)>,
     /// Calls made in the transaction
     calls: Vec<Call>,
     /// Execution steps
@@ -247,11 +265,39 @@ impl Transaction {
             to: eth_tx.to.unwrap_or_default(),
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            access_list: eth_tx
+                .access_list
+                .as_ref()
+                .map(|access_list| {
+                    access_list
+                        .0
+                        .iter()
+                        .map(|item| {
+                            (
+                                item.address,
+                                item.storage_keys.iter().map(ToWord::to_word).collect(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
             calls: vec![call],
             steps: Vec::new(),
         })
     }
 
+    /// Return the gas cost of the access list, which is part of the intrinsic
+    /// gas of the transaction.
+    pub fn access_list_gas_cost(&self) -> u64 {
+        self.access_list
+            .iter()
+            .map(|(_, storage_keys)| {
+                GasCost::ACCESS_LIST_ADDRESS.as_u64()
+                    + GasCost::ACCESS_LIST_STORAGE_KEY.as_u64() * storage_keys.len() as u64
+            })
+            .sum()
+    }
+
     /// Wether this [`Transaction`] is a create one
     pub fn is_create(&self) -> bool {
         self.calls[0].is_create()
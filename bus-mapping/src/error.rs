@@ -1,13 +1,19 @@
 //! Error module for the bus-mapping crate
 
 use core::fmt::{Display, Formatter, Result as FmtResult};
-use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
+use eth_types::{
+    evm_types::{OpcodeId, ProgramCounter},
+    Address, GethExecStep, Word, H256,
+};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
+        GETH_ERR_STACK_UNDERFLOW,
+    },
+    operation::OpEnum,
 };
 
 /// Error type for any BusMapping related failure.
@@ -32,10 +38,16 @@ pub enum Error {
     InvalidGethExecTrace(&'static str),
     /// Invalid [`GethExecStep`] due to an invalid/unexpected value in it.
     InvalidGethExecStep(&'static str, GethExecStep),
+    /// Opcode without implemented associated operations, found at the step
+    /// index of the trace and the program counter.
+    UnsupportedOpcode(usize, ProgramCounter, OpcodeId),
     /// Eth type related error.
     EthTypeError(eth_types::Error),
     /// EVM Execution error
     ExecutionError(ExecError),
+    /// Reversion of a call which doesn't restore the state written over by the
+    /// reverted operation.
+    InconsistentReversion(OpEnum),
 }
 
 impl From<eth_types::Error> for Error {
@@ -1,6 +1,7 @@
 //! Definition of each opcode of the EVM.
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
     evm::OpcodeId,
     operation::{
         AccountField, AccountOp, CallContextField, CallContextOp, TxAccessListAccountOp,
@@ -10,13 +11,15 @@ use crate::{
 };
 use core::fmt::Debug;
 use eth_types::{
-    evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    GethExecStep, ToAddress, ToWord, Word,
+    evm_types::{gas_utils::memory_expansion_gas_cost, GasCost},
+    Address, GethExecStep, ToAddress, ToWord, Word,
 };
 use keccak256::EMPTY_HASH;
 use log::warn;
 use std::collections::HashMap;
 
+mod balance;
+mod block_ctx;
 mod call;
 mod calldatacopy;
 mod calldataload;
@@ -26,19 +29,32 @@ mod callvalue;
 mod chainid;
 mod codecopy;
 mod dup;
+mod error_invalid_jump;
 mod extcodehash;
+mod extcodesize;
 mod gasprice;
+mod invalid;
+mod jump;
+mod logs;
 mod mload;
 mod mstore;
 mod number;
 mod origin;
+mod precompiles;
+mod push;
+mod return_revert;
+mod returndatacopy;
+mod returndatasize;
 mod selfbalance;
+mod sha3;
 mod sload;
 mod sstore;
 mod stackonlyop;
 mod stop;
 mod swap;
 
+use balance::Balance;
+use block_ctx::BlockCtx;
 use call::Call;
 use calldatacopy::Calldatacopy;
 use calldataload::Calldataload;
@@ -47,12 +63,22 @@ use caller::Caller;
 use callvalue::Callvalue;
 use codecopy::Codecopy;
 use dup::Dup;
+use error_invalid_jump::ErrorInvalidJump;
 use extcodehash::Extcodehash;
+use extcodesize::Extcodesize;
 use gasprice::GasPrice;
+use invalid::InvalidOpcode;
+use jump::Jump;
+use logs::Log;
 use mload::Mload;
 use mstore::Mstore;
 use origin::Origin;
+use push::Push;
+use return_revert::ReturnRevert;
+use returndatacopy::Returndatacopy;
+use returndatasize::Returndatasize;
 use selfbalance::Selfbalance;
+use sha3::Sha3;
 use sload::Sload;
 use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
@@ -86,12 +112,12 @@ type FnGenAssociatedOps = fn(
     geth_steps: &[GethExecStep],
 ) -> Result<Vec<ExecStep>, Error>;
 
-fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
+fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> Option<FnGenAssociatedOps> {
     if opcode_id.is_push() {
-        return StackOnlyOpcode::<0, 1>::gen_associated_ops;
+        return Some(Push::gen_associated_ops);
     }
 
-    match opcode_id {
+    Some(match opcode_id {
         OpcodeId::STOP => Stop::gen_associated_ops,
         OpcodeId::ADD => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::MUL => StackOnlyOpcode::<2, 1>::gen_associated_ops,
@@ -118,9 +144,9 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::SHL => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::SHR => StackOnlyOpcode::<2, 1>::gen_associated_ops,
         OpcodeId::SAR => StackOnlyOpcode::<2, 1>::gen_associated_ops,
-        // OpcodeId::SHA3 => {},
+        OpcodeId::SHA3 => Sha3::gen_associated_ops,
         // OpcodeId::ADDRESS => {},
-        // OpcodeId::BALANCE => {},
+        OpcodeId::BALANCE => Balance::gen_associated_ops,
         OpcodeId::ORIGIN => Origin::gen_associated_ops,
         OpcodeId::CALLER => Caller::gen_associated_ops,
         OpcodeId::CALLVALUE => Callvalue::gen_associated_ops,
@@ -130,28 +156,28 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         // OpcodeId::CODESIZE => {},
         OpcodeId::GASPRICE => GasPrice::gen_associated_ops,
         OpcodeId::CODECOPY => Codecopy::gen_associated_ops,
-        // OpcodeId::EXTCODESIZE => {},
+        OpcodeId::EXTCODESIZE => Extcodesize::gen_associated_ops,
         // OpcodeId::EXTCODECOPY => {},
-        // OpcodeId::RETURNDATASIZE => {},
-        // OpcodeId::RETURNDATACOPY => {},
+        OpcodeId::RETURNDATASIZE => Returndatasize::gen_associated_ops,
+        OpcodeId::RETURNDATACOPY => Returndatacopy::gen_associated_ops,
         OpcodeId::EXTCODEHASH => Extcodehash::gen_associated_ops,
         // OpcodeId::BLOCKHASH => {},
-        OpcodeId::COINBASE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::TIMESTAMP => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::NUMBER => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::DIFFICULTY => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::GASLIMIT => StackOnlyOpcode::<0, 1>::gen_associated_ops,
-        OpcodeId::CHAINID => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        OpcodeId::COINBASE => BlockCtx::gen_associated_ops,
+        OpcodeId::TIMESTAMP => BlockCtx::gen_associated_ops,
+        OpcodeId::NUMBER => BlockCtx::gen_associated_ops,
+        OpcodeId::DIFFICULTY => BlockCtx::gen_associated_ops,
+        OpcodeId::GASLIMIT => BlockCtx::gen_associated_ops,
+        OpcodeId::CHAINID => BlockCtx::gen_associated_ops,
         OpcodeId::SELFBALANCE => Selfbalance::gen_associated_ops,
-        OpcodeId::BASEFEE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        OpcodeId::BASEFEE => BlockCtx::gen_associated_ops,
         OpcodeId::POP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::MLOAD => Mload::gen_associated_ops,
         OpcodeId::MSTORE => Mstore::<false>::gen_associated_ops,
         OpcodeId::MSTORE8 => Mstore::<true>::gen_associated_ops,
         OpcodeId::SLOAD => Sload::gen_associated_ops,
         OpcodeId::SSTORE => Sstore::gen_associated_ops,
-        OpcodeId::JUMP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
-        OpcodeId::JUMPI => StackOnlyOpcode::<2, 0>::gen_associated_ops,
+        OpcodeId::JUMP => Jump::gen_associated_ops,
+        OpcodeId::JUMPI => Jump::gen_associated_ops,
         OpcodeId::PC => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::MSIZE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::GAS => StackOnlyOpcode::<0, 1>::gen_associated_ops,
@@ -188,21 +214,26 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::SWAP14 => Swap::<14>::gen_associated_ops,
         OpcodeId::SWAP15 => Swap::<15>::gen_associated_ops,
         OpcodeId::SWAP16 => Swap::<16>::gen_associated_ops,
-        // OpcodeId::LOG0 => {},
-        // OpcodeId::LOG1 => {},
-        // OpcodeId::LOG2 => {},
-        // OpcodeId::LOG3 => {},
-        // OpcodeId::LOG4 => {},
+        OpcodeId::LOG0 => Log::gen_associated_ops,
+        OpcodeId::LOG1 => Log::gen_associated_ops,
+        OpcodeId::LOG2 => Log::gen_associated_ops,
+        OpcodeId::LOG3 => Log::gen_associated_ops,
+        OpcodeId::LOG4 => Log::gen_associated_ops,
         // OpcodeId::CREATE => {},
         OpcodeId::CALL => Call::gen_associated_ops,
         // OpcodeId::CALLCODE => {},
-        // TODO: Handle RETURN by its own gen_associated_ops.
-        OpcodeId::RETURN => Stop::gen_associated_ops,
+        OpcodeId::RETURN => ReturnRevert::gen_associated_ops,
         // OpcodeId::DELEGATECALL => {},
         // OpcodeId::CREATE2 => {},
         // OpcodeId::STATICCALL => {},
-        // TODO: Handle REVERT by its own gen_associated_ops.
-        OpcodeId::REVERT => Stop::gen_associated_ops,
+        OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
+        OpcodeId::INVALID(_) => InvalidOpcode::gen_associated_ops,
+        _ => return None,
+    })
+}
+
+fn fn_gen_dummy_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
+    match opcode_id {
         OpcodeId::SELFDESTRUCT => {
             warn!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
             dummy_gen_selfdestruct_ops
@@ -222,6 +253,13 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
     }
 }
 
+fn fn_gen_error_state_associated_ops(error: &ExecError) -> Option<FnGenAssociatedOps> {
+    match error {
+        ExecError::InvalidJump => Some(ErrorInvalidJump::gen_associated_ops),
+        _ => None,
+    }
+}
+
 /// Generate the associated operations according to the particular
 /// [`OpcodeId`].
 pub fn gen_associated_ops(
@@ -229,10 +267,126 @@ pub fn gen_associated_ops(
     state: &mut CircuitInputStateRef,
     geth_steps: &[GethExecStep],
 ) -> Result<Vec<ExecStep>, Error> {
-    let fn_gen_associated_ops = fn_gen_associated_ops(opcode_id);
+    // Steps failing with an error that has its own execution state are
+    // handled separately from the opcode
+    let geth_step = &geth_steps[0];
+    if let Some(exec_error) = state.get_step_err(geth_step, geth_steps.get(1))? {
+        if let Some(fn_gen_error_state_associated_ops) =
+            fn_gen_error_state_associated_ops(&exec_error)
+        {
+            return fn_gen_error_state_associated_ops(state, geth_steps);
+        }
+    }
+
+    let fn_gen_associated_ops =
+        fn_gen_associated_ops(opcode_id).unwrap_or_else(|| fn_gen_dummy_associated_ops(opcode_id));
     fn_gen_associated_ops(state, geth_steps)
 }
 
+/// Verify that the gas consumed between the first of the `geth_steps` and the
+/// next step in the trace is the gas cost the circuits charge for it, i.e.
+/// the constant gas cost of its opcode plus the memory expansion, with the
+/// memory sizes taken from the memory of the steps. Steps that fail, that
+/// leave the current call context, or whose opcode has a dynamic gas cost
+/// besides the memory expansion, are skipped.
+pub fn check_gas_cost(exec_step: &ExecStep, geth_steps: &[GethExecStep]) -> Result<(), Error> {
+    let geth_step = &geth_steps[0];
+    let next_geth_step = match geth_steps.get(1) {
+        Some(next_geth_step) if next_geth_step.depth == geth_step.depth => next_geth_step,
+        _ => return Ok(()),
+    };
+    if exec_step.error.is_some()
+        || geth_step.op.is_log()
+        || matches!(
+            geth_step.op,
+            OpcodeId::EXP
+                | OpcodeId::SHA3
+                | OpcodeId::BALANCE
+                | OpcodeId::CALLDATACOPY
+                | OpcodeId::CODECOPY
+                | OpcodeId::EXTCODESIZE
+                | OpcodeId::EXTCODECOPY
+                | OpcodeId::RETURNDATACOPY
+                | OpcodeId::EXTCODEHASH
+                | OpcodeId::SLOAD
+                | OpcodeId::SSTORE
+                | OpcodeId::CALL
+                | OpcodeId::CALLCODE
+                | OpcodeId::DELEGATECALL
+                | OpcodeId::STATICCALL
+                | OpcodeId::CREATE
+                | OpcodeId::CREATE2
+                | OpcodeId::SELFDESTRUCT
+        )
+    {
+        return Ok(());
+    }
+
+    let gas_cost = geth_step.op.constant_gas_cost().0
+        + memory_expansion_gas_cost(
+            geth_step.memory.word_size() as u64,
+            next_geth_step.memory.word_size() as u64,
+        );
+    if gas_cost != geth_step.gas.0 - next_geth_step.gas.0 {
+        return Err(Error::InvalidGethExecStep(
+            "gas consumed by the step doesn't match its gas cost in the circuits",
+            geth_step.clone(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns whether the associated operations of the [`OpcodeId`] are
+/// implemented, rather than approximated by a dummy step.
+pub fn is_opcode_supported(opcode_id: &OpcodeId) -> bool {
+    fn_gen_associated_ops(opcode_id).is_some()
+}
+
+/// Pop the address of an external account off the stack, read the tx id and
+/// the reversion info of the current call, and add the account to the tx
+/// access list. This is the common start of the opcodes which access an
+/// external account, and the address is returned for their account reads.
+fn gen_external_account_ops(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    step: &GethExecStep,
+) -> Result<Address, Error> {
+    let external_address = step.stack.last()?.to_address();
+    state.stack_read(
+        exec_step,
+        step.stack.last_filled(),
+        external_address.to_word(),
+    )?;
+
+    for (field, value) in [
+        (CallContextField::TxId, Word::from(state.tx_ctx.id())),
+        (
+            CallContextField::RwCounterEndOfReversion,
+            Word::from(state.call()?.rw_counter_end_of_reversion as u64),
+        ),
+        (
+            CallContextField::IsPersistent,
+            Word::from(state.call()?.is_persistent as u64),
+        ),
+    ] {
+        state.call_context_read(exec_step, state.call()?.call_id, field, value);
+    }
+
+    let is_warm = state.sdb.check_account_in_access_list(&external_address);
+    state.push_op_reversible(
+        exec_step,
+        RW::WRITE,
+        TxAccessListAccountOp {
+            tx_id: state.tx_ctx.id(),
+            address: external_address,
+            is_warm: true,
+            is_warm_prev: is_warm,
+        },
+    )?;
+
+    Ok(external_address)
+}
+
 pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
     let mut exec_step = state.new_begin_tx_step();
     let call = state.call()?.clone();
@@ -288,6 +442,22 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
         );
     }
 
+    // The precompiles, and the coinbase from Shanghai, are also warm from the
+    // start of the tx, but without any rw. The state circuit seeds them into
+    // the access list instead, see `RwMap::insert_access_list_seeds`.
+    for address in state.block.prewarmed_addresses() {
+        state.sdb.add_account_to_access_list(address);
+    }
+
+    // So are the accounts and storage slots in the access list of the tx
+    // (EIP-2930), see `RwMap::insert_tx_access_list_seeds`.
+    for (address, storage_keys) in state.tx.access_list.clone() {
+        state.sdb.add_account_to_access_list(address);
+        for key in storage_keys {
+            state.sdb.add_account_storage_to_access_list((address, key));
+        }
+    }
+
     // Calculate intrinsic gas cost
     let call_data_gas_cost = state
         .tx
@@ -298,7 +468,8 @@ pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Er
         GasCost::CREATION_TX.as_u64()
     } else {
         GasCost::TX.as_u64()
-    } + call_data_gas_cost;
+    } + call_data_gas_cost
+        + state.tx.access_list_gas_cost();
     exec_step.gas_cost = GasCost(intrinsic_gas_cost);
 
     // Transfer with fee
@@ -426,8 +597,9 @@ pub fn gen_end_tx_ops(
         },
     );
 
-    let effective_refund =
-        refund.min((state.tx.gas - exec_step.gas_left.0) / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    let effective_refund = refund.min(
+        (state.tx.gas - exec_step.gas_left.0) / state.block.fork.max_refund_quotient_of_gas_used(),
+    );
     let (found, caller_account) = state.sdb.get_account_mut(&call.caller_address);
     if !found {
         return Err(Error::AccountNotFound(call.caller_address));
@@ -676,3 +848,73 @@ fn dummy_gen_selfdestruct_ops(
 
     Ok(vec![exec_step])
 }
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn create_increases_creator_nonce() {
+        // CREATE twice with empty init code from the same account, so the
+        // second creation must observe the nonce bumped by the first one.
+        let code = bytecode! {
+            PUSH1(0x00) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+            CREATE
+            PUSH1(0x00) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+            CREATE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+
+        // BeginTx increases the tx caller's nonce right after reading TxId,
+        // RwCounterEndOfReversion and IsPersistent.
+        let begin_tx_nonce_op =
+            builder.block.container.account[steps[0].bus_mapping_instance[3].as_usize()].op();
+        assert_eq!(begin_tx_nonce_op.address, MOCK_ACCOUNTS[1]);
+        assert_eq!(begin_tx_nonce_op.field, AccountField::Nonce);
+        assert_eq!(begin_tx_nonce_op.value, begin_tx_nonce_op.value_prev + 1);
+
+        // Each CREATE increases the creator's nonce exactly once.
+        let create_nonce_ops = steps
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(OpcodeId::CREATE))
+            .map(|step| {
+                builder.block.container.account[step.bus_mapping_instance[0].as_usize()].op()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(create_nonce_ops.len(), 2);
+        for op in create_nonce_ops.iter() {
+            assert_eq!(op.address, MOCK_ACCOUNTS[0]);
+            assert_eq!(op.field, AccountField::Nonce);
+            assert_eq!(op.value, op.value_prev + 1);
+        }
+        assert_eq!(create_nonce_ops[1].value_prev, create_nonce_ops[0].value);
+    }
+}
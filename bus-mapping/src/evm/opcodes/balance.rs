@@ -0,0 +1,127 @@
+use super::{gen_external_account_ops, Opcode};
+use crate::{
+    circuit_input_builder::CircuitInputStateRef, evm::opcodes::ExecStep, operation::AccountField,
+    Error,
+};
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::BALANCE`](crate::evm::OpcodeId::BALANCE)
+/// `OpcodeId`, which warms the queried account up.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Balance;
+
+impl Opcode for Balance {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let step = &steps[0];
+        let mut exec_step = state.new_step(step)?;
+        let stack_address = step.stack.last_filled();
+
+        let external_address = gen_external_account_ops(state, &mut exec_step, step)?;
+
+        let balance = state.sdb.get_account(&external_address).1.balance;
+        state.account_read(
+            &mut exec_step,
+            external_address,
+            AccountField::Balance,
+            balance,
+            balance,
+        )?;
+
+        // Stack write of the balance
+        state.stack_write(&mut exec_step, stack_address, balance)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod balance_tests {
+    use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::Target};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        geth_types::GethData,
+        Address, ToWord, Word,
+    };
+    use ethers_core::types::transaction::eip2930::{AccessList, AccessListItem};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    /// Return the gas cost of the BALANCE of `address`, and whether it was warm
+    /// before, in a tx with `access_list`.
+    fn balance_of(address: Address, access_list: AccessList) -> (u64, bool) {
+        let code = bytecode! {
+            PUSH20(address.to_word())
+            BALANCE
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .access_list(access_list);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::BALANCE))
+            .unwrap();
+        let op_ref = step
+            .bus_mapping_instance
+            .iter()
+            .find(|op_ref| op_ref.target() == Target::TxAccessListAccount)
+            .unwrap();
+        let op = builder.block.container.tx_access_list_account[op_ref.as_usize()].op();
+        assert_eq!((op.address, op.is_warm), (address, true));
+        (step.gas_cost.as_u64(), op.is_warm_prev)
+    }
+
+    #[test]
+    fn balance_opcode_cold() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        assert_eq!(
+            balance_of(address, AccessList::default()),
+            (GasCost::COLD_ACCOUNT_ACCESS.as_u64(), false)
+        );
+    }
+
+    #[test]
+    fn balance_opcode_in_tx_access_list() {
+        // The first access to an account in the access list of the tx is warm
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let access_list = AccessList(vec![AccessListItem {
+            address,
+            storage_keys: vec![],
+        }]);
+        assert_eq!(
+            balance_of(address, access_list),
+            (GasCost::WARM_ACCESS.as_u64(), true)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::OpcodeId,
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the block context opcodes, which push a single field of
+/// the block: `NUMBER`, `TIMESTAMP`, `COINBASE`, `DIFFICULTY`, `GASLIMIT`,
+/// `CHAINID` and `BASEFEE`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct BlockCtx;
+
+impl Opcode for BlockCtx {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let block = &state.block;
+        let value = match geth_step.op {
+            OpcodeId::NUMBER => block.number,
+            OpcodeId::TIMESTAMP => block.timestamp,
+            OpcodeId::COINBASE => block.coinbase.to_word(),
+            OpcodeId::DIFFICULTY if block.fork.has_prevrandao() => block.prevrandao,
+            OpcodeId::DIFFICULTY => block.difficulty,
+            OpcodeId::GASLIMIT => Word::from(block.gas_limit),
+            OpcodeId::CHAINID => block.chain_id,
+            OpcodeId::BASEFEE => block.base_fee,
+            _ => {
+                return Err(Error::InvalidGethExecStep(
+                    "BlockCtx handles only the block context opcodes",
+                    geth_step.clone(),
+                ))
+            }
+        };
+        state.stack_write(
+            &mut exec_step,
+            geth_step.stack.last_filled().map(|a| a - 1),
+            value,
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod block_ctx_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        evm::OpcodeId,
+        mock::BlockData,
+        operation::{StackOp, RW},
+    };
+    use eth_types::{bytecode, evm_types::StackAddress, geth_types::GethData, ToWord};
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn coinbase_opcode_impl() {
+        let code = bytecode! {
+            COINBASE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let coinbase = block.eth_block.author;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::COINBASE))
+            .unwrap();
+
+        let op_coinbase = &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(step.bus_mapping_instance.len(), 1);
+        assert_eq!(
+            (op_coinbase.rw(), op_coinbase.op()),
+            (
+                RW::WRITE,
+                &StackOp::new(1, StackAddress(1023usize), coinbase.to_word())
+            )
+        );
+    }
+}
@@ -1,19 +1,28 @@
-use super::Opcode;
+use super::{precompiles, Opcode};
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{self, CircuitInputStateRef, ExecStep},
+    error::ExecError,
     operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
     Error,
 };
 use eth_types::{
     evm_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
-        GasCost,
+        GasCost, GAS_STIPEND_CALL_WITH_VALUE,
     },
-    GethExecStep, ToWord,
+    GethExecStep, ToWord, Word,
 };
 use keccak256::EMPTY_HASH;
 use log::warn;
 
+/// Maximum call depth, a call made from a deeper context fails the precheck.
+const MAX_CALL_DEPTH: usize = 1024;
+
+/// Address of the ecrecover precompile.
+const PRECOMPILE_ECRECOVER: u64 = 0x01;
+/// Address of the identity precompile.
+const PRECOMPILE_IDENTITY: u64 = 0x04;
+
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the `OpcodeId::CALL` `OpcodeId`.
 #[derive(Debug, Copy, Clone)]
@@ -31,6 +40,32 @@ impl Opcode for Call {
         let current_call = state.call()?.clone();
         let call = state.parse_call(geth_step)?;
 
+        // Call depth is checked before caller's balance
+        if current_call.depth > MAX_CALL_DEPTH {
+            return gen_precheck_failure_ops(
+                state,
+                exec_step,
+                geth_step,
+                current_call,
+                call,
+                ExecError::Depth,
+            );
+        }
+        let (found, caller_account) = state.sdb.get_account(&call.caller_address);
+        if !found {
+            return Err(Error::AccountNotFound(call.caller_address));
+        }
+        if caller_account.balance < call.value {
+            return gen_precheck_failure_ops(
+                state,
+                exec_step,
+                geth_step,
+                current_call,
+                call,
+                ExecError::InsufficientBalance,
+            );
+        }
+
         // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a placeholder,
         // and later set the proper value in
         // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
@@ -89,6 +124,7 @@ impl Opcode for Call {
                 CallContextField::IsPersistent,
                 (call.is_persistent as u64).into(),
             ),
+            (CallContextField::IsSuccess, (call.is_success as u64).into()),
         ] {
             state.call_context_read(&mut exec_step, call.call_id, field, value);
         }
@@ -141,18 +177,30 @@ impl Opcode for Call {
         );
         let callee_gas_left = eip150_gas(geth_step.gas.0 - gas_cost, geth_step.stack.last()?);
 
+        let gen_precompile_steps: Option<precompiles::GenPrecompileSteps> =
+            if state.is_precompiled(&call.address) {
+                match call.address.to_low_u64_be() {
+                    PRECOMPILE_ECRECOVER => Some(precompiles::gen_ecrecover_steps),
+                    PRECOMPILE_IDENTITY => Some(precompiles::gen_identity_steps),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+
         // There are 3 branches from here.
         match (
             state.is_precompiled(&call.address),
+            gen_precompile_steps.is_some(),
             callee_code_hash.to_fixed_bytes() == *EMPTY_HASH,
         ) {
-            // 1. Call to precompiled.
-            (true, _) => {
+            // 1. Call to unimplemented precompiled.
+            (true, false, _) => {
                 warn!("Call to precompiled is left unimplemented");
                 Ok(vec![exec_step])
             }
             // 2. Call to account with empty code.
-            (_, true) => {
+            (false, _, true) => {
                 for (field, value) in [
                     (CallContextField::LastCalleeId, 0.into()),
                     (CallContextField::LastCalleeReturnDataOffset, 0.into()),
@@ -163,9 +211,9 @@ impl Opcode for Call {
                 state.handle_return(geth_step)?;
                 Ok(vec![exec_step])
             }
-            // 3. Call to account with non-empty code.
-            (_, false) => {
-                for (field, value) in [
+            // 3. Call to account with non-empty code or to implemented precompiled.
+            _ => {
+                let caller_state = [
                     (
                         CallContextField::ProgramCounter,
                         (geth_step.pc.0 + 1).into(),
@@ -183,7 +231,8 @@ impl Opcode for Call {
                         CallContextField::StateWriteCounter,
                         (exec_step.reversible_write_counter + 1).into(),
                     ),
-                ] {
+                ];
+                for (field, value) in caller_state.clone() {
                     state.call_context_write(&mut exec_step, current_call.call_id, field, value);
                 }
 
@@ -213,7 +262,6 @@ impl Opcode for Call {
                         call.return_data_length.into(),
                     ),
                     (CallContextField::Value, call.value),
-                    (CallContextField::IsSuccess, (call.is_success as u64).into()),
                     (CallContextField::IsStatic, (call.is_static as u64).into()),
                     (CallContextField::LastCalleeId, 0.into()),
                     (CallContextField::LastCalleeReturnDataOffset, 0.into()),
@@ -225,8 +273,234 @@ impl Opcode for Call {
                     state.call_context_read(&mut exec_step, call.call_id, field, value);
                 }
 
+                if let Some(gen_precompile_steps) = gen_precompile_steps {
+                    // Give gas stipend if value is not zero
+                    let callee_gas_left = callee_gas_left
+                        + if has_value {
+                            GAS_STIPEND_CALL_WITH_VALUE
+                        } else {
+                            0
+                        };
+                    let mut exec_steps = vec![exec_step];
+                    exec_steps.extend(gen_precompile_steps(
+                        state,
+                        geth_steps,
+                        &current_call,
+                        callee_gas_left,
+                        caller_state,
+                    )?);
+                    return Ok(exec_steps);
+                }
+
                 Ok(vec![exec_step])
             }
         }
     }
 }
+
+/// Generate the associated operations of a CALL which fails the precheck,
+/// either because the call depth would exceed [`MAX_CALL_DEPTH`] or because
+/// the caller doesn't have enough balance to transfer `value`. The callee is
+/// added into access list but its context is never entered, and `0` is pushed
+/// onto the caller's stack.
+fn gen_precheck_failure_ops(
+    state: &mut CircuitInputStateRef,
+    mut exec_step: ExecStep,
+    geth_step: &GethExecStep,
+    current_call: circuit_input_builder::Call,
+    call: circuit_input_builder::Call,
+    error: ExecError,
+) -> Result<Vec<ExecStep>, Error> {
+    let is_insufficient_balance = error == ExecError::InsufficientBalance;
+    exec_step.error = Some(error);
+
+    // The caller's balance is read ahead of the operations shared by both
+    // failures
+    if is_insufficient_balance {
+        state.call_context_read(
+            &mut exec_step,
+            current_call.call_id,
+            CallContextField::CalleeAddress,
+            current_call.address.to_word(),
+        );
+        let (_, caller_account) = state.sdb.get_account(&call.caller_address);
+        let caller_balance = caller_account.balance;
+        state.account_read(
+            &mut exec_step,
+            call.caller_address,
+            AccountField::Balance,
+            caller_balance,
+            caller_balance,
+        )?;
+    }
+
+    let tx_id = state.tx_ctx.id();
+    // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a placeholder,
+    // and later set the proper value in
+    // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+    for (field, value) in [
+        (CallContextField::TxId, tx_id.into()),
+        (CallContextField::RwCounterEndOfReversion, 0.into()),
+        (
+            CallContextField::IsPersistent,
+            (current_call.is_persistent as u64).into(),
+        ),
+        (
+            CallContextField::IsStatic,
+            (current_call.is_static as u64).into(),
+        ),
+        (CallContextField::Depth, current_call.depth.into()),
+    ] {
+        state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+    }
+
+    for i in 0..7 {
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(i),
+            geth_step.stack.nth_last(i)?,
+        )?;
+    }
+    state.stack_write(
+        &mut exec_step,
+        geth_step.stack.nth_last_filled(6),
+        Word::zero(),
+    )?;
+
+    let is_warm = state.sdb.check_account_in_access_list(&call.address);
+    state.push_op_reversible(
+        &mut exec_step,
+        RW::WRITE,
+        TxAccessListAccountOp {
+            tx_id,
+            address: call.address,
+            is_warm: true,
+            is_warm_prev: is_warm,
+        },
+    )?;
+
+    let (_, callee_account) = state.sdb.get_account(&call.address);
+    let callee_nonce = callee_account.nonce;
+    let callee_balance = callee_account.balance;
+    let callee_code_hash = callee_account.code_hash;
+    for (field, value) in [
+        (AccountField::Nonce, callee_nonce),
+        (AccountField::Balance, callee_balance),
+        (AccountField::CodeHash, callee_code_hash.to_word()),
+    ] {
+        state.account_read(&mut exec_step, call.address, field, value, value)?;
+    }
+
+    for (field, value) in [
+        (CallContextField::LastCalleeId, 0.into()),
+        (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+        (CallContextField::LastCalleeReturnDataLength, 0.into()),
+    ] {
+        state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+    }
+
+    // The failed call is still tracked so later calls keep their `is_success`
+    // in order, and it returns immediately.
+    state.push_call(call, geth_step);
+    state.handle_return(geth_step)?;
+
+    Ok(vec![exec_step])
+}
+
+#[cfg(test)]
+mod call_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, Target},
+    };
+    use eth_types::{
+        address, bytecode, evm_types::OpcodeId, geth_types::GethData, Address, Bytecode, ToWord,
+        Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    fn call_code(callee: Address) -> Bytecode {
+        bytecode! {
+            PUSH1(0) // rd_length
+            PUSH1(0) // rd_offset
+            PUSH1(0) // cd_length
+            PUSH1(0) // cd_offset
+            PUSH1(0) // value
+            PUSH32(callee.to_word())
+            PUSH2(10000) // gas
+            CALL
+            STOP
+        }
+    }
+
+    #[test]
+    fn depth_through_nested_calls() {
+        let addr_a = address!("0x00000000000000000000000000000000000000aa");
+        let addr_b = address!("0x00000000000000000000000000000000000000bb");
+        let addr_c = address!("0x00000000000000000000000000000000000000cc");
+
+        let block: GethData = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+                accs[1].address(addr_a).code(call_code(addr_b));
+                accs[2].address(addr_b).code(call_code(addr_c));
+                accs[3].address(addr_c).code(bytecode! { STOP });
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let transaction = &builder.block.txs()[0];
+        let container = &builder.block.container;
+        let depth_values = |step_index: usize| {
+            transaction.steps()[step_index]
+                .bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.target() == Target::CallContext)
+                .map(|op_ref| container.call_context[op_ref.as_usize()].op())
+                .filter(|op| op.field == CallContextField::Depth)
+                .map(|op| op.value.as_usize())
+                .collect::<Vec<_>>()
+        };
+
+        // Depth is initialized to 1 at BeginTx, the same as geth's trace.
+        assert_eq!(transaction.steps()[0].exec_state, ExecState::BeginTx);
+        assert_eq!(depth_values(0), vec![1]);
+
+        // Each CALL reads caller's depth and sets callee's depth to 1 more.
+        let call_steps = transaction
+            .steps()
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        assert_eq!(call_steps.len(), 2);
+        assert_eq!(depth_values(call_steps[0]), vec![1, 2]);
+        assert_eq!(depth_values(call_steps[1]), vec![2, 3]);
+
+        // Depth of the executing call goes up through the nested calls and
+        // back down when they return.
+        let mut depths = transaction
+            .steps()
+            .iter()
+            .map(|step| transaction.calls()[step.call_index].depth)
+            .collect::<Vec<_>>();
+        depths.dedup();
+        assert_eq!(depths, vec![1, 2, 3, 2, 1]);
+    }
+}
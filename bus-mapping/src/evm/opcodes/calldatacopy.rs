@@ -425,4 +425,60 @@ mod calldatacopy_tests {
             ]
         );
     }
+
+    #[test]
+    fn calldatacopy_opcode_root_past_calldata_end() {
+        let calldata = vec![0x11, 0x22, 0x33, 0x44];
+        let code = bytecode! {
+            PUSH32(8) // length
+            PUSH32(2) // data_offset
+            PUSH32(0) // memory_offset
+            CALLDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(calldata.clone().into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // The 2 bytes of calldata from the offset are copied, and the 6 bytes
+        // past its end are written as 0. The calldata of the root call is in
+        // the tx, so there are no memory reads.
+        let memory_ops = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::CopyToMemory)
+            .flat_map(|step| step.bus_mapping_instance.iter())
+            .map(|op_ref| {
+                let operation = &builder.block.container.memory[op_ref.as_usize()];
+                (operation.rw(), operation.op().clone())
+            })
+            .collect::<Vec<(RW, MemoryOp)>>();
+        assert_eq!(
+            memory_ops,
+            [0x33, 0x44, 0, 0, 0, 0, 0, 0]
+                .iter()
+                .enumerate()
+                .map(|(idx, byte)| (RW::WRITE, MemoryOp::new(call_id, idx.into(), *byte)))
+                .collect::<Vec<(RW, MemoryOp)>>()
+        );
+    }
 }
@@ -59,8 +59,11 @@ impl Opcode for Calldataload {
         }
 
         let call = state.call()?.clone();
+        // an offset past the end of call data reads all zeros, so it's clamped
+        // to the call data length to not overflow for huge offsets.
         let (src_addr, src_addr_end, caller_id, call_data) = (
-            call.call_data_offset as usize + offset.as_usize(),
+            call.call_data_offset as usize
+                + offset.min(U256::from(call.call_data_length)).as_usize(),
             call.call_data_offset as usize + call.call_data_length as usize,
             call.caller_id,
             state.call_ctx()?.call_data.to_vec(),
@@ -365,6 +368,9 @@ mod calldataload_tests {
             calldata.clone(),
             Word::from_big_endian(&calldata[12..44]),
         );
+
+        // 4. offset past the end of call data: all zeros
+        test_root_ok(0x40u64, rand_bytes(32), Word::zero());
     }
 
     #[test]
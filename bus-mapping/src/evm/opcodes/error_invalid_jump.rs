@@ -0,0 +1,117 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::OpcodeId,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to a [`OpcodeId::JUMP`] or [`OpcodeId::JUMPI`] whose
+/// destination is not a valid `JUMPDEST`, which fails with
+/// [`ExecError::InvalidJump`].
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorInvalidJump;
+
+impl Opcode for ErrorInvalidJump {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        // Pop the destination, and the condition for JUMPI
+        let n_pop = if geth_step.op == OpcodeId::JUMPI {
+            2
+        } else {
+            1
+        };
+        for i in 0..n_pop {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        state.handle_error_return(&mut exec_step, geth_step, ExecError::InvalidJump)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod error_invalid_jump_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn invalid_jumpi_impl() {
+        // Jump into the push data of PUSH1
+        let code = bytecode! {
+            PUSH1(0x01) // condition
+            PUSH1(0x01) // destination
+            JUMPI
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let call_id = tx.calls()[0].call_id;
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::JUMPI))
+            .unwrap();
+
+        assert_eq!(step.error, Some(ExecError::InvalidJump));
+        assert_eq!(step.gas_cost.0, step.gas_left.0);
+        assert!(!tx.calls()[0].is_persistent);
+
+        assert_eq!(
+            (0..2)
+                .map(|idx| {
+                    let operation =
+                        &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [(1022, Word::from(0x01)), (1023, Word::from(0x01))]
+                .map(|(address, value)| (
+                    RW::READ,
+                    StackOp::new(call_id, StackAddress::from(address), value)
+                ))
+                .to_vec()
+        );
+    }
+}
@@ -1,12 +1,9 @@
-use super::Opcode;
+use super::{gen_external_account_ops, Opcode};
 use crate::{
-    circuit_input_builder::CircuitInputStateRef,
-    evm::opcodes::ExecStep,
-    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
-    state_db::Account,
-    Error,
+    circuit_input_builder::CircuitInputStateRef, evm::opcodes::ExecStep, operation::AccountField,
+    state_db::Account, Error,
 };
-use eth_types::{GethExecStep, ToAddress, ToWord, U256};
+use eth_types::{GethExecStep, ToWord};
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct Extcodehash;
@@ -20,39 +17,7 @@ impl Opcode for Extcodehash {
         let mut exec_step = state.new_step(step)?;
         let stack_address = step.stack.last_filled();
 
-        // Pop external address off stack
-        let external_address = step.stack.last()?.to_address();
-        state.stack_read(&mut exec_step, stack_address, external_address.to_word())?;
-
-        // Read transaction id, rw_counter_end_of_reversion, and is_persistent from call
-        // context
-
-        for (field, value) in [
-            (CallContextField::TxId, U256::from(state.tx_ctx.id())),
-            (
-                CallContextField::RwCounterEndOfReversion,
-                U256::from(state.call()?.rw_counter_end_of_reversion as u64),
-            ),
-            (
-                CallContextField::IsPersistent,
-                U256::from(state.call()?.is_persistent as u64),
-            ),
-        ] {
-            state.call_context_read(&mut exec_step, state.call()?.call_id, field, value);
-        }
-
-        // Update transaction access list for external_address
-        let is_warm = state.sdb.check_account_in_access_list(&external_address);
-        state.push_op_reversible(
-            &mut exec_step,
-            RW::WRITE,
-            TxAccessListAccountOp {
-                tx_id: state.tx_ctx.id(),
-                address: external_address,
-                is_warm: true,
-                is_warm_prev: is_warm,
-            },
-        )?;
+        let external_address = gen_external_account_ops(state, &mut exec_step, step)?;
 
         // These three lookups are required to determine the existence of the external
         // account
@@ -98,7 +63,9 @@ mod extcodehash_tests {
     use super::*;
     use crate::circuit_input_builder::ExecState;
     use crate::mock::BlockData;
-    use crate::operation::{AccountOp, CallContextOp, StackOp};
+    use crate::operation::{
+        AccountOp, CallContextField, CallContextOp, StackOp, TxAccessListAccountOp, RW,
+    };
     use eth_types::{
         address, bytecode,
         evm_types::{OpcodeId, StackAddress},
@@ -0,0 +1,132 @@
+use super::{gen_external_account_ops, Opcode};
+use crate::{
+    circuit_input_builder::CircuitInputStateRef, evm::opcodes::ExecStep, operation::AccountField,
+    Error,
+};
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODESIZE`](crate::evm::OpcodeId::EXTCODESIZE) `OpcodeId`,
+/// which warms the queried account up.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Extcodesize;
+
+impl Opcode for Extcodesize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let step = &steps[0];
+        let mut exec_step = state.new_step(step)?;
+        let stack_address = step.stack.last_filled();
+
+        let external_address = gen_external_account_ops(state, &mut exec_step, step)?;
+
+        let code_hash = state.sdb.get_account(&external_address).1.code_hash;
+        state.account_read(
+            &mut exec_step,
+            external_address,
+            AccountField::CodeHash,
+            code_hash.to_word(),
+            code_hash.to_word(),
+        )?;
+
+        // Stack write of the size of the code
+        state.stack_write(&mut exec_step, stack_address, steps[1].stack.last()?)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod extcodesize_tests {
+    use super::*;
+    use crate::circuit_input_builder::ExecState;
+    use crate::mock::BlockData;
+    use crate::operation::{StackOp, TxAccessListAccountOp, RW};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytes, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn extcodesize_cold_then_warm() {
+        let external_address = address!("0xaabbccddee000000000000000000000000000000");
+        let code = bytecode! {
+            PUSH20(external_address.to_word())
+            EXTCODESIZE
+            POP
+            PUSH20(external_address.to_word())
+            EXTCODESIZE
+            STOP
+        };
+        let code_ext = Bytes::from([34, 54, 56]);
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1].address(external_address).code(code_ext.clone());
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let transaction = &builder.block.txs()[0];
+        let call_id = transaction.calls()[0].call_id;
+        let steps = transaction
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(OpcodeId::EXTCODESIZE))
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 2);
+
+        // The first access is cold and the second one warm, and both push the
+        // size of the code
+        for (step, is_warm_prev) in steps.into_iter().zip([false, true]) {
+            let indices = &step.bus_mapping_instance;
+            assert_eq!(
+                builder.block.container.tx_access_list_account[indices[4].as_usize()].op(),
+                &TxAccessListAccountOp {
+                    tx_id: 1,
+                    address: external_address,
+                    is_warm: true,
+                    is_warm_prev,
+                }
+            );
+            let operation = &builder.block.container.stack[indices[6].as_usize()];
+            assert_eq!(
+                (operation.rw(), operation.op()),
+                (
+                    RW::WRITE,
+                    &StackOp::new(
+                        call_id,
+                        StackAddress::from(1023u32),
+                        Word::from(code_ext.len())
+                    )
+                )
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::INVALID`](crate::evm::OpcodeId::INVALID) `OpcodeId`, which
+/// covers both the designated invalid opcode `0xfe` and any undefined opcode.
+/// The step consumes all the gas left and the current call is reverted.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct InvalidOpcode;
+
+impl Opcode for InvalidOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        state.handle_error_return(&mut exec_step, geth_step, ExecError::InvalidOpcode)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod invalid_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, RW},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn invalid_opcode_impl() {
+        let mut code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+        };
+        code.write(0x0c);
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let call = &tx.calls()[0];
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::INVALID(0x0c)))
+            .unwrap();
+
+        // All the gas left is consumed and the call is reverted
+        assert_eq!(step.error, Some(ExecError::InvalidOpcode));
+        assert_eq!(step.gas_cost.0, step.gas_left.0);
+        assert!(!call.is_persistent);
+        assert_ne!(call.rw_counter_end_of_reversion, 0);
+
+        assert_eq!(
+            (0..2)
+                .map(|idx| {
+                    let operation = &builder.block.container.call_context
+                        [step.bus_mapping_instance[idx].as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [
+                (
+                    CallContextField::RwCounterEndOfReversion,
+                    Word::from(call.rw_counter_end_of_reversion),
+                ),
+                (CallContextField::IsPersistent, Word::zero()),
+            ]
+            .map(|(field, value)| (
+                RW::READ,
+                CallContextOp {
+                    call_id: call.call_id,
+                    field,
+                    value,
+                }
+            ))
+            .to_vec()
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::OpcodeId,
+    Error,
+};
+use eth_types::{GethExecStep, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to a successful [`OpcodeId::JUMP`] or [`OpcodeId::JUMPI`].
+/// The ones to an invalid destination are handled by
+/// [`ErrorInvalidJump`](super::error_invalid_jump::ErrorInvalidJump).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Jump;
+
+impl Opcode for Jump {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        // Pop the destination, and the condition for JUMPI
+        let n_pop = if geth_step.op == OpcodeId::JUMPI {
+            2
+        } else {
+            1
+        };
+        for i in 0..n_pop {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        // The circuit looks the destination of a taken jump up in the bytecode
+        // table as a JUMPDEST which isn't push data, so it must be one.
+        let is_taken = geth_step.op == OpcodeId::JUMP || !geth_step.stack.nth_last(1)?.is_zero();
+        if is_taken {
+            let code = state.code(state.call()?.code_hash)?;
+            if !is_valid_jump_dest(&code, geth_step.stack.last()?) {
+                return Err(Error::InvalidGethExecStep(
+                    "JUMP or JUMPI without error to an invalid JUMPDEST",
+                    geth_step.clone(),
+                ));
+            }
+        }
+
+        Ok(vec![exec_step])
+    }
+}
+
+/// Whether `dest` is the position of a `JUMPDEST` in `code`, which isn't the
+/// push data of a `PUSH*`.
+pub(crate) fn is_valid_jump_dest(code: &[u8], dest: Word) -> bool {
+    if dest >= Word::from(code.len()) {
+        return false;
+    }
+    let dest = dest.as_usize();
+    let mut pc = 0;
+    while pc < dest {
+        let op = code[pc];
+        pc += 1;
+        if (OpcodeId::PUSH1.as_u8()..=OpcodeId::PUSH32.as_u8()).contains(&op) {
+            pc += (op - OpcodeId::PUSH1.as_u8() + 1) as usize;
+        }
+    }
+    pc == dest && code[dest] == OpcodeId::JUMPDEST.as_u8()
+}
+
+#[cfg(test)]
+mod jump_tests {
+    use super::is_valid_jump_dest;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytecode, Word,
+    };
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// Run `code`, which has a single JUMPI at pc 4, and return its stack reads
+    /// and the pc of the step after it.
+    fn jumpi_ops(code: Bytecode) -> (Vec<(RW, StackOp)>, usize) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let idx = steps
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::JUMPI))
+            .unwrap();
+        assert_eq!(steps[idx].pc.0, 4);
+        assert_eq!(steps[idx].error, None);
+        let ops = steps[idx]
+            .bus_mapping_instance
+            .iter()
+            .map(|op_ref| {
+                let operation = &builder.block.container.stack[op_ref.as_usize()];
+                (operation.rw(), operation.op().clone())
+            })
+            .collect();
+        (ops, steps[idx + 1].pc.0)
+    }
+
+    #[test]
+    fn jumpi_taken() {
+        let code = bytecode! {
+            PUSH1(0x01) // condition
+            PUSH1(0x06) // destination
+            JUMPI
+            STOP
+            JUMPDEST
+            STOP
+        };
+
+        assert_eq!(
+            jumpi_ops(code),
+            (
+                vec![
+                    (RW::READ, StackOp::new(1, StackAddress(1022), Word::from(6))),
+                    (RW::READ, StackOp::new(1, StackAddress(1023), Word::from(1))),
+                ],
+                6
+            )
+        );
+    }
+
+    #[test]
+    fn jumpi_not_taken() {
+        let code = bytecode! {
+            PUSH1(0x00) // condition
+            PUSH1(0x06) // destination
+            JUMPI
+            STOP
+            JUMPDEST
+            STOP
+        };
+
+        assert_eq!(
+            jumpi_ops(code),
+            (
+                vec![
+                    (RW::READ, StackOp::new(1, StackAddress(1022), Word::from(6))),
+                    (RW::READ, StackOp::new(1, StackAddress(1023), Word::zero())),
+                ],
+                5
+            )
+        );
+    }
+
+    #[test]
+    fn valid_jump_dests() {
+        let code = bytecode! {
+            PUSH2(0x5b5b) // push data which looks like JUMPDESTs
+            JUMPDEST
+            STOP
+        }
+        .to_vec();
+
+        assert!(is_valid_jump_dest(&code, Word::from(3)));
+        // In the push data of PUSH2
+        assert!(!is_valid_jump_dest(&code, Word::from(1)));
+        assert!(!is_valid_jump_dest(&code, Word::from(2)));
+        // Not a JUMPDEST
+        assert!(!is_valid_jump_dest(&code, Word::from(4)));
+        // Past the end of the code
+        assert!(!is_valid_jump_dest(&code, Word::from(5)));
+        assert!(!is_valid_jump_dest(&code, Word::MAX));
+    }
+}
@@ -0,0 +1,352 @@
+use super::Opcode;
+use crate::operation::{CallContextField, TxLogField, TxLogOp, RW};
+use crate::Error;
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDetails, ExecState, ExecStep, StepAuxiliaryData,
+    },
+    constants::MAX_COPY_BYTES,
+};
+use eth_types::{evm_types::OpcodeId, GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the `LOG0` - `LOG4` opcodes.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Log;
+
+impl Opcode for Log {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_steps = vec![gen_log_step(state, geth_step)?];
+        let log_copy_steps = gen_log_copy_steps(state, geth_steps)?;
+        exec_steps.extend(log_copy_steps);
+        Ok(exec_steps)
+    }
+}
+
+fn gen_log_step(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_step(geth_step)?;
+    let mstart = geth_step.stack.nth_last(0)?;
+    let msize = geth_step.stack.nth_last(1)?;
+
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), mstart)?;
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), msize)?;
+
+    let call = state.call()?.clone();
+    let tx_id = state.tx_ctx.id();
+    for (field, value) in [
+        (CallContextField::TxId, tx_id.into()),
+        (CallContextField::IsStatic, (call.is_static as u64).into()),
+        (CallContextField::CalleeAddress, call.address.to_word()),
+        (
+            CallContextField::IsPersistent,
+            (call.is_persistent as u64).into(),
+        ),
+    ] {
+        state.call_context_read(&mut exec_step, call.call_id, field, value);
+    }
+
+    // Logs of a persistent call are written to the TxLog with the next log id
+    let log_id = state.tx_ctx.log_id + 1;
+    if call.is_persistent {
+        state.push_op(
+            &mut exec_step,
+            RW::WRITE,
+            TxLogOp {
+                tx_id,
+                log_id,
+                field: TxLogField::Address,
+                index: 0,
+                value: call.address.to_word(),
+            },
+        );
+    }
+
+    let topic_count = (geth_step.op.as_u8() - OpcodeId::LOG0.as_u8()) as usize;
+    for idx in 0..topic_count {
+        let topic = geth_step.stack.nth_last(2 + idx)?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(2 + idx),
+            topic,
+        )?;
+        if call.is_persistent {
+            state.push_op(
+                &mut exec_step,
+                RW::WRITE,
+                TxLogOp {
+                    tx_id,
+                    log_id,
+                    field: TxLogField::Topic,
+                    index: idx,
+                    value: topic,
+                },
+            );
+        }
+    }
+
+    if call.is_persistent {
+        state.tx_ctx.log_id = log_id;
+    }
+
+    Ok(exec_step)
+}
+
+fn gen_log_copy_step(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    memory: &[u8],
+    src_addr: u64,
+    src_addr_end: u64,
+    bytes_left: usize,
+    data_start_index: usize,
+) -> Result<(), Error> {
+    let call = state.call()?.clone();
+    let tx_id = state.tx_ctx.id();
+    for idx in 0..std::cmp::min(bytes_left, MAX_COPY_BYTES) {
+        let addr = src_addr as usize + idx;
+        // Memory beyond the current size is expanded with zeros by the LOG
+        let byte = memory.get(addr).copied().unwrap_or(0);
+        state.memory_read(exec_step, addr.into(), byte)?;
+        if call.is_persistent {
+            state.push_op(
+                exec_step,
+                RW::WRITE,
+                TxLogOp {
+                    tx_id,
+                    log_id: state.tx_ctx.log_id,
+                    field: TxLogField::Data,
+                    index: data_start_index + idx,
+                    value: Word::from(byte),
+                },
+            );
+        }
+    }
+
+    exec_step.aux_data = Some(StepAuxiliaryData::new(
+        src_addr,
+        0,
+        bytes_left as u64,
+        src_addr_end,
+        CopyDetails::Log((call.is_persistent, tx_id, data_start_index)),
+    ));
+
+    Ok(())
+}
+
+fn gen_log_copy_steps(
+    state: &mut CircuitInputStateRef,
+    geth_steps: &[GethExecStep],
+) -> Result<Vec<ExecStep>, Error> {
+    let mstart = geth_steps[0].stack.nth_last(0)?.as_u64();
+    let msize = geth_steps[0].stack.nth_last(1)?.as_usize();
+
+    let mut copied = 0;
+    let mut steps = vec![];
+    while copied < msize {
+        let mut exec_step = state.new_step(&geth_steps[1])?;
+        exec_step.exec_state = ExecState::CopyToLog;
+        gen_log_copy_step(
+            state,
+            &mut exec_step,
+            &geth_steps[0].memory.0,
+            mstart + copied as u64,
+            mstart + msize as u64,
+            msize - copied,
+            copied,
+        )?;
+        steps.push(exec_step);
+        copied += MAX_COPY_BYTES;
+    }
+
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod log_tests {
+    use super::*;
+    use crate::{
+        mock::BlockData,
+        operation::{MemoryOp, Target},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, MemoryAddress},
+        geth_types::GethData,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn log3_opcode_impl() {
+        let data = (1..=32).collect::<Vec<u8>>();
+        let topics = [0xA0u64, 0xef, 0xb0].map(Word::from);
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&data))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(topics[2])
+            PUSH32(topics[1])
+            PUSH32(topics[0])
+            PUSH1(0x10) // msize
+            PUSH1(0x08) // mstart
+            LOG3
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        assert!(tx
+            .steps()
+            .iter()
+            .any(|step| step.exec_state == ExecState::Op(OpcodeId::LOG3)));
+        assert_eq!(
+            tx.steps()
+                .iter()
+                .filter(|step| step.exec_state == ExecState::CopyToLog)
+                .count(),
+            (data[8..24].len() + MAX_COPY_BYTES - 1) / MAX_COPY_BYTES
+        );
+
+        let expected = std::iter::once((TxLogField::Address, 0, tx.to.to_word()))
+            .chain(
+                topics
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, topic)| (TxLogField::Topic, idx, *topic)),
+            )
+            .chain(
+                data[8..24]
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, byte)| (TxLogField::Data, idx, Word::from(*byte))),
+            )
+            .map(|(field, index, value)| TxLogOp {
+                tx_id: 1,
+                log_id: 1,
+                field,
+                index,
+                value,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            builder
+                .block
+                .container
+                .tx_log
+                .iter()
+                .map(|operation| {
+                    assert_eq!(operation.rw(), RW::WRITE);
+                    operation.op().clone()
+                })
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        // The log id is increased after the LOG
+        assert_eq!(tx.steps().last().unwrap().log_id, 1);
+    }
+
+    #[test]
+    fn log1_opcode_impl() {
+        let topic = Word::from(0xcafeu64);
+        let code = bytecode! {
+            PUSH4(0xdeadbeefu64)
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(topic)
+            PUSH1(0x04) // msize
+            PUSH1(0x1c) // mstart
+            LOG1
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let log_step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::LOG1))
+            .unwrap();
+        let copy_step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::CopyToLog)
+            .unwrap();
+        let stop_step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::STOP))
+            .unwrap();
+
+        // One topic is read from the stack and written to the log
+        let topics = builder
+            .block
+            .container
+            .tx_log
+            .iter()
+            .filter(|operation| operation.op().field == TxLogField::Topic)
+            .map(|operation| operation.op().value)
+            .collect::<Vec<_>>();
+        assert_eq!(topics, vec![topic]);
+
+        // The data is read from memory by the copy step
+        let data = [0xdeu8, 0xad, 0xbe, 0xef];
+        assert_eq!(
+            copy_step
+                .bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.target() == Target::Memory)
+                .map(|op_ref| {
+                    let operation = &builder.block.container.memory[op_ref.as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            data.iter()
+                .enumerate()
+                .map(|(idx, byte)| (
+                    RW::READ,
+                    MemoryOp::new(1, MemoryAddress::from(0x1c + idx), *byte)
+                ))
+                .collect::<Vec<_>>()
+        );
+
+        // LOG1 advances the pc by 1, and costs the static gas of a log with a
+        // topic and 4 bytes of data, as the memory doesn't expand
+        let gas_cost = GasCost::LOG.as_u64() + GasCost::LOG.as_u64() + 8 * data.len() as u64;
+        assert_eq!(stop_step.pc.0, log_step.pc.0 + 1);
+        assert_eq!(log_step.gas_cost.as_u64(), gas_cost);
+        assert_eq!(log_step.gas_left.0 - stop_step.gas_left.0, gas_cost);
+    }
+}
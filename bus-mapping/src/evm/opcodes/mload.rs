@@ -1,5 +1,5 @@
 use super::Opcode;
-use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep, MemoryOpGranularity};
 use crate::Error;
 use core::convert::TryInto;
 use eth_types::evm_types::MemoryAddress;
@@ -43,11 +43,18 @@ impl Opcode for Mload {
         //
         // First mem read -> 32 MemoryOp generated.
         //
-        for byte in mem_read_value.to_be_bytes() {
-            state.memory_read(&mut exec_step, mem_read_addr, byte)?;
-
-            // Update mem_read_addr to next byte's one
-            mem_read_addr += MemoryAddress::from(1);
+        match state.memory_op_granularity {
+            MemoryOpGranularity::Byte => {
+                for byte in mem_read_value.to_be_bytes() {
+                    state.memory_read(&mut exec_step, mem_read_addr, byte)?;
+
+                    // Update mem_read_addr to next byte's one
+                    mem_read_addr += MemoryAddress::from(1);
+                }
+            }
+            MemoryOpGranularity::Word => {
+                state.memory_word_read(&mut exec_step, mem_read_addr, mem_read_value)?;
+            }
         }
 
         Ok(vec![exec_step])
@@ -60,7 +67,7 @@ mod mload_tests {
     use crate::{
         circuit_input_builder::ExecState,
         mock::BlockData,
-        operation::{MemoryOp, StackOp, RW},
+        operation::{MemoryOp, MemoryWordOp, StackOp, Target, RW},
     };
     use eth_types::{
         bytecode,
@@ -133,4 +140,84 @@ mod mload_tests {
                 .collect_vec()
         )
     }
+
+    #[test]
+    fn mload_opcode_byte_and_word_granularity_agree() {
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&(1..=32u8).collect_vec()))
+            PUSH1(0x40u64)
+            MSTORE
+            PUSH1(0x40u64)
+            MLOAD
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        // Build the same trace with memory operations of each granularity, and
+        // return the word pushed by MLOAD and the memory operations it did.
+        let mload = |memory_op_granularity| {
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder.memory_op_granularity = memory_op_granularity;
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+
+            let step = builder.block.txs()[0]
+                .steps()
+                .iter()
+                .find(|step| step.exec_state == ExecState::Op(OpcodeId::MLOAD))
+                .unwrap();
+            let pushed = *builder.block.container.stack[step.bus_mapping_instance[1].as_usize()]
+                .op()
+                .value();
+            let memory_ops = step
+                .bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.target() == Target::Memory)
+                .map(|op_ref| {
+                    builder.block.container.memory[op_ref.as_usize()]
+                        .op()
+                        .clone()
+                })
+                .collect_vec();
+            let memory_word_ops = step
+                .bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.target() == Target::MemoryWord)
+                .map(|op_ref| {
+                    builder.block.container.memory_word[op_ref.as_usize()]
+                        .op()
+                        .clone()
+                })
+                .collect_vec();
+            (pushed, memory_ops, memory_word_ops)
+        };
+
+        let (pushed_byte, memory_ops, _) = mload(MemoryOpGranularity::Byte);
+        let (pushed_word, _, memory_word_ops) = mload(MemoryOpGranularity::Word);
+
+        let word = Word::from_big_endian(&(1..=32u8).collect_vec());
+        assert_eq!(pushed_byte, word);
+        assert_eq!(pushed_word, word);
+        assert_eq!(
+            memory_word_ops,
+            vec![MemoryWordOp::new(1, MemoryAddress(0x40), word)]
+        );
+        // Each representation converts to the other one
+        assert_eq!(memory_word_ops[0].memory_ops(), memory_ops);
+        assert_eq!(
+            MemoryWordOp::from_memory_ops(&memory_ops),
+            Some(memory_word_ops[0].clone())
+        );
+    }
 }
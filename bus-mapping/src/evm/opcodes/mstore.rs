@@ -1,5 +1,5 @@
 use super::Opcode;
-use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep, MemoryOpGranularity};
 use crate::Error;
 use core::convert::TryInto;
 use eth_types::evm_types::MemoryAddress;
@@ -40,13 +40,18 @@ impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
                     *value.to_le_bytes().first().unwrap(),
                 )?;
             }
-            false => {
-                // stack write each byte for mstore
-                let bytes = value.to_be_bytes();
-                for (i, byte) in bytes.iter().enumerate() {
-                    state.memory_write(&mut exec_step, offset_addr.map(|a| a + i), *byte)?;
+            false => match state.memory_op_granularity {
+                MemoryOpGranularity::Byte => {
+                    // stack write each byte for mstore
+                    let bytes = value.to_be_bytes();
+                    for (i, byte) in bytes.iter().enumerate() {
+                        state.memory_write(&mut exec_step, offset_addr.map(|a| a + i), *byte)?;
+                    }
                 }
-            }
+                MemoryOpGranularity::Word => {
+                    state.memory_word_write(&mut exec_step, offset_addr, value)?;
+                }
+            },
         }
 
         Ok(vec![exec_step])
@@ -59,7 +64,7 @@ mod mstore_tests {
     use crate::{
         circuit_input_builder::ExecState,
         mock::BlockData,
-        operation::{MemoryOp, StackOp, RW},
+        operation::{MemoryOp, MemoryWordOp, StackOp, RW},
     };
     use eth_types::{
         bytecode,
@@ -137,6 +142,51 @@ mod mstore_tests {
         )
     }
 
+    #[test]
+    fn mstore_opcode_word_granularity() {
+        let code = bytecode! {
+            PUSH2(0x1234)
+            PUSH2(0x100)
+            MSTORE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.memory_op_granularity = MemoryOpGranularity::Word;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::MSTORE))
+            .unwrap();
+
+        // The word is written by a single operation instead of 32 byte ones
+        assert_eq!(step.bus_mapping_instance.len(), 3);
+        assert!(builder.block.container.memory.is_empty());
+        let memory_word_op =
+            &builder.block.container.memory_word[step.bus_mapping_instance[2].as_usize()];
+        assert_eq!(
+            (memory_word_op.rw(), memory_word_op.op()),
+            (
+                RW::WRITE,
+                &MemoryWordOp::new(1, MemoryAddress(0x100), Word::from(0x1234u64))
+            )
+        )
+    }
+
     #[test]
     fn mstore8_opcode_impl() {
         let code = bytecode! {
@@ -0,0 +1,307 @@
+use crate::{
+    circuit_input_builder::{
+        Call, CircuitInputStateRef, CopyDetails, EcrecoverEvent, ExecState, ExecStep,
+        StepAuxiliaryData,
+    },
+    constants::MAX_COPY_BYTES,
+    operation::{CallContextField, MemoryOp, RW},
+    Error,
+};
+use eth_types::{
+    evm_types::{Gas, GasCost, ProgramCounter},
+    Address, GethExecStep, Hash, ToBigEndian, ToWord, Word,
+};
+use ethers_core::types::Signature;
+
+/// Generator of the steps of a call to precompile, which is called after
+/// `CALL` has entered the precompile's context.
+pub(crate) type GenPrecompileSteps = fn(
+    &mut CircuitInputStateRef,
+    &[GethExecStep],
+    &Call,
+    u64,
+    [(CallContextField, Word); 5],
+) -> Result<Vec<ExecStep>, Error>;
+
+/// Generate the steps of a call to the identity precompile, which is called
+/// by `caller` and whose context is the current one. The first step restores
+/// the caller's context with `caller_state` saved by the `CALL`, and the
+/// following `CopyToMemory` steps copy the call data to the return data
+/// region in caller's memory.
+pub(crate) fn gen_identity_steps(
+    state: &mut CircuitInputStateRef,
+    geth_steps: &[GethExecStep],
+    caller: &Call,
+    callee_gas_left: u64,
+    caller_state: [(CallContextField, Word); 5],
+) -> Result<Vec<ExecStep>, Error> {
+    let call = state.call()?.clone();
+    let call_data = state.call_ctx()?.call_data.clone();
+
+    let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+        + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * ((call.call_data_length + 31) / 32);
+    let mut exec_step = new_precompile_step(
+        state,
+        &geth_steps[0],
+        ExecState::PrecompileIdentity,
+        callee_gas_left,
+        gas_cost,
+    )?;
+
+    for (field, value) in [
+        (CallContextField::CallerId, caller.call_id.into()),
+        (
+            CallContextField::CallDataOffset,
+            call.call_data_offset.into(),
+        ),
+        (
+            CallContextField::CallDataLength,
+            call.call_data_length.into(),
+        ),
+        (
+            CallContextField::ReturnDataOffset,
+            call.return_data_offset.into(),
+        ),
+        (
+            CallContextField::ReturnDataLength,
+            call.return_data_length.into(),
+        ),
+        (CallContextField::CalleeAddress, call.address.to_word()),
+    ] {
+        state.call_context_read(&mut exec_step, call.call_id, field, value);
+    }
+
+    // The call data is returned as is
+    gen_restore_context_ops(
+        state,
+        &mut exec_step,
+        &call,
+        caller,
+        caller_state,
+        call.call_data_length,
+    );
+    state.handle_return(&geth_steps[0])?;
+
+    // Copy min(cd_length, rd_length) bytes from call data to return data in
+    // caller's memory
+    let mut exec_steps = vec![exec_step];
+    exec_steps.extend(gen_copy_steps(
+        state,
+        &geth_steps[1],
+        caller.call_id,
+        call.call_data_offset,
+        call.call_data_offset + call.call_data_length,
+        call.return_data_offset,
+        &call_data[..call.call_data_length.min(call.return_data_length) as usize],
+    )?);
+
+    Ok(exec_steps)
+}
+
+/// Generate the steps of a call to the ecrecover precompile, which is called
+/// by `caller` and whose context is the current one. The recovered address is
+/// written to callee's memory as the 32 bytes return data, which is then
+/// copied to the return data region in caller's memory by the following
+/// `CopyToMemory` steps. The return data is empty if the recovery fails.
+pub(crate) fn gen_ecrecover_steps(
+    state: &mut CircuitInputStateRef,
+    geth_steps: &[GethExecStep],
+    caller: &Call,
+    callee_gas_left: u64,
+    caller_state: [(CallContextField, Word); 5],
+) -> Result<Vec<ExecStep>, Error> {
+    let call = state.call()?.clone();
+
+    // Input is right padded with zeros to 128 bytes
+    let mut input = state.call_ctx()?.call_data.clone();
+    input.resize(128, 0);
+    let [msg_hash, v, r, s] =
+        [0, 1, 2, 3].map(|idx| Word::from_big_endian(&input[32 * idx..32 * (idx + 1)]));
+    let recovered_address = ecrecover(msg_hash, v, r, s);
+    state.block.ecrecover_events.push(EcrecoverEvent {
+        call_id: call.call_id,
+        msg_hash,
+        v,
+        r,
+        s,
+        recovered_address,
+    });
+
+    let mut exec_step = new_precompile_step(
+        state,
+        &geth_steps[0],
+        ExecState::PrecompileEcrecover,
+        callee_gas_left,
+        GasCost::PRECOMPILE_ECRECOVER.as_u64(),
+    )?;
+
+    for (field, value) in [
+        (CallContextField::CallerId, caller.call_id.into()),
+        (
+            CallContextField::ReturnDataOffset,
+            call.return_data_offset.into(),
+        ),
+        (
+            CallContextField::ReturnDataLength,
+            call.return_data_length.into(),
+        ),
+    ] {
+        state.call_context_read(&mut exec_step, call.call_id, field, value);
+    }
+
+    let output = recovered_address
+        .map(|address| address.to_word().to_be_bytes().to_vec())
+        .unwrap_or_default();
+    gen_restore_context_ops(
+        state,
+        &mut exec_step,
+        &call,
+        caller,
+        caller_state,
+        output.len() as u64,
+    );
+    for (idx, byte) in output.iter().enumerate() {
+        state.memory_write(&mut exec_step, idx.into(), *byte)?;
+    }
+    state.handle_return(&geth_steps[0])?;
+
+    // Copy min(32, rd_length) bytes of the output to return data in caller's
+    // memory
+    let mut exec_steps = vec![exec_step];
+    exec_steps.extend(gen_copy_steps(
+        state,
+        &geth_steps[1],
+        call.call_id,
+        0,
+        output.len() as u64,
+        call.return_data_offset,
+        &output[..output.len().min(call.return_data_length as usize)],
+    )?);
+
+    Ok(exec_steps)
+}
+
+/// Recover the signer's address of `msg_hash` from the signature `(v, r, s)`
+/// as the ecrecover precompile does, which returns `None` if the signature is
+/// invalid.
+fn ecrecover(msg_hash: Word, v: Word, r: Word, s: Word) -> Option<Address> {
+    let secp256k1_n = Word::from_str_radix(
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .unwrap();
+    if !(v == 27.into() || v == 28.into())
+        || r.is_zero()
+        || r >= secp256k1_n
+        || s.is_zero()
+        || s >= secp256k1_n
+    {
+        return None;
+    }
+
+    // High s is allowed by the precompile, so use the equivalent signature
+    // with low s which recovers the same public key.
+    let (s, v) = if s > secp256k1_n / 2 {
+        (secp256k1_n - s, 55 - v.as_u64())
+    } else {
+        (s, v.as_u64())
+    };
+    Signature { r, s, v }
+        .recover(Hash::from(msg_hash.to_be_bytes()))
+        .ok()
+}
+
+/// Create the first step of a call to precompile, which is in callee's
+/// context.
+fn new_precompile_step(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+    exec_state: ExecState,
+    callee_gas_left: u64,
+    gas_cost: u64,
+) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_step(geth_step)?;
+    exec_step.exec_state = exec_state;
+    exec_step.pc = ProgramCounter(0);
+    exec_step.stack_size = 0;
+    exec_step.memory_size = 0;
+    exec_step.gas_left = Gas(callee_gas_left);
+    exec_step.gas_cost = GasCost(gas_cost);
+    Ok(exec_step)
+}
+
+/// Generate the operations to restore caller's call context and the call state
+/// saved by `CALL`, and to set the return data of the call, which is at offset
+/// 0 in callee's memory.
+fn gen_restore_context_ops(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    caller: &Call,
+    caller_state: [(CallContextField, Word); 5],
+    return_data_length: u64,
+) {
+    for (field, value) in [
+        (CallContextField::IsRoot, (caller.is_root as u64).into()),
+        (
+            CallContextField::IsCreate,
+            (caller.is_create() as u64).into(),
+        ),
+        (CallContextField::CodeSource, caller.code_hash.to_word()),
+    ]
+    .into_iter()
+    .chain(caller_state)
+    {
+        state.call_context_read(exec_step, caller.call_id, field, value);
+    }
+
+    for (field, value) in [
+        (CallContextField::LastCalleeId, call.call_id.into()),
+        (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+        (
+            CallContextField::LastCalleeReturnDataLength,
+            return_data_length.into(),
+        ),
+    ] {
+        state.call_context_write(exec_step, caller.call_id, field, value);
+    }
+}
+
+/// Generate the `CopyToMemory` steps in caller's context, which copy `bytes`
+/// from memory of call `src_id` at `src_addr` to current memory at
+/// `dst_addr`.
+pub(crate) fn gen_copy_steps(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+    src_id: usize,
+    src_addr: u64,
+    src_addr_end: u64,
+    dst_addr: u64,
+    bytes: &[u8],
+) -> Result<Vec<ExecStep>, Error> {
+    let mut exec_steps = Vec::new();
+    for (chunk_idx, chunk) in bytes.chunks(MAX_COPY_BYTES).enumerate() {
+        let copied = chunk_idx * MAX_COPY_BYTES;
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.exec_state = ExecState::CopyToMemory;
+        for (idx, byte) in chunk.iter().enumerate() {
+            let offset = copied + idx;
+            state.push_op(
+                &mut exec_step,
+                RW::READ,
+                MemoryOp::new(src_id, (src_addr as usize + offset).into(), *byte),
+            );
+            state.memory_write(&mut exec_step, (dst_addr as usize + offset).into(), *byte)?;
+        }
+        exec_step.aux_data = Some(StepAuxiliaryData::new(
+            src_addr + copied as u64,
+            dst_addr + copied as u64,
+            (bytes.len() - copied) as u64,
+            src_addr_end,
+            CopyDetails::Memory(src_id),
+        ));
+        exec_steps.push(exec_step);
+    }
+
+    Ok(exec_steps)
+}
@@ -0,0 +1,95 @@
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::Error;
+use eth_types::{evm_types::OpcodeId, GethExecStep, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the `OpcodeId::PUSH*` `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Push;
+
+impl Opcode for Push {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        // The pushed value is the immediate of the n bytes after the opcode,
+        // which are 0 past the end of the code.
+        let n = (geth_step.op.as_u8() - OpcodeId::PUSH1.as_u8() + 1) as usize;
+        let code = state.code(state.call()?.code_hash)?;
+        let pc = geth_step.pc.0;
+        let immediate: Vec<_> = (pc + 1..pc + 1 + n)
+            .map(|idx| code.get(idx).copied().unwrap_or_default())
+            .collect();
+        state.stack_write(
+            &mut exec_step,
+            geth_step.stack.last_filled().map(|a| a - 1),
+            Word::from_big_endian(&immediate),
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod push_tests {
+    use crate::{
+        circuit_input_builder::ExecState, mock::BlockData, operation::StackOp, operation::RW,
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        word,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn push_opcode_impl() {
+        let code = bytecode! {
+            PUSH1(0x1)
+            PUSH3(0x123456)
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let idx = steps
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::PUSH3))
+            .unwrap();
+        let step = &steps[idx];
+
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &StackOp::new(1, StackAddress::from(1022), word!("0x123456"))
+            )
+        );
+        // PUSH3 is followed by its 3 bytes immediate
+        assert_eq!(steps[idx + 1].pc.0, step.pc.0 + 4);
+    }
+}
@@ -0,0 +1,213 @@
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::operation::CallContextField;
+use crate::Error;
+use core::convert::TryInto;
+use eth_types::evm_types::{MemoryAddress, OpcodeId};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::RETURN`] and [`OpcodeId::REVERT`]
+/// `OpcodeId`s, which read the return data from memory and set it as the last
+/// callee's return data of the caller.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReturnRevert;
+
+impl Opcode for ReturnRevert {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        // The call's success comes from the trace, and it's what decides
+        // whether its operations get reverted, so it must agree with REVERT.
+        let call = state.call()?.clone();
+        if geth_step.op == OpcodeId::REVERT && call.is_success {
+            return Err(Error::InvalidGethExecStep(
+                "REVERT in a call which succeeds",
+                geth_step.clone(),
+            ));
+        }
+
+        // The offset is meaningless for empty return data, and may not fit a
+        // MemoryAddress.
+        let mut return_data = vec![];
+        if !length.is_zero() {
+            let offset: MemoryAddress = offset.try_into()?;
+            let length: MemoryAddress = length.try_into()?;
+            return_data = geth_step.memory.read_chunk(offset, length);
+            for (idx, byte) in return_data.iter().enumerate() {
+                state.memory_read(&mut exec_step, offset.map(|a| a + idx), *byte)?;
+            }
+        }
+
+        // Set the return data of the call for caller
+        if !call.is_root {
+            for (field, value) in [
+                (CallContextField::LastCalleeId, call.call_id.into()),
+                (CallContextField::LastCalleeReturnDataOffset, offset),
+                (CallContextField::LastCalleeReturnDataLength, length),
+            ] {
+                state.call_context_write(&mut exec_step, call.caller_id, field, value);
+            }
+            let caller_ctx = state.caller_ctx_mut()?;
+            caller_ctx.last_callee_id = call.call_id;
+            caller_ctx.last_callee_return_data_offset = offset;
+            caller_ctx.last_callee_return_data = return_data;
+        }
+
+        state.handle_return(geth_step)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod return_revert_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, MemoryOp, StackOp, Target, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        ToWord, Word,
+    };
+    use mock::{test_ctx::TestContext, MOCK_ACCOUNTS};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn revert_opcode_impl() {
+        let code_b = bytecode! {
+            PUSH4(0xdeadbeefu64)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x04) // length
+            PUSH1(0x1c) // offset
+            REVERT
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let (caller, callee) = (&tx.calls()[0], &tx.calls()[1]);
+        // The revert of the callee doesn't fail the caller
+        assert_eq!((caller.is_success, callee.is_success), (true, false));
+        assert!(!callee.is_persistent);
+
+        let steps = tx.steps();
+        let idx = steps
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::REVERT))
+            .unwrap();
+        let step = &steps[idx];
+
+        // REVERT reads the return data from the callee's memory
+        let return_data = [0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(
+            (2..6)
+                .map(|idx| {
+                    let operation =
+                        &builder.block.container.memory[step.bus_mapping_instance[idx].as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            return_data
+                .iter()
+                .enumerate()
+                .map(|(idx, byte)| (
+                    RW::READ,
+                    MemoryOp::new(callee.call_id, MemoryAddress::from(0x1c + idx), *byte)
+                ))
+                .collect::<Vec<_>>()
+        );
+
+        // And sets it as the return data of the callee for the caller
+        assert_eq!(
+            (6..9)
+                .map(|idx| {
+                    let operation = &builder.block.container.call_context
+                        [step.bus_mapping_instance[idx].as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [
+                (CallContextField::LastCalleeId, Word::from(callee.call_id)),
+                (
+                    CallContextField::LastCalleeReturnDataOffset,
+                    Word::from(0x1c)
+                ),
+                (
+                    CallContextField::LastCalleeReturnDataLength,
+                    Word::from(0x04)
+                ),
+            ]
+            .map(|(field, value)| (
+                RW::WRITE,
+                CallContextOp {
+                    call_id: caller.call_id,
+                    field,
+                    value,
+                }
+            ))
+            .to_vec()
+        );
+
+        // The caller sees the failure as the 0 pushed by CALL
+        let step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+        let pushed = step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == Target::Stack)
+            .map(|op_ref| &builder.block.container.stack[op_ref.as_usize()])
+            .find(|operation| operation.rw() == RW::WRITE)
+            .unwrap();
+        assert_eq!(
+            pushed.op(),
+            &StackOp::new(caller.call_id, StackAddress::from(1023), Word::zero())
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use super::{precompiles::gen_copy_steps, Opcode};
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{GethExecStep, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::RETURNDATACOPY`](crate::evm::OpcodeId::RETURNDATACOPY)
+/// `OpcodeId`, which copies the return data of the last callee from its memory
+/// with `CopyToMemory` steps, and fails with
+/// [`ExecError::ReturnDataOutOfBounds`] when reading past its end.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Returndatacopy;
+
+impl Opcode for Returndatacopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let memory_offset = geth_step.stack.nth_last(0)?;
+        let data_offset = geth_step.stack.nth_last(1)?;
+        let length = geth_step.stack.nth_last(2)?;
+        for (i, value) in [memory_offset, data_offset, length].into_iter().enumerate() {
+            state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(i), value)?;
+        }
+
+        let call_id = state.call()?.call_id;
+        let call_ctx = state.call_ctx()?;
+        let (last_callee_id, return_data_offset, return_data) = (
+            call_ctx.last_callee_id,
+            call_ctx.last_callee_return_data_offset,
+            call_ctx.last_callee_return_data.clone(),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::LastCalleeReturnDataLength,
+            Word::from(return_data.len()),
+        );
+
+        let is_out_of_bounds = data_offset
+            .checked_add(length)
+            .map_or(true, |end| end > Word::from(return_data.len()));
+        if is_out_of_bounds {
+            state.handle_error_return(
+                &mut exec_step,
+                geth_step,
+                ExecError::ReturnDataOutOfBounds,
+            )?;
+            return Ok(vec![exec_step]);
+        }
+
+        for (field, value) in [
+            (CallContextField::LastCalleeId, last_callee_id.into()),
+            (
+                CallContextField::LastCalleeReturnDataOffset,
+                return_data_offset,
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, call_id, field, value);
+        }
+
+        // Both are in bounds of the return data, so they fit in a usize, and
+        // the return data offset in callee's memory fits in a u64 unless
+        // there's nothing to copy.
+        let (data_offset, length) = (data_offset.as_usize(), length.as_usize());
+        let mut exec_steps = vec![exec_step];
+        if length != 0 {
+            let return_data_offset = return_data_offset.as_u64();
+            exec_steps.extend(gen_copy_steps(
+                state,
+                &geth_steps[1],
+                last_callee_id,
+                return_data_offset + data_offset as u64,
+                return_data_offset + return_data.len() as u64,
+                memory_offset.as_u64(),
+                &return_data[data_offset..data_offset + length],
+            )?);
+        }
+
+        Ok(exec_steps)
+    }
+}
+
+#[cfg(test)]
+mod returndatacopy_tests {
+    use crate::{
+        circuit_input_builder::{ExecState, ExecStep},
+        error::ExecError,
+        mock::BlockData,
+        operation::{MemoryOp, Target, RW},
+    };
+    use eth_types::{
+        bytecode, evm_types::OpcodeId, geth_types::GethData, word, ToBigEndian, ToWord, Word,
+    };
+    use mock::{test_ctx::TestContext, MOCK_ACCOUNTS};
+    use pretty_assertions::assert_eq;
+    use std::iter;
+
+    /// Return the RETURNDATACOPY step of the caller, which copies `length`
+    /// bytes of the 32 bytes returned by the callee from `data_offset`, the
+    /// memory operations done in it and its `CopyToMemory` steps, and the call
+    /// id of the callee.
+    fn returndatacopy_step(
+        data_offset: u64,
+        length: u64,
+    ) -> (ExecStep, Vec<(RW, MemoryOp)>, usize) {
+        let code_b = bytecode! {
+            PUSH32(word!("0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20) // length
+            PUSH1(0x00) // offset
+            RETURN
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH32(length)
+            PUSH32(data_offset)
+            PUSH32(0x40) // memory_offset
+            RETURNDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let idx = tx
+            .steps()
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::RETURNDATACOPY))
+            .unwrap();
+        let copy_steps = tx.steps()[idx + 1..]
+            .iter()
+            .take_while(|step| step.exec_state == ExecState::CopyToMemory);
+        let memory_ops = iter::once(&tx.steps()[idx])
+            .chain(copy_steps)
+            .flat_map(|step| step.bus_mapping_instance.iter())
+            .filter(|op_ref| op_ref.target() == Target::Memory)
+            .map(|op_ref| {
+                let operation = &builder.block.container.memory[op_ref.as_usize()];
+                (operation.rw(), operation.op().clone())
+            })
+            .collect();
+        (tx.steps()[idx].clone(), memory_ops, tx.calls()[1].call_id)
+    }
+
+    #[test]
+    fn returndatacopy_opcode_impl() {
+        let (step, memory_ops, callee_id) = returndatacopy_step(0x10, 0x10);
+
+        let return_data =
+            word!("0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")
+                .to_be_bytes();
+        assert_eq!(step.error, None);
+        // Each byte is read from the return data in callee's memory, and
+        // written to caller's memory
+        assert_eq!(
+            memory_ops,
+            return_data[0x10..0x20]
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, byte)| [
+                    (
+                        RW::READ,
+                        MemoryOp::new(callee_id, (0x10 + idx).into(), *byte)
+                    ),
+                    (RW::WRITE, MemoryOp::new(1, (0x40 + idx).into(), *byte))
+                ])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn returndatacopy_opcode_out_of_bounds() {
+        // Reads 0x10 bytes past the end of the return data
+        let (step, memory_ops, _) = returndatacopy_step(0x10, 0x20);
+
+        assert_eq!(step.error, Some(ExecError::ReturnDataOutOfBounds));
+        assert_eq!(memory_ops, vec![]);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::CallContextField,
+    Error,
+};
+
+use eth_types::GethExecStep;
+
+use super::Opcode;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::RETURNDATASIZE`](crate::evm::OpcodeId::RETURNDATASIZE)
+/// `OpcodeId`, which pushes the length of the return data set by the last
+/// callee.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Returndatasize;
+
+impl Opcode for Returndatasize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let value = geth_steps[1].stack.last()?;
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::LastCalleeReturnDataLength,
+            value,
+        );
+
+        state.stack_write(
+            &mut exec_step,
+            geth_step.stack.last_filled().map(|a| a - 1),
+            value,
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod returndatasize_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        ToWord, Word,
+    };
+    use mock::{test_ctx::TestContext, MOCK_ACCOUNTS};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn returndatasize_opcode_impl() {
+        let code_b = bytecode! {
+            PUSH1(0x40) // length
+            PUSH1(0x00) // offset
+            RETURN
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            RETURNDATASIZE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let (caller_id, callee_id) = (tx.calls()[0].call_id, tx.calls()[1].call_id);
+
+        // RETURN sets the return data of the callee for the caller, after the 2
+        // stack reads and the 0x40 memory reads of the return data
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::RETURN))
+            .unwrap();
+        assert_eq!(
+            (66..69)
+                .map(|idx| {
+                    let operation = &builder.block.container.call_context
+                        [step.bus_mapping_instance[idx].as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [
+                (CallContextField::LastCalleeId, Word::from(callee_id)),
+                (CallContextField::LastCalleeReturnDataOffset, Word::zero()),
+                (
+                    CallContextField::LastCalleeReturnDataLength,
+                    Word::from(0x40)
+                ),
+            ]
+            .map(|(field, value)| (
+                RW::WRITE,
+                CallContextOp {
+                    call_id: caller_id,
+                    field,
+                    value,
+                }
+            ))
+            .to_vec()
+        );
+
+        // RETURNDATASIZE reads it back in the caller's context
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::RETURNDATASIZE))
+            .unwrap();
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.call_context[step.bus_mapping_instance[0].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id: caller_id,
+                    field: CallContextField::LastCalleeReturnDataLength,
+                    value: Word::from(0x40),
+                }
+            )
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.stack[step.bus_mapping_instance[1].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::WRITE,
+                &StackOp::new(caller_id, StackAddress::from(1022), Word::from(0x40))
+            )
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::Error;
+use core::convert::TryInto;
+use eth_types::evm_types::MemoryAddress;
+use eth_types::{GethExecStep, Word};
+use ethers_core::utils::keccak256;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::SHA3`](crate::evm::OpcodeId::SHA3)
+/// `OpcodeId`. Its input is recorded in
+/// [`Block::sha3_inputs`](crate::circuit_input_builder::Block::sha3_inputs)
+/// for the keccak table.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Sha3;
+
+impl Opcode for Sha3 {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        // The offset is meaningless for an empty input, and may not fit a
+        // MemoryAddress.
+        let mut input = vec![];
+        if !length.is_zero() {
+            let offset: MemoryAddress = offset.try_into()?;
+            let length: MemoryAddress = length.try_into()?;
+            // Memory past its size before the step reads as 0
+            input = geth_step.memory.read_chunk(offset, length);
+            for (idx, byte) in input.iter().enumerate() {
+                state.memory_read(&mut exec_step, offset.map(|a| a + idx), *byte)?;
+            }
+        }
+
+        let hash = Word::from_big_endian(&keccak256(&input));
+        state.stack_write(&mut exec_step, geth_step.stack.nth_last_filled(1), hash)?;
+        state.block.sha3_inputs.push(input);
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod sha3_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{MemoryOp, StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sha3_opcode_impl() {
+        let code = bytecode! {
+            PUSH4(0xdeadbeefu64)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x04) // length
+            PUSH1(0x1c) // offset
+            SHA3
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SHA3))
+            .unwrap();
+
+        let input = vec![0xde, 0xad, 0xbe, 0xef];
+        let hash = Word::from_big_endian(&keccak256(&input));
+
+        assert_eq!(
+            [0, 1, 6]
+                .map(|idx| &builder.block.container.stack
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1022), Word::from(0x1c))
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1023), Word::from(0x04))
+                ),
+                (RW::WRITE, &StackOp::new(1, StackAddress::from(1023), hash)),
+            ]
+        );
+
+        assert_eq!(
+            (2..6)
+                .map(|idx| &builder.block.container.memory
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op().clone()))
+                .collect::<Vec<(RW, MemoryOp)>>(),
+            input
+                .iter()
+                .enumerate()
+                .map(|(idx, byte)| (
+                    RW::READ,
+                    MemoryOp::new(1, MemoryAddress::from(0x1c + idx), *byte)
+                ))
+                .collect::<Vec<(RW, MemoryOp)>>()
+        );
+
+        assert_eq!(builder.block.sha3_inputs, vec![input]);
+    }
+}
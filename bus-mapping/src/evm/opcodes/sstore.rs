@@ -6,7 +6,7 @@ use crate::{
     Error,
 };
 
-use eth_types::{GethExecStep, ToWord, Word};
+use eth_types::{evm_types::GasCost, GethExecStep, ToWord, Word};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::SSTORE`](crate::evm::OpcodeId::SSTORE)
@@ -100,13 +100,22 @@ impl Opcode for Sstore {
             },
         )?;
 
+        let tx_refund_prev = state.sdb.refund();
+        let tx_refund = geth_step.refund.0;
+        if Some(tx_refund) != sstore_tx_refund(tx_refund_prev, value, value_prev, committed_value) {
+            return Err(Error::InvalidGethExecStep(
+                "SSTORE refund doesn't match EIP-2200",
+                geth_step.clone(),
+            ));
+        }
+
         state.push_op_reversible(
             &mut exec_step,
             RW::WRITE,
             TxRefundOp {
                 tx_id: state.tx_ctx.id(),
-                value_prev: state.sdb.refund(),
-                value: geth_step.refund.0,
+                value_prev: tx_refund_prev,
+                value: tx_refund,
             },
         )?;
 
@@ -114,16 +123,52 @@ impl Opcode for Sstore {
     }
 }
 
+/// Calculate the tx refund after `SSTORE` writes `value` to a slot with
+/// current value `value_prev` and `original_value` at the beginning of the
+/// tx, as in [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27).
+/// Returns `None` if recreating a slot takes back more refund than
+/// `tx_refund_old`, which a consistent trace never does.
+fn sstore_tx_refund(
+    tx_refund_old: u64,
+    value: Word,
+    value_prev: Word,
+    original_value: Word,
+) -> Option<u64> {
+    let mut tx_refund = tx_refund_old;
+    if value_prev != value {
+        if !original_value.is_zero() {
+            if value_prev.is_zero() {
+                // recreate slot (2.2.1.1)
+                tx_refund = tx_refund.checked_sub(GasCost::SSTORE_CLEARS_SCHEDULE.as_u64())?;
+            }
+            if value.is_zero() {
+                // delete slot (2.2.1.2)
+                tx_refund += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64();
+            }
+        }
+        if original_value == value {
+            if original_value.is_zero() {
+                // reset to original inexistent slot (2.2.2.1)
+                tx_refund += GasCost::SSTORE_SET.as_u64() - GasCost::WARM_ACCESS.as_u64();
+            } else {
+                // reset to original existing slot (2.2.2.2)
+                tx_refund += GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64();
+            }
+        }
+    }
+    Some(tx_refund)
+}
+
 #[cfg(test)]
 mod sstore_tests {
     use super::*;
-    use crate::circuit_input_builder::ExecState;
+    use crate::circuit_input_builder::{ExecState, TransactionContext};
     use crate::mock::BlockData;
-    use crate::operation::{CallContextOp, StackOp};
+    use crate::operation::{CallContextOp, Op, OpEnum, StackOp, Target};
     use eth_types::bytecode;
-    use eth_types::evm_types::{OpcodeId, StackAddress};
+    use eth_types::evm_types::{GasCost, OpcodeId, StackAddress};
     use eth_types::geth_types::GethData;
-    use eth_types::Word;
+    use eth_types::{ToWord, Word};
     use mock::test_ctx::helpers::tx_from_1_to_0;
     use mock::{TestContext, MOCK_ACCOUNTS};
     use pretty_assertions::assert_eq;
@@ -265,6 +310,386 @@ mod sstore_tests {
         );
     }
 
+    fn clear_slot_block() -> GethData {
+        let code = bytecode! {
+            // Clear storage slot 0
+            PUSH1(0x00u64)
+            PUSH1(0x00u64)
+            SSTORE
+            STOP
+        };
+        TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(code)
+                    .storage(vec![(0x00u64.into(), 0x6fu64.into())].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn sstore_opcode_refund_clear_slot() {
+        let block = clear_slot_block();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SSTORE))
+            .unwrap();
+        let refund_op = &builder.block.container.tx_refund[step.bus_mapping_instance[9].as_usize()];
+        assert_eq!(
+            (refund_op.rw(), refund_op.op()),
+            (
+                RW::WRITE,
+                &TxRefundOp {
+                    tx_id: 1,
+                    value_prev: 0,
+                    value: GasCost::SSTORE_CLEARS_SCHEDULE.as_u64(),
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn sstore_opcode_refund_mismatch() {
+        let mut block = clear_slot_block();
+        let sstore = block.geth_traces[0]
+            .struct_logs
+            .iter_mut()
+            .find(|step| step.op == OpcodeId::SSTORE)
+            .unwrap();
+        sstore.refund.0 += 1;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::InvalidGethExecStep(..))
+        ));
+    }
+
+    #[test]
+    fn sstore_tx_refund_recreate_slot_underflow() {
+        let clears = GasCost::SSTORE_CLEARS_SCHEDULE.as_u64();
+        // Recreating a slot cleared earlier in the tx takes its refund back
+        assert_eq!(
+            sstore_tx_refund(clears, Word::one(), Word::zero(), Word::from(2)),
+            Some(0)
+        );
+        // Without that refund the trace is inconsistent
+        assert_eq!(
+            sstore_tx_refund(clears - 1, Word::one(), Word::zero(), Word::from(2)),
+            None
+        );
+    }
+
+    #[test]
+    fn sstore_opcode_committed_value_is_original() {
+        let code = bytecode! {
+            // Write 0x01 to storage slot 0
+            PUSH1(0x01u64)
+            PUSH1(0x00u64)
+            SSTORE
+            // Write 0x02 to storage slot 0
+            PUSH1(0x02u64)
+            PUSH1(0x00u64)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(code)
+                    .storage(vec![(0x00u64.into(), 0x6fu64.into())].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // The committed value is the one at the beginning of the tx for both
+        // SSTOREs, while the previous value is the one written by the first
+        // SSTORE for the second one.
+        let storage_ops = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(OpcodeId::SSTORE))
+            .map(|step| {
+                builder.block.container.storage[step.bus_mapping_instance[7].as_usize()]
+                    .op()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            storage_ops,
+            vec![
+                StorageOp::new(
+                    MOCK_ACCOUNTS[0],
+                    Word::zero(),
+                    Word::from(0x01),
+                    Word::from(0x6f),
+                    1,
+                    Word::from(0x6f),
+                ),
+                StorageOp::new(
+                    MOCK_ACCOUNTS[0],
+                    Word::zero(),
+                    Word::from(0x02),
+                    Word::from(0x01),
+                    1,
+                    Word::from(0x6f),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn sstore_opcode_warms_slot_for_sload() {
+        let code = bytecode! {
+            // Write 0x6f to storage slot 0
+            PUSH1(0x6fu64)
+            PUSH1(0x00u64)
+            SSTORE
+            // Read storage slot 0
+            PUSH1(0x00u64)
+            SLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(code);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // SSTORE accesses the cold slot, after which SLOAD accesses it warm
+        let access_list_ops = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| {
+                step.exec_state == ExecState::Op(OpcodeId::SSTORE)
+                    || step.exec_state == ExecState::Op(OpcodeId::SLOAD)
+            })
+            .map(|step| {
+                let op_ref = step
+                    .bus_mapping_instance
+                    .iter()
+                    .find(|op_ref| op_ref.target() == Target::TxAccessListAccountStorage)
+                    .unwrap();
+                builder.block.container.tx_access_list_account_storage[op_ref.as_usize()]
+                    .op()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            access_list_ops,
+            [false, true]
+                .map(|is_warm_prev| TxAccessListAccountStorageOp {
+                    tx_id: 1,
+                    address: MOCK_ACCOUNTS[0],
+                    key: Word::zero(),
+                    is_warm: true,
+                    is_warm_prev,
+                })
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn sstore_opcode_reverted_call_restores_storage() {
+        let code_b = bytecode! {
+            // Write 0x2a to storage slot 0, and then revert
+            PUSH1(0x2au64)
+            PUSH1(0x00u64)
+            SSTORE
+            PUSH1(0x00) // length
+            PUSH1(0x00) // offset
+            REVERT
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            STOP
+        };
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        // The reversion of the callee is checked to restore the state written
+        // over by each of its reversible ops
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // The reversions are done in the step of the reverted write
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SSTORE))
+            .unwrap();
+        let storage_write = StorageOp::new(
+            MOCK_ACCOUNTS[1],
+            Word::zero(),
+            Word::from(0x2a),
+            Word::zero(),
+            1,
+            Word::zero(),
+        );
+        let access_list_write = TxAccessListAccountStorageOp {
+            tx_id: 1,
+            address: MOCK_ACCOUNTS[1],
+            key: Word::zero(),
+            is_warm: true,
+            is_warm_prev: false,
+        };
+        assert_eq!(
+            step.bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.target() == Target::Storage)
+                .map(|op_ref| &builder.block.container.storage[op_ref.as_usize()])
+                .filter(|operation| operation.rw().is_write())
+                .map(|operation| (operation.reversible(), operation.op().clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (true, storage_write.clone()),
+                (false, storage_write.reverse())
+            ]
+        );
+        assert_eq!(
+            step.bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.target() == Target::TxAccessListAccountStorage)
+                .map(|op_ref| {
+                    &builder.block.container.tx_access_list_account_storage[op_ref.as_usize()]
+                })
+                .filter(|operation| operation.rw().is_write())
+                .map(|operation| (operation.reversible(), operation.op().clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (true, access_list_write.clone()),
+                (false, access_list_write.reverse())
+            ]
+        );
+
+        // And leave the slot as before the call
+        assert_eq!(
+            builder.sdb.get_storage(&MOCK_ACCOUNTS[1], &Word::zero()).1,
+            &Word::zero()
+        );
+    }
+
+    #[test]
+    fn sstore_opcode_reversion_over_untracked_write_is_inconsistent() {
+        let code = bytecode! {
+            // Write 0x2a to storage slot 0, and then revert the tx
+            PUSH1(0x2au64)
+            PUSH1(0x00u64)
+            SSTORE
+            PUSH1(0x00) // length
+            PUSH1(0x00) // offset
+            REVERT
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+
+        let eth_tx = &block.eth_block.transactions[0];
+        let geth_trace = &block.geth_traces[0];
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let mut tx = builder.new_tx(eth_tx, !geth_trace.failed).unwrap();
+        let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace, true).unwrap();
+        let mut state = builder.state_ref(&mut tx, &mut tx_ctx);
+
+        let sstore_step = geth_trace
+            .struct_logs
+            .iter()
+            .find(|step| step.op == OpcodeId::SSTORE)
+            .unwrap();
+        let mut exec_step = state.new_step(sstore_step).unwrap();
+        let storage_write = StorageOp::new(
+            MOCK_ACCOUNTS[0],
+            Word::zero(),
+            Word::from(0x2a),
+            Word::zero(),
+            1,
+            Word::zero(),
+        );
+        state
+            .push_op_reversible(&mut exec_step, RW::WRITE, storage_write.clone())
+            .unwrap();
+
+        // The slot is overwritten without an op, so reverting the write of
+        // 0x2a doesn't restore the state before it
+        state
+            .sdb
+            .set_storage(&MOCK_ACCOUNTS[0], &Word::zero(), &Word::from(0x2b));
+        let revert_step = geth_trace.struct_logs.last().unwrap();
+        assert!(matches!(
+            state.handle_return(revert_step),
+            Err(Error::InconsistentReversion(OpEnum::Storage(op))) if op == storage_write.reverse()
+        ));
+    }
+
     #[test]
     fn sstore_opcode_impl_warm() {
         test_ok(true)
@@ -1,14 +1,16 @@
 use super::Opcode;
 use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::operation::CallContextField;
 use crate::Error;
-use eth_types::GethExecStep;
+use eth_types::{GethExecStep, Word};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::STOP`](crate::evm::OpcodeId::STOP)
 /// `OpcodeId`. This is responsible of generating all of the associated
 /// operations and place them inside the trace's
 /// [`OperationContainer`](crate::operation::OperationContainer). In the case of
-/// STOP, it simply does not add anything.
+/// STOP, it only sets the (empty) return data of an internal call for its
+/// caller.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct Stop;
 
@@ -18,7 +20,24 @@ impl Opcode for Stop {
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
-        let exec_step = state.new_step(geth_step)?;
+        let mut exec_step = state.new_step(geth_step)?;
+
+        // Set the return data of the call for caller, which is empty for STOP
+        let call = state.call()?.clone();
+        if !call.is_root {
+            for (field, value) in [
+                (CallContextField::LastCalleeId, call.call_id.into()),
+                (CallContextField::LastCalleeReturnDataOffset, Word::zero()),
+                (CallContextField::LastCalleeReturnDataLength, Word::zero()),
+            ] {
+                state.call_context_write(&mut exec_step, call.caller_id, field, value);
+            }
+            let caller_ctx = state.caller_ctx_mut()?;
+            caller_ctx.last_callee_id = call.call_id;
+            caller_ctx.last_callee_return_data_offset = Word::zero();
+            caller_ctx.last_callee_return_data.clear();
+        }
+
         state.handle_return(geth_step)?;
         Ok(vec![exec_step])
     }
@@ -14,6 +14,7 @@ impl fmt::Debug for OperationRef {
             "OperationRef{{ {}, {} }}",
             match self.0 {
                 Target::Memory => "Memory",
+                Target::MemoryWord => "MemoryWord",
                 Target::Stack => "Stack",
                 Target::Storage => "Storage",
                 Target::TxAccessListAccount => "TxAccessListAccount",
@@ -23,6 +24,7 @@ impl fmt::Debug for OperationRef {
                 Target::AccountDestructed => "AccountDestructed",
                 Target::CallContext => "CallContext",
                 Target::TxReceipt => "TxReceipt",
+                Target::TxLog => "TxLog",
             },
             self.1
         ))
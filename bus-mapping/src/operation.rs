@@ -12,7 +12,7 @@ pub use eth_types::evm_types::{MemoryAddress, StackAddress};
 use core::cmp::Ordering;
 use core::fmt;
 use core::fmt::Debug;
-use eth_types::{Address, Word};
+use eth_types::{Address, ToBigEndian, Word};
 use std::mem::swap;
 
 /// Marker that defines whether an Operation performs a `READ` or a `WRITE`.
@@ -90,6 +90,8 @@ impl RWCounter {
 pub enum Target {
     /// Means the target of the operation is the Memory.
     Memory,
+    /// Means the target of the operation is a word of the Memory.
+    MemoryWord,
     /// Means the target of the operation is the Stack.
     Stack,
     /// Means the target of the operation is the Storage.
@@ -108,6 +110,8 @@ pub enum Target {
     CallContext,
     /// Means the target of the operation is the TxReceipt.
     TxReceipt,
+    /// Means the target of the operation is the TxLog.
+    TxLog,
 }
 
 /// Trait used for Operation Kinds.
@@ -197,6 +201,113 @@ impl Ord for MemoryOp {
     }
 }
 
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) of a 32 bytes word
+/// into the memory, generated instead of 32 [`MemoryOp`]s when the
+/// [`CircuitInputBuilder`](crate::circuit_input_builder::CircuitInputBuilder)
+/// uses [`MemoryOpGranularity::Word`](crate::circuit_input_builder::
+/// MemoryOpGranularity::Word).
+#[derive(Clone, PartialEq, Eq)]
+pub struct MemoryWordOp {
+    /// Call ID
+    pub call_id: usize,
+    /// Memory Address of the first byte of the word
+    pub address: MemoryAddress,
+    /// Value
+    pub value: Word,
+}
+
+impl fmt::Debug for MemoryWordOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MemoryWordOp { ")?;
+        f.write_fmt(format_args!(
+            "call_id: {:?}, addr: {:?}, value: {:?}",
+            self.call_id, self.address, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl MemoryWordOp {
+    /// Create a new instance of a `MemoryWordOp` from it's components.
+    pub fn new(call_id: usize, address: MemoryAddress, value: Word) -> MemoryWordOp {
+        MemoryWordOp {
+            call_id,
+            address,
+            value,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::MemoryWord
+    }
+
+    /// Returns the call id associated to this Operation.
+    pub const fn call_id(&self) -> usize {
+        self.call_id
+    }
+
+    /// Returns the [`MemoryAddress`] associated to this Operation.
+    pub const fn address(&self) -> &MemoryAddress {
+        &self.address
+    }
+
+    /// Returns the word read or written by this operation.
+    pub fn value(&self) -> &Word {
+        &self.value
+    }
+
+    /// Returns the [`MemoryOp`]s of the bytes of the word, as generated for it
+    /// with [`MemoryOpGranularity::Byte`](crate::circuit_input_builder::
+    /// MemoryOpGranularity::Byte), i.e. in big-endian order from the address.
+    pub fn memory_ops(&self) -> Vec<MemoryOp> {
+        self.value
+            .to_be_bytes()
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| MemoryOp::new(self.call_id, self.address.map(|a| a + idx), *byte))
+            .collect()
+    }
+
+    /// Returns the `MemoryWordOp` of the word made of the bytes of
+    /// `memory_ops`, which is the inverse of [`Self::memory_ops`], or `None`
+    /// when they aren't the 32 bytes of a word in the memory of one call.
+    pub fn from_memory_ops(memory_ops: &[MemoryOp]) -> Option<MemoryWordOp> {
+        let first = memory_ops.first()?;
+        let is_word = memory_ops.len() == 32
+            && memory_ops.iter().enumerate().all(|(idx, memory_op)| {
+                memory_op.call_id == first.call_id
+                    && memory_op.address == first.address.map(|a| a + idx)
+            });
+        is_word.then(|| {
+            let bytes: Vec<u8> = memory_ops.iter().map(MemoryOp::value).collect();
+            MemoryWordOp::new(first.call_id, first.address, Word::from_big_endian(&bytes))
+        })
+    }
+}
+
+impl Op for MemoryWordOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::MemoryWord(self)
+    }
+
+    fn reverse(&self) -> Self {
+        unreachable!("MemoryWordOp can't be reverted")
+    }
+}
+
+impl PartialOrd for MemoryWordOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MemoryWordOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.call_id, &self.address).cmp(&(&other.call_id, &other.address))
+    }
+}
+
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the stack implied
 /// by an specific [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
 /// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
@@ -836,6 +947,72 @@ impl Op for TxReceiptOp {
     }
 }
 
+/// Represents a field parameter of the TxLog that can be written by the LOG
+/// opcodes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TxLogField {
+    /// contract address of the log
+    Address,
+    /// topic of the log
+    Topic,
+    /// data byte of the log
+    Data,
+}
+
+/// Represents TxLog write operation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TxLogOp {
+    /// tx_id of TxLog
+    pub tx_id: usize,
+    /// id of the log in the tx, which starts from 1
+    pub log_id: usize,
+    /// field of TxLog
+    pub field: TxLogField,
+    /// topic index if field is Topic, byte index if field is Data, 0
+    /// otherwise
+    pub index: usize,
+    /// value of TxLog
+    pub value: Word,
+}
+
+impl fmt::Debug for TxLogOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TxLogOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, log_id: {:?}, field: {:?}, index: {:?}, value: {:?}",
+            self.tx_id, self.log_id, self.field, self.index, self.value,
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialOrd for TxLogOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxLogOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.log_id, &self.field, &self.index).cmp(&(
+            &other.tx_id,
+            &other.log_id,
+            &other.field,
+            &other.index,
+        ))
+    }
+}
+
+impl Op for TxLogOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxLog(self)
+    }
+
+    fn reverse(&self) -> Self {
+        unreachable!("TxLogOp can't be reverted")
+    }
+}
+
 /// Generic enum that wraps over all the operation types possible.
 /// In particular [`StackOp`], [`MemoryOp`] and [`StorageOp`].
 #[derive(Debug, Clone)]
@@ -844,6 +1021,8 @@ pub enum OpEnum {
     Stack(StackOp),
     /// Memory
     Memory(MemoryOp),
+    /// MemoryWord
+    MemoryWord(MemoryWordOp),
     /// Storage
     Storage(StorageOp),
     /// TxAccessListAccount
@@ -860,6 +1039,8 @@ pub enum OpEnum {
     CallContext(CallContextOp),
     /// TxReceipt
     TxReceipt(TxReceiptOp),
+    /// TxLog
+    TxLog(TxLogOp),
 }
 
 /// Operation is a Wrapper over a type that implements Op with a RWCounter.
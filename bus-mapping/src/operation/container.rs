@@ -1,7 +1,7 @@
 use super::{
-    AccountDestructedOp, AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter,
-    StackOp, StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxReceiptOp,
-    TxRefundOp, RW,
+    AccountDestructedOp, AccountOp, CallContextOp, MemoryOp, MemoryWordOp, Op, OpEnum, Operation,
+    RWCounter, StackOp, StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp,
+    TxLogOp, TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
@@ -24,6 +24,8 @@ use itertools::Itertools;
 pub struct OperationContainer {
     /// Operations of MemoryOp
     pub memory: Vec<Operation<MemoryOp>>,
+    /// Operations of MemoryWordOp
+    pub memory_word: Vec<Operation<MemoryWordOp>>,
     /// Operations of StackOp
     pub stack: Vec<Operation<StackOp>>,
     /// Operations of StorageOp
@@ -42,6 +44,8 @@ pub struct OperationContainer {
     pub call_context: Vec<Operation<CallContextOp>>,
     /// Operations of TxReceiptOp
     pub tx_receipt: Vec<Operation<TxReceiptOp>>,
+    /// Operations of TxLogOp
+    pub tx_log: Vec<Operation<TxLogOp>>,
 }
 
 impl Default for OperationContainer {
@@ -56,6 +60,7 @@ impl OperationContainer {
     pub fn new() -> Self {
         Self {
             memory: Vec::new(),
+            memory_word: Vec::new(),
             stack: Vec::new(),
             storage: Vec::new(),
             tx_access_list_account: Vec::new(),
@@ -65,6 +70,7 @@ impl OperationContainer {
             account_destructed: Vec::new(),
             call_context: Vec::new(),
             tx_receipt: Vec::new(),
+            tx_log: Vec::new(),
         }
     }
 
@@ -95,6 +101,10 @@ impl OperationContainer {
                 self.memory.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Memory, self.memory.len() - 1))
             }
+            OpEnum::MemoryWord(op) => {
+                self.memory_word.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::MemoryWord, self.memory_word.len() - 1))
+            }
             OpEnum::Stack(op) => {
                 self.stack.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Stack, self.stack.len() - 1))
@@ -161,6 +171,10 @@ impl OperationContainer {
                 self.tx_receipt.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::TxReceipt, self.tx_receipt.len() - 1))
             }
+            OpEnum::TxLog(op) => {
+                self.tx_log.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::TxLog, self.tx_log.len() - 1))
+            }
         }
     }
 
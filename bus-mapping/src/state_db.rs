@@ -230,6 +230,17 @@ impl StateDB {
         self.destructed_account.insert(addr);
     }
 
+    /// Check whether `addr` has been set as self destructed.
+    pub fn check_account_destructed(&self, addr: &Address) -> bool {
+        self.destructed_account.contains(addr)
+    }
+
+    /// Remove `addr` from the self destructed accounts.
+    pub fn remove_destructed_account(&mut self, addr: &Address) {
+        let exist = self.destructed_account.remove(addr);
+        debug_assert!(exist);
+    }
+
     /// Retrieve refund.
     pub fn refund(&self) -> u64 {
         self.refund
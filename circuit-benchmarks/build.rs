@@ -11,8 +11,16 @@ fn main() {
         .parse()
         .expect("Cannot parse DEGREE env var as usize");
 
+    let rw_set_size: usize = var("RW_SET_SIZE")
+        .unwrap_or_else(|_| "1000".to_string())
+        .parse()
+        .expect("Cannot parse RW_SET_SIZE env var as usize");
+
     // Add state_circuit module to `lib.rs`
-    let consts = format!("pub(crate) const DEGREE: usize = {};\n", degree);
+    let consts = format!(
+        "pub(crate) const DEGREE: usize = {};\npub(crate) const RW_SET_SIZE: usize = {};\n",
+        degree, rw_set_size
+    );
 
     let mut state_file =
         File::create("src/bench_params.rs").expect("Error generating bench_params.rs file");
@@ -0,0 +1,182 @@
+//! Helpers to store the setup artifacts of the benchmarks on disk, so that
+//! repeated runs can skip the parameter generation.
+
+use halo2_proofs::{
+    pairing::bn256::{Bn256, G1Affine},
+    poly::commitment::Params,
+};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Write the polynomial commitment parameters to `path`.
+pub fn write_params(path: impl AsRef<Path>, params: &Params<G1Affine>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    params.write(&mut writer)?;
+    writer.flush()
+}
+
+/// Read the polynomial commitment parameters from `path`.
+pub fn read_params(path: impl AsRef<Path>) -> io::Result<Params<G1Affine>> {
+    Params::read(&mut BufReader::new(File::open(path)?))
+}
+
+/// Path of the parameters of `degree` shared by the runs of the benchmarks,
+/// in the temporary directory.
+pub fn params_path(degree: u32) -> PathBuf {
+    std::env::temp_dir().join(format!("circuit-benchmarks-params-{}", degree))
+}
+
+/// Path in the temporary directory unique to the current process, for the
+/// artifacts that aren't shared between runs.
+pub fn process_temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "circuit-benchmarks-{}-{}",
+        name,
+        std::process::id()
+    ))
+}
+
+/// Read the parameters from `path` if it exists, otherwise generate them with
+/// `degree` and write them to `path` for the next run. The caller should use
+/// a different `path` for each degree, e.g. [`params_path`]. The parameters
+/// are written to a file of the current process first and then renamed to
+/// `path`, so concurrent runs never read a partially written file.
+pub fn load_or_setup_params(path: impl AsRef<Path>, degree: u32) -> io::Result<Params<G1Affine>> {
+    let path = path.as_ref();
+    if path.exists() {
+        return read_params(path);
+    }
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(degree);
+    let tmp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+    write_params(&tmp_path, &params)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(params)
+}
+
+/// Write a serialized proof to `path`.
+pub fn write_proof(path: impl AsRef<Path>, proof: &[u8]) -> io::Result<()> {
+    File::create(path)?.write_all(proof)
+}
+
+/// Read a serialized proof from `path`.
+pub fn read_proof(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let mut proof = Vec::new();
+    File::open(path)?.read_to_end(&mut proof)?;
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        pairing::bn256::Fr,
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+            ConstraintSystem, Error, Selector, SingleVerifier,
+        },
+        poly::Rotation,
+        transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+    };
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn params_and_proof_round_trip() {
+        let degree = 4;
+        let params_path = process_temp_path("params-round-trip");
+        let proof_path = process_temp_path("proof-round-trip");
+
+        let _ = std::fs::remove_file(&params_path);
+        let params = load_or_setup_params(&params_path, degree).unwrap();
+        let cached = load_or_setup_params(&params_path, degree).unwrap();
+        let [mut bytes, mut cached_bytes] = [vec![], vec![]];
+        params.write(&mut bytes).unwrap();
+        cached.write(&mut cached_bytes).unwrap();
+        assert_eq!(bytes, cached_bytes);
+        std::fs::remove_file(&params_path).unwrap();
+
+        // A proof created with the params verifies with the cached params
+        // after a round trip through the disk
+        let circuit = SquareCircuit { a: Fr::from(3) };
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(&params, &pk, &[circuit], &[&[]], rng, &mut transcript).unwrap();
+        let proof = transcript.finalize();
+
+        write_proof(&proof_path, &proof).unwrap();
+        let read = read_proof(&proof_path).unwrap();
+        std::fs::remove_file(&proof_path).unwrap();
+        assert_eq!(read, proof);
+
+        let verifier_params = cached.verifier(degree as usize * 2).unwrap();
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&read[..]);
+        assert!(verify_proof(
+            &verifier_params,
+            pk.get_vk(),
+            SingleVerifier::new(&verifier_params),
+            &[&[]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    #[derive(Clone, Copy)]
+    struct SquareConfig {
+        q: Selector,
+        a: Column<Advice>,
+        b: Column<Advice>,
+    }
+
+    /// Circuit proving the knowledge of `a` with `a * a = b`.
+    #[derive(Default)]
+    struct SquareCircuit {
+        a: Fr,
+    }
+
+    impl Circuit<Fr> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let config = SquareConfig {
+                q: meta.selector(),
+                a: meta.advice_column(),
+                b: meta.advice_column(),
+            };
+            meta.create_gate("a * a = b", |meta| {
+                let q = meta.query_selector(config.q);
+                let a = meta.query_advice(config.a, Rotation::cur());
+                let b = meta.query_advice(config.b, Rotation::cur());
+                vec![q * (a.clone() * a - b)]
+            });
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Ok(self.a))?;
+                    region.assign_advice(|| "b", config.b, 0, || Ok(self.a * self.a))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+}
@@ -5,7 +5,12 @@ use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
     plonk::{Circuit, ConstraintSystem, Error, Expression},
 };
-use zkevm_circuits::evm_circuit::{witness::Block, EvmCircuit};
+use zkevm_circuits::{
+    block_table::BlockTable,
+    bytecode_table::BytecodeTable,
+    evm_circuit::{witness::Block, EvmCircuit},
+    rw_table::RwTable,
+};
 
 #[derive(Debug, Default)]
 pub struct TestCircuit<F> {
@@ -22,9 +27,10 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let tx_table = [(); 4].map(|_| meta.advice_column());
-        let rw_table = [(); 11].map(|_| meta.advice_column());
-        let bytecode_table = [(); 5].map(|_| meta.advice_column());
-        let block_table = [(); 3].map(|_| meta.advice_column());
+        let rw_table = RwTable::construct(meta);
+        let bytecode_table = BytecodeTable::construct(meta);
+        let block_table = BlockTable::construct(meta);
+        let ecrecover_table = [(); 5].map(|_| meta.advice_column());
         // Use constant expression to mock constant instance column for a more
         // reasonable benchmark.
         let power_of_randomness = [(); 31].map(|_| Expression::Constant(F::one()));
@@ -36,6 +42,7 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
             &rw_table,
             &bytecode_table,
             &block_table,
+            &ecrecover_table,
         )
     }
 
@@ -52,6 +59,9 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
 #[cfg(test)]
 mod evm_circ_benches {
     use super::*;
+    use crate::artifacts::{
+        load_or_setup_params, params_path, process_temp_path, read_proof, write_proof,
+    };
     use crate::bench_params::DEGREE;
     use ark_std::{end_timer, start_timer};
     use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, SingleVerifier};
@@ -78,10 +88,12 @@ mod evm_circ_benches {
             0xbc, 0xe5,
         ]);
 
-        // Bench setup generation
+        // Bench setup generation, which is skipped when a previous run has
+        // stored the params of the degree
         let setup_message = format!("Setup generation with degree = {}", degree);
         let start1 = start_timer!(|| setup_message);
-        let general_params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(degree);
+        let general_params: Params<G1Affine> =
+            load_or_setup_params(params_path(degree), degree).unwrap();
         end_timer!(start1);
 
         let vk = keygen_vk(&general_params, &circuit).unwrap();
@@ -102,8 +114,11 @@ mod evm_circ_benches {
             &mut transcript,
         )
         .unwrap();
-        let proof = transcript.finalize();
+        let proof_path = process_temp_path("evm-circuit-proof");
+        write_proof(&proof_path, &transcript.finalize()).unwrap();
         end_timer!(start2);
+        let proof = read_proof(&proof_path).unwrap();
+        std::fs::remove_file(&proof_path).unwrap();
 
         // Verify
         let verifier_params: ParamsVerifier<Bn256> = general_params.verifier(DEGREE * 2).unwrap();
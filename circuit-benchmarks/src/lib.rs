@@ -1,3 +1,6 @@
+#[cfg(feature = "benches")]
+pub mod artifacts;
+
 #[cfg(feature = "benches")]
 pub mod evm_circuit;
 
@@ -2,22 +2,48 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::bench_params::DEGREE;
+    use crate::artifacts::{
+        load_or_setup_params, params_path, process_temp_path, read_proof, write_proof,
+    };
+    use crate::bench_params::{DEGREE, RW_SET_SIZE};
     use ark_std::{end_timer, start_timer};
+    use bus_mapping::operation::{MemoryOp, Operation, OperationContainer, RWCounter, RW};
+    use eth_types::evm_types::MemoryAddress;
     use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, SingleVerifier};
     use halo2_proofs::{
+        arithmetic::BaseExt,
         pairing::bn256::{Bn256, Fr, G1Affine},
         poly::commitment::{Params, ParamsVerifier},
         transcript::{Blake2bRead, Blake2bWrite, Challenge255},
     };
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
-    use zkevm_circuits::state_circuit::StateCircuit;
+    use zkevm_circuits::{evm_circuit::witness::RwMap, state_circuit::StateCircuit};
+
+    /// Build a `RwMap` of `size` memory operations, which alternately write
+    /// and read back a byte at increasing addresses.
+    fn memory_rw_map(size: usize) -> RwMap {
+        let memory = (0..size)
+            .map(|idx| {
+                Operation::new(
+                    RWCounter::from(idx + 1),
+                    if idx % 2 == 0 { RW::WRITE } else { RW::READ },
+                    MemoryOp::new(1, MemoryAddress::from(idx / 2), (idx / 2) as u8),
+                )
+            })
+            .collect();
+        RwMap::from(&OperationContainer {
+            memory,
+            ..Default::default()
+        })
+    }
 
     #[cfg_attr(not(feature = "benches"), ignore)]
     #[test]
     fn bench_state_circuit_prover() {
-        let empty_circuit = StateCircuit::<Fr>::default();
+        let circuit = StateCircuit::<Fr>::new(Fr::rand(), memory_rw_map(RW_SET_SIZE));
+        let instance = circuit.instance();
+        let instance = instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
 
         // Initialize the polynomial commitment parameters
         let rng = XorShiftRng::from_seed([
@@ -25,34 +51,46 @@ mod tests {
             0xbc, 0xe5,
         ]);
 
-        // Bench setup generation
-        let setup_message = format!("Setup generation with degree = {}", DEGREE);
+        // Bench setup generation, which is skipped when a previous run has
+        // stored the params of the degree
+        let setup_message = format!(
+            "Setup generation with degree = {} and {} rw rows",
+            DEGREE, RW_SET_SIZE
+        );
         let start1 = start_timer!(|| setup_message);
+        let degree = DEGREE.try_into().unwrap();
         let general_params: Params<G1Affine> =
-            Params::<G1Affine>::unsafe_setup::<Bn256>(DEGREE.try_into().unwrap());
+            load_or_setup_params(params_path(degree), degree).unwrap();
         let verifier_params: ParamsVerifier<Bn256> = general_params.verifier(DEGREE * 2).unwrap();
         end_timer!(start1);
 
         // Initialize the proving key
-        let vk = keygen_vk(&general_params, &empty_circuit).expect("keygen_vk should not fail");
-        let pk = keygen_pk(&general_params, vk, &empty_circuit).expect("keygen_pk should not fail");
+        // The selector is assigned for the rows, so the keys depend on the rw set
+        let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
         // Create a proof
         let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
 
         // Bench proof generation time
-        let proof_message = format!("State Proof generation with {} degree", DEGREE);
+        let proof_message = format!(
+            "State Proof generation with {} degree and {} rw rows",
+            DEGREE, RW_SET_SIZE
+        );
         let start2 = start_timer!(|| proof_message);
         create_proof(
             &general_params,
             &pk,
-            &[empty_circuit],
-            &[&[]],
+            &[circuit],
+            &[&instance[..]],
             rng,
             &mut transcript,
         )
         .expect("proof generation should not fail");
-        let proof = transcript.finalize();
+        let proof_path = process_temp_path("state-circuit-proof");
+        write_proof(&proof_path, &transcript.finalize()).unwrap();
         end_timer!(start2);
+        let proof = read_proof(&proof_path).unwrap();
+        std::fs::remove_file(&proof_path).unwrap();
 
         // Bench verification time
         let start3 = start_timer!(|| "State Proof verification");
@@ -63,7 +101,7 @@ mod tests {
             &verifier_params,
             pk.get_vk(),
             strategy,
-            &[&[]],
+            &[&instance[..]],
             &mut verifier_transcript,
         )
         .expect("failed to verify bench circuit");
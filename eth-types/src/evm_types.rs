@@ -67,6 +67,43 @@ pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
 
+/// Hard fork of the block, which decides its gas rules and the behaviour of
+/// some opcodes. The circuits follow London and the forks after it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum HardFork {
+    /// London, with BASEFEE (EIP-3198) and the refund cap of EIP-3529
+    London,
+    /// The Merge, where DIFFICULTY returns PREVRANDAO (EIP-4399)
+    Merge,
+    /// Shanghai, where the coinbase is warm from the start of each
+    /// transaction (EIP-3651)
+    Shanghai,
+}
+
+impl Default for HardFork {
+    fn default() -> Self {
+        Self::London
+    }
+}
+
+impl HardFork {
+    /// Quotient for max refund of gas used, which is the same for all the
+    /// forks from London on.
+    pub fn max_refund_quotient_of_gas_used(&self) -> u64 {
+        MAX_REFUND_QUOTIENT_OF_GAS_USED as u64
+    }
+
+    /// Whether DIFFICULTY returns PREVRANDAO, i.e. the mix hash of the block.
+    pub fn has_prevrandao(&self) -> bool {
+        *self >= Self::Merge
+    }
+
+    /// Whether the coinbase is warm from the start of each transaction.
+    pub fn is_coinbase_warm(&self) -> bool {
+        *self >= Self::Shanghai
+    }
+}
+
 /// Defines the gas consumption.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct GasCost(pub u64);
@@ -119,6 +156,12 @@ impl GasCost {
     pub const TX: Self = Self(21000);
     /// Constant cost for a creation transaction
     pub const CREATION_TX: Self = Self(53000);
+    /// Constant cost for each address in the access list of a transaction
+    /// (EIP-2930)
+    pub const ACCESS_LIST_ADDRESS: Self = Self(2400);
+    /// Constant cost for each storage key in the access list of a transaction
+    /// (EIP-2930)
+    pub const ACCESS_LIST_STORAGE_KEY: Self = Self(1900);
     /// Constant cost for calling with non-zero value
     pub const CALL_WITH_VALUE: Self = Self(9000);
     /// Constant cost for turning empty account into non-empty account
@@ -129,6 +172,12 @@ impl GasCost {
     pub const MEMORY_EXPANSION_LINEAR_COEFF: Self = Self(3);
     /// constant gas for logs op codes
     pub const LOG: Self = Self(375);
+    /// Constant cost for calling the ecrecover precompile
+    pub const PRECOMPILE_ECRECOVER: Self = Self(3000);
+    /// Constant cost for calling the identity precompile
+    pub const PRECOMPILE_IDENTITY_BASE: Self = Self(15);
+    /// Cost per word of input for calling the identity precompile
+    pub const PRECOMPILE_IDENTITY_PER_WORD: Self = Self(3);
 }
 
 impl GasCost {
@@ -1,14 +1,13 @@
 #![cfg(feature = "circuits")]
 
 use bus_mapping::circuit_input_builder::BuilderClient;
-use bus_mapping::operation::OperationContainer;
 use halo2_proofs::dev::MockProver;
 use integration_tests::{get_client, log_init, GenDataOutput};
 use lazy_static::lazy_static;
 use log::trace;
-use zkevm_circuits::evm_circuit::witness::RwMap;
 use zkevm_circuits::evm_circuit::{
-    test::run_test_circuit_complete_fixed_table, witness::block_convert,
+    test::run_test_circuit_complete_fixed_table,
+    witness::{block_convert, Witnesses},
 };
 use zkevm_circuits::state_circuit::StateCircuit;
 
@@ -34,24 +33,13 @@ async fn test_state_circuit_block(block_num: u64) {
     let builder = cli.gen_inputs(block_num).await.unwrap();
 
     // Generate state proof
-    let stack_ops = builder.block.container.sorted_stack();
-    trace!("stack_ops: {:#?}", stack_ops);
-    let memory_ops = builder.block.container.sorted_memory();
-    trace!("memory_ops: {:#?}", memory_ops);
-    let storage_ops = builder.block.container.sorted_storage();
-    trace!("storage_ops: {:#?}", storage_ops);
+    let Witnesses { block, rws } =
+        Witnesses::from_builder(&builder, Fr::rand()).expect("inconsistent step transitions");
+    trace!("rws: {:#?}", rws);
 
     const DEGREE: usize = 17;
 
-    let rw_map = RwMap::from(&OperationContainer {
-        memory: memory_ops,
-        stack: stack_ops,
-        storage: storage_ops,
-        ..Default::default()
-    });
-
-    let randomness = Fr::rand();
-    let circuit = StateCircuit::<Fr>::new(randomness, rw_map);
+    let circuit = StateCircuit::<Fr>::new(block.randomness, rws);
     let power_of_randomness = circuit.instance();
 
     use halo2_proofs::pairing::bn256::Fr as Fp;
@@ -2,6 +2,7 @@ use bus_mapping::circuit_input_builder::BuilderClient;
 use bus_mapping::rpc::GethClient;
 use ethers_providers::Http;
 use halo2_proofs::{
+    arithmetic::BaseExt,
     pairing::bn256::{Fr, G1Affine},
     plonk::*,
     poly::commitment::Params,
@@ -12,7 +13,7 @@ use rand_xorshift::XorShiftRng;
 use std::str::FromStr;
 use strum::IntoEnumIterator;
 use zkevm_circuits::evm_circuit::{
-    table::FixedTableTag, test::TestCircuit, witness::block_convert,
+    param::MIN_N_POWER_OF_RANDOMNESS, table::FixedTableTag, test::TestCircuit, witness::Witnesses,
 };
 use zkevm_circuits::state_circuit::StateCircuit;
 
@@ -35,10 +36,13 @@ pub async fn compute_proof(
     // TODO: only {evm,state}_proof are implemented right now
     let evm_proof;
     let state_proof;
-    let block = block_convert(&builder.block, &builder.code_db);
+    let Witnesses { block, rws } = Witnesses::from_builder(&builder, Fr::rand());
     {
         // generate evm_circuit proof
-        let circuit = TestCircuit::<Fr>::new(block.clone(), FixedTableTag::iter().collect());
+        let circuit = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::new(
+            block.clone(),
+            FixedTableTag::iter().collect(),
+        );
 
         // TODO: can this be pre-generated to a file?
         // related
@@ -61,7 +65,7 @@ pub async fn compute_proof(
 
     {
         // generate state_circuit proof
-        let circuit = StateCircuit::new(block.randomness, block.rws);
+        let circuit = StateCircuit::new(block.randomness, rws);
 
         // TODO: same quest like in the first scope
         let vk = keygen_vk(params, &circuit)?;
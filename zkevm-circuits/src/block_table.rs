@@ -0,0 +1,124 @@
+#![allow(missing_docs)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::evm_circuit::{table::LookupTable, witness::BlockContext};
+use eth_types::Field;
+
+/// The block table which contains the constants of the block
+#[derive(Clone, Copy)]
+pub struct BlockTable {
+    pub tag: Column<Advice>,
+    /// The block number for BlockHash, otherwise 0
+    pub number: Column<Advice>,
+    pub value: Column<Advice>,
+}
+
+impl<F: FieldExt> LookupTable<F> for BlockTable {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_advice(self.tag, Rotation::cur()),
+            meta.query_advice(self.number, Rotation::cur()),
+            meta.query_advice(self.value, Rotation::cur()),
+        ]
+    }
+}
+
+impl BlockTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.advice_column(),
+            number: meta.advice_column(),
+            value: meta.advice_column(),
+        }
+    }
+
+    pub fn assign<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &[F; 3],
+    ) -> Result<(), Error> {
+        for (column, value) in [self.tag, self.number, self.value].iter().zip(row) {
+            region.assign_advice(
+                || "assign block row on block table",
+                *column,
+                offset,
+                || Ok(*value),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Load an all-zero row followed by a row for every
+    /// [`BlockContextFieldTag`](crate::evm_circuit::table::
+    /// BlockContextFieldTag) of `block`, with one BlockHash row per history
+    /// hash.
+    pub fn assign_block_context<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        block: &BlockContext,
+        randomness: F,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "block table",
+            |mut region| {
+                let mut offset = 0;
+                self.assign(&mut region, offset, &[F::zero(); 3])?;
+                offset += 1;
+
+                for row in block.table_assignments(randomness) {
+                    self.assign(&mut region, offset, &row)?;
+                    offset += 1;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evm_circuit::{table::BlockContextFieldTag, witness::BlockContext};
+    use eth_types::Word;
+    use halo2_proofs::{arithmetic::BaseExt, pairing::bn256::Fr};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn block_context_has_all_fields() {
+        let block = BlockContext {
+            number: Word::from(0x100),
+            history_hashes: vec![Word::from(0xcafe), Word::from(0xbeef)],
+            ..Default::default()
+        };
+        let rows = block.table_assignments(Fr::rand());
+
+        for tag in BlockContextFieldTag::iter() {
+            let count = rows
+                .iter()
+                .filter(|[field_tag, _, _]| *field_tag == Fr::from(tag as u64))
+                .count();
+            let expected = match tag {
+                BlockContextFieldTag::BlockHash => block.history_hashes.len(),
+                _ => 1,
+            };
+            assert_eq!(count, expected, "unexpected number of {:?} rows", tag);
+        }
+        assert_eq!(rows.len(), BlockContextFieldTag::iter().count() + 1);
+
+        // History hashes are keyed by the number of the previous blocks
+        let block_hash_numbers = rows
+            .iter()
+            .filter(|[field_tag, _, _]| {
+                *field_tag == Fr::from(BlockContextFieldTag::BlockHash as u64)
+            })
+            .map(|[_, number, _]| *number)
+            .collect::<Vec<_>>();
+        assert_eq!(block_hash_numbers, vec![Fr::from(0xff), Fr::from(0xfe)]);
+    }
+}
@@ -0,0 +1,123 @@
+#![allow(missing_docs)]
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::evm_circuit::{table::LookupTable, witness::Bytecode};
+
+/// The bytecode table shared between evm circuit and bytecode circuit
+#[derive(Clone, Copy)]
+pub struct BytecodeTable {
+    pub hash: Column<Advice>,
+    pub tag: Column<Advice>,
+    pub index: Column<Advice>,
+    /// Whether the byte is an executable opcode (1) or the immediate data of
+    /// a PUSH* (0)
+    pub is_code: Column<Advice>,
+    pub value: Column<Advice>,
+}
+
+impl<F: FieldExt> LookupTable<F> for BytecodeTable {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_advice(self.hash, Rotation::cur()),
+            meta.query_advice(self.tag, Rotation::cur()),
+            meta.query_advice(self.index, Rotation::cur()),
+            meta.query_advice(self.is_code, Rotation::cur()),
+            meta.query_advice(self.value, Rotation::cur()),
+        ]
+    }
+}
+
+impl BytecodeTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            hash: meta.advice_column(),
+            tag: meta.advice_column(),
+            index: meta.advice_column(),
+            is_code: meta.advice_column(),
+            value: meta.advice_column(),
+        }
+    }
+
+    pub fn assign<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &[F; 5],
+    ) -> Result<(), Error> {
+        for (column, value) in [self.hash, self.tag, self.index, self.is_code, self.value]
+            .iter()
+            .zip(row)
+        {
+            region.assign_advice(
+                || "assign bytecode row on bytecode table",
+                *column,
+                offset,
+                || Ok(*value),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Load an all-zero row followed by the rows of all `bytecodes`, where the
+    /// `is_code` flag of each byte is derived by skipping the PUSH* data.
+    pub fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bytecodes: &[Bytecode],
+        randomness: F,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "bytecode table",
+            |mut region| {
+                let mut offset = 0;
+                self.assign(&mut region, offset, &[F::zero(); 5])?;
+                offset += 1;
+
+                for bytecode in bytecodes.iter() {
+                    for row in bytecode.table_assignments(randomness) {
+                        self.assign(&mut region, offset, &row)?;
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evm_circuit::{table::BytecodeFieldTag, witness::Bytecode};
+    use eth_types::evm_types::OpcodeId;
+    use halo2_proofs::{arithmetic::BaseExt, pairing::bn256::Fr};
+
+    #[test]
+    fn push_data_is_not_code() {
+        let bytecode = Bytecode::new(vec![
+            OpcodeId::PUSH1.as_u8(),
+            OpcodeId::JUMPDEST.as_u8(),
+            OpcodeId::JUMPDEST.as_u8(),
+        ]);
+        let rows = bytecode.table_assignments(Fr::rand());
+
+        // The first row is the length, followed by one row per byte
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0][1], Fr::from(BytecodeFieldTag::Length as u64));
+        assert_eq!(rows[0][4], Fr::from(3));
+
+        let is_code = rows[1..]
+            .iter()
+            .map(|[_, tag, _, is_code, _]| {
+                assert_eq!(*tag, Fr::from(BytecodeFieldTag::Byte as u64));
+                *is_code
+            })
+            .collect::<Vec<_>>();
+        // The immediate of PUSH1 is data even though it's a JUMPDEST byte
+        assert_eq!(is_code, vec![Fr::one(), Fr::zero(), Fr::one()]);
+    }
+}
@@ -14,6 +14,7 @@ pub mod witness;
 use eth_types::Field;
 use execution::ExecutionConfig;
 use itertools::Itertools;
+use param::MIN_N_POWER_OF_RANDOMNESS;
 use table::{FixedTableTag, LookupTable};
 use witness::Block;
 
@@ -26,27 +27,38 @@ pub struct EvmCircuit<F> {
 }
 
 impl<F: Field> EvmCircuit<F> {
-    /// Configure EvmCircuit
-    pub fn configure(
+    /// Configure EvmCircuit with `N_POWER_OF_RANDOMNESS` powers of randomness
+    /// `[r, r^2, ...]`, which should be at least
+    /// [`MIN_N_POWER_OF_RANDOMNESS`].
+    pub fn configure<const N_POWER_OF_RANDOMNESS: usize>(
         meta: &mut ConstraintSystem<F>,
-        power_of_randomness: [Expression<F>; 31],
+        power_of_randomness: [Expression<F>; N_POWER_OF_RANDOMNESS],
         tx_table: &dyn LookupTable<F>,
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
+        ecrecover_table: &dyn LookupTable<F>,
     ) -> Self {
+        assert!(
+            N_POWER_OF_RANDOMNESS >= MIN_N_POWER_OF_RANDOMNESS,
+            "EvmCircuit requires at least {} powers of randomness, but got {}",
+            MIN_N_POWER_OF_RANDOMNESS,
+            N_POWER_OF_RANDOMNESS
+        );
+
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
         let byte_table = [(); 1].map(|_| meta.fixed_column());
 
         let execution = ExecutionConfig::configure(
             meta,
-            power_of_randomness,
+            &power_of_randomness,
             &fixed_table,
             &byte_table,
             tx_table,
             rw_table,
             bytecode_table,
             block_table,
+            ecrecover_table,
         );
 
         Self {
@@ -104,6 +116,7 @@ impl<F: Field> EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
     ) -> Result<(), Error> {
+        self.check_row_budget(block)?;
         self.execution.assign_block(layouter, block, false)
     }
 
@@ -114,9 +127,23 @@ impl<F: Field> EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
     ) -> Result<(), Error> {
+        self.check_row_budget(block)?;
         self.execution.assign_block(layouter, block, true)
     }
 
+    /// Check that the steps of the block fit in its `max_evm_rows`, so an
+    /// overflowing block is reported before any assignment.
+    pub fn check_row_budget(&self, block: &Block<F>) -> Result<(), RowBudgetExceeded> {
+        let needed = self.get_num_rows_required(block);
+        if block.max_evm_rows != 0 && needed > block.max_evm_rows {
+            return Err(RowBudgetExceeded {
+                needed,
+                budget: block.max_evm_rows,
+            });
+        }
+        Ok(())
+    }
+
     /// Calculate which rows are "actually" used in the circuit
     pub fn get_active_rows(&self, block: &Block<F>) -> (Vec<usize>, Vec<usize>) {
         let max_offset = self.get_num_rows_required(block);
@@ -127,6 +154,23 @@ impl<F: Field> EvmCircuit<F> {
         (gates_row_ids, lookup_row_ids)
     }
 
+    /// The minimal set of fixed tables to load for the block, i.e. the ones
+    /// looked up by the execution states of its steps.
+    pub fn required_fixed_table_tags(&self, block: &Block<F>) -> Vec<FixedTableTag> {
+        self.execution.required_fixed_table_tags(block)
+    }
+
+    /// Execution states whose constraints are known to be incomplete.
+    pub(crate) fn stub_states(&self) -> &[step::ExecutionState] {
+        self.execution.stub_states()
+    }
+
+    /// Execution states whose gadgets don't declare the rw_counter deltas of
+    /// [`step::ExecutionState::rw_counter_deltas`].
+    pub(crate) fn rw_counter_delta_mismatches(&self) -> &[step::ExecutionState] {
+        self.execution.rw_counter_delta_mismatches()
+    }
+
     pub fn get_num_rows_required(&self, block: &Block<F>) -> usize {
         // Start at 1 so we can be sure there is an unused `next` row available
         let mut num_rows = 1;
@@ -139,21 +183,58 @@ impl<F: Field> EvmCircuit<F> {
     }
 }
 
+/// Error of a block whose steps need more rows than the EvmCircuit has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowBudgetExceeded {
+    /// Number of rows required by the steps of the block
+    pub needed: usize,
+    /// Number of rows available to the steps
+    pub budget: usize,
+}
+
+impl std::fmt::Display for RowBudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "step budget exceeded (needed {}, have {})",
+            self.needed, self.budget
+        )
+    }
+}
+
+impl std::error::Error for RowBudgetExceeded {}
+
+impl From<RowBudgetExceeded> for Error {
+    /// The synthesis only accepts [`Error`], so the context is logged before
+    /// it's dropped.
+    fn from(err: RowBudgetExceeded) -> Self {
+        log::error!("{}", err);
+        Error::Synthesis
+    }
+}
+
 #[cfg(any(feature = "test", test))]
 pub mod test {
     use crate::{
+        block_table::BlockTable,
+        bytecode_table::BytecodeTable,
         evm_circuit::{
+            param::MIN_N_POWER_OF_RANDOMNESS,
+            step::ExecutionState,
             table::FixedTableTag,
-            witness::{Block, BlockContext, Bytecode, RwMap, Transaction},
+            witness::{Block, BlockContext, Bytecode, Ecrecover, RwMap, Transaction},
             EvmCircuit,
         },
         rw_table::RwTable,
+        state_circuit::{StateCircuit, StateConfig},
         util::Expr,
     };
     use eth_types::{Field, Word};
     use halo2_proofs::{
+        arithmetic::BaseExt,
         circuit::{Layouter, SimpleFloorPlanner},
         dev::{MockProver, VerifyFailure},
+        pairing::bn256::Fr,
         plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
         poly::Rotation,
     };
@@ -164,6 +245,21 @@ pub mod test {
     };
     use strum::IntoEnumIterator;
 
+    /// Env var whose value is used as the seed of the randomness of the test
+    /// circuits, to reproduce a failing case exactly.
+    pub const RANDOMNESS_SEED_ENV_VAR: &str = "EVM_CIRCUIT_RANDOMNESS_SEED";
+
+    /// Fix the randomness of `block` with the seed in
+    /// [`RANDOMNESS_SEED_ENV_VAR`] if it's set.
+    pub fn with_env_randomness_seed<F: Field>(block: Block<F>) -> Block<F> {
+        match std::env::var(RANDOMNESS_SEED_ENV_VAR) {
+            Ok(seed) => block.with_fixed_randomness(seed.parse().unwrap_or_else(|_| {
+                panic!("Cannot parse {} env var as u64", RANDOMNESS_SEED_ENV_VAR)
+            })),
+            Err(_) => block,
+        }
+    }
+
     pub(crate) fn rand_range<T, R>(range: R) -> T
     where
         T: SampleUniform,
@@ -188,12 +284,54 @@ pub mod test {
     pub struct TestCircuitConfig<F> {
         tx_table: [Column<Advice>; 4],
         rw_table: RwTable,
-        bytecode_table: [Column<Advice>; 5],
-        block_table: [Column<Advice>; 3],
+        bytecode_table: BytecodeTable,
+        block_table: BlockTable,
+        ecrecover_table: [Column<Advice>; 5],
         evm_circuit: EvmCircuit<F>,
     }
 
     impl<F: Field> TestCircuitConfig<F> {
+        fn configure<const N_POWER_OF_RANDOMNESS: usize>(
+            meta: &mut ConstraintSystem<F>,
+            rw_table: RwTable,
+        ) -> Self {
+            let tx_table = [(); 4].map(|_| meta.advice_column());
+            let bytecode_table = BytecodeTable::construct(meta);
+            let block_table = BlockTable::construct(meta);
+            let ecrecover_table = [(); 5].map(|_| meta.advice_column());
+
+            let power_of_randomness = {
+                let columns = [(); N_POWER_OF_RANDOMNESS].map(|_| meta.instance_column());
+                let mut power_of_randomness = None;
+
+                meta.create_gate("", |meta| {
+                    power_of_randomness =
+                        Some(columns.map(|column| meta.query_instance(column, Rotation::cur())));
+
+                    [0.expr()]
+                });
+
+                power_of_randomness.unwrap()
+            };
+
+            Self {
+                tx_table,
+                rw_table,
+                bytecode_table,
+                block_table,
+                ecrecover_table,
+                evm_circuit: EvmCircuit::configure(
+                    meta,
+                    power_of_randomness,
+                    &tx_table,
+                    &rw_table,
+                    &bytecode_table,
+                    &block_table,
+                    &ecrecover_table,
+                ),
+            }
+        }
+
         fn load_txs(
             &self,
             layouter: &mut impl Layouter<F>,
@@ -270,57 +408,19 @@ pub mod test {
             )
         }
 
-        fn load_bytecodes(
-            &self,
-            layouter: &mut impl Layouter<F>,
-            bytecodes: &[Bytecode],
-            randomness: F,
-        ) -> Result<(), Error> {
-            layouter.assign_region(
-                || "bytecode table",
-                |mut region| {
-                    let mut offset = 0;
-                    for column in self.bytecode_table {
-                        region.assign_advice(
-                            || "bytecode table all-zero row",
-                            column,
-                            offset,
-                            || Ok(F::zero()),
-                        )?;
-                    }
-                    offset += 1;
-
-                    for bytecode in bytecodes.iter() {
-                        for row in bytecode.table_assignments(randomness) {
-                            for (column, value) in self.bytecode_table.iter().zip_eq(row) {
-                                region.assign_advice(
-                                    || format!("bytecode table row {}", offset),
-                                    *column,
-                                    offset,
-                                    || Ok(value),
-                                )?;
-                            }
-                            offset += 1;
-                        }
-                    }
-                    Ok(())
-                },
-            )
-        }
-
-        fn load_block(
+        fn load_ecrecovers(
             &self,
             layouter: &mut impl Layouter<F>,
-            block: &BlockContext,
+            ecrecovers: &[Ecrecover],
             randomness: F,
         ) -> Result<(), Error> {
             layouter.assign_region(
-                || "block table",
+                || "ecrecover table",
                 |mut region| {
                     let mut offset = 0;
-                    for column in self.block_table {
+                    for column in self.ecrecover_table {
                         region.assign_advice(
-                            || "block table all-zero row",
+                            || "ecrecover table all-zero row",
                             column,
                             offset,
                             || Ok(F::zero()),
@@ -328,10 +428,11 @@ pub mod test {
                     }
                     offset += 1;
 
-                    for row in block.table_assignments(randomness) {
-                        for (column, value) in self.block_table.iter().zip_eq(row) {
+                    for ecrecover in ecrecovers.iter() {
+                        let row = ecrecover.table_assignment(randomness);
+                        for (column, value) in self.ecrecover_table.iter().zip_eq(row) {
                             region.assign_advice(
-                                || format!("block table row {}", offset),
+                                || format!("ecrecover table row {}", offset),
                                 *column,
                                 offset,
                                 || Ok(value),
@@ -347,12 +448,12 @@ pub mod test {
     }
 
     #[derive(Default)]
-    pub struct TestCircuit<F> {
+    pub struct TestCircuit<F, const N_POWER_OF_RANDOMNESS: usize> {
         block: Block<F>,
         fixed_table_tags: Vec<FixedTableTag>,
     }
 
-    impl<F> TestCircuit<F> {
+    impl<F, const N_POWER_OF_RANDOMNESS: usize> TestCircuit<F, N_POWER_OF_RANDOMNESS> {
         pub fn new(block: Block<F>, fixed_table_tags: Vec<FixedTableTag>) -> Self {
             Self {
                 block,
@@ -361,7 +462,9 @@ pub mod test {
         }
     }
 
-    impl<F: Field> Circuit<F> for TestCircuit<F> {
+    impl<F: Field, const N_POWER_OF_RANDOMNESS: usize> Circuit<F>
+        for TestCircuit<F, N_POWER_OF_RANDOMNESS>
+    {
         type Config = TestCircuitConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
 
@@ -370,39 +473,8 @@ pub mod test {
         }
 
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let tx_table = [(); 4].map(|_| meta.advice_column());
             let rw_table = RwTable::construct(meta);
-            let bytecode_table = [(); 5].map(|_| meta.advice_column());
-            let block_table = [(); 3].map(|_| meta.advice_column());
-
-            let power_of_randomness = {
-                let columns = [(); 31].map(|_| meta.instance_column());
-                let mut power_of_randomness = None;
-
-                meta.create_gate("", |meta| {
-                    power_of_randomness =
-                        Some(columns.map(|column| meta.query_instance(column, Rotation::cur())));
-
-                    [0.expr()]
-                });
-
-                power_of_randomness.unwrap()
-            };
-
-            Self::Config {
-                tx_table,
-                rw_table,
-                bytecode_table,
-                block_table,
-                evm_circuit: EvmCircuit::configure(
-                    meta,
-                    power_of_randomness,
-                    &tx_table,
-                    &rw_table,
-                    &bytecode_table,
-                    &block_table,
-                ),
-            }
+            TestCircuitConfig::configure::<N_POWER_OF_RANDOMNESS>(meta, rw_table)
         }
 
         fn synthesize(
@@ -416,24 +488,33 @@ pub mod test {
             config.evm_circuit.load_byte_table(&mut layouter)?;
             config.load_txs(&mut layouter, &self.block.txs, self.block.randomness)?;
             config.load_rws(&mut layouter, &self.block.rws, self.block.randomness)?;
-            config.load_bytecodes(&mut layouter, &self.block.bytecodes, self.block.randomness)?;
-            config.load_block(&mut layouter, &self.block.context, self.block.randomness)?;
+            config.bytecode_table.load(
+                &mut layouter,
+                &self.block.bytecodes,
+                self.block.randomness,
+            )?;
+            config.block_table.assign_block_context(
+                &mut layouter,
+                &self.block.context,
+                self.block.randomness,
+            )?;
+            config.load_ecrecovers(&mut layouter, &self.block.ecrecovers, self.block.randomness)?;
             config
                 .evm_circuit
                 .assign_block_exact(&mut layouter, &self.block)
         }
     }
 
-    impl<F: Field> TestCircuit<F> {
+    impl<F: Field, const N_POWER_OF_RANDOMNESS: usize> TestCircuit<F, N_POWER_OF_RANDOMNESS> {
         pub fn get_num_rows_required(block: &Block<F>) -> usize {
             let mut cs = ConstraintSystem::default();
-            let config = TestCircuit::configure(&mut cs);
+            let config = Self::configure(&mut cs);
             config.evm_circuit.get_num_rows_required(block)
         }
 
         pub fn get_active_rows(block: &Block<F>) -> (Vec<usize>, Vec<usize>) {
             let mut cs = ConstraintSystem::default();
-            let config = TestCircuit::configure(&mut cs);
+            let config = Self::configure(&mut cs);
             config.evm_circuit.get_active_rows(block)
         }
     }
@@ -441,10 +522,24 @@ pub mod test {
     pub fn run_test_circuit<F: Field>(
         block: Block<F>,
         fixed_table_tags: Vec<FixedTableTag>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        run_test_circuit_with_power_of_randomness::<F, MIN_N_POWER_OF_RANDOMNESS>(
+            block,
+            fixed_table_tags,
+        )
+    }
+
+    pub fn run_test_circuit_with_power_of_randomness<
+        F: Field,
+        const N_POWER_OF_RANDOMNESS: usize,
+    >(
+        block: Block<F>,
+        fixed_table_tags: Vec<FixedTableTag>,
     ) -> Result<(), Vec<VerifyFailure>> {
         let log2_ceil = |n| u32::BITS - (n as u32).leading_zeros() - (n & (n - 1) == 0) as u32;
 
-        let num_rows_required_for_steps = TestCircuit::get_num_rows_required(&block);
+        let num_rows_required_for_steps =
+            TestCircuit::<F, N_POWER_OF_RANDOMNESS>::get_num_rows_required(&block);
 
         let k = log2_ceil(
             64 + fixed_table_tags
@@ -462,11 +557,12 @@ pub mod test {
         let k = k.max(log2_ceil(64 + num_rows_required_for_steps));
         log::debug!("evm circuit uses k = {}", k);
 
-        let power_of_randomness = (1..32)
+        let power_of_randomness = (1..=N_POWER_OF_RANDOMNESS as u64)
             .map(|exp| vec![block.randomness.pow(&[exp, 0, 0, 0]); (1 << k) - 64])
             .collect();
-        let (active_gate_rows, active_lookup_rows) = TestCircuit::get_active_rows(&block);
-        let circuit = TestCircuit::<F>::new(block, fixed_table_tags);
+        let (active_gate_rows, active_lookup_rows) =
+            TestCircuit::<F, N_POWER_OF_RANDOMNESS>::get_active_rows(&block);
+        let circuit = TestCircuit::<F, N_POWER_OF_RANDOMNESS>::new(block, fixed_table_tags);
         let prover = MockProver::<F>::run(k, &circuit, power_of_randomness).unwrap();
         prover.verify_at_rows(active_gate_rows.into_iter(), active_lookup_rows.into_iter())
     }
@@ -475,7 +571,7 @@ pub mod test {
         block: Block<F>,
     ) -> Result<(), Vec<VerifyFailure>> {
         run_test_circuit(
-            block,
+            with_env_randomness_seed(block),
             vec![
                 FixedTableTag::Zero,
                 FixedTableTag::Range5,
@@ -494,6 +590,679 @@ pub mod test {
     pub fn run_test_circuit_complete_fixed_table<F: Field>(
         block: Block<F>,
     ) -> Result<(), Vec<VerifyFailure>> {
-        run_test_circuit(block, FixedTableTag::iter().collect())
+        run_test_circuit(
+            with_env_randomness_seed(block),
+            FixedTableTag::iter().collect(),
+        )
+    }
+
+    /// Assert that one of the failures of `result` is a constraint of the gate
+    /// named `gate`, which for an execution gadget is its `NAME`, so that a
+    /// test can tell which gadget tripped.
+    pub fn assert_gate_failure(result: Result<(), Vec<VerifyFailure>>, gate: &str) {
+        let failures = result.expect_err("result is not an error");
+        // The fields of halo2_proofs::dev::metadata::Constraint aren't public,
+        // so we match on its format string, which ends with the gate name.
+        let gate_suffix = format!("('{}')", gate);
+        assert!(
+            failures.iter().any(|failure| matches!(
+                failure,
+                VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                    if format!("{}", constraint).ends_with(&gate_suffix)
+            )),
+            "no constraint of gate {} fails in {:#?}",
+            gate,
+            failures
+        );
+    }
+
+    /// Circuit with both EvmCircuit and StateCircuit, which share the columns
+    /// of the rw table, so that every rw lookup of EvmCircuit is checked
+    /// against the rows proven by StateCircuit.
+    #[derive(Default)]
+    struct SharedRwTableCircuit {
+        block: Block<Fr>,
+        state_rws: RwMap,
+        fixed_table_tags: Vec<FixedTableTag>,
+    }
+
+    impl Circuit<Fr> for SharedRwTableCircuit {
+        type Config = (TestCircuitConfig<Fr>, StateConfig<Fr>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let rw_table = RwTable::construct(meta);
+            // EvmCircuit expects its powers of randomness in the first
+            // instance columns, so it's configured before StateCircuit.
+            let evm_config =
+                TestCircuitConfig::configure::<MIN_N_POWER_OF_RANDOMNESS>(meta, rw_table);
+            let state_config = StateConfig::configure(meta, rw_table);
+            (evm_config, state_config)
+        }
+
+        fn synthesize(
+            &self,
+            (evm_config, state_config): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let randomness = self.block.randomness;
+            evm_config
+                .evm_circuit
+                .load_fixed_table(&mut layouter, self.fixed_table_tags.clone())?;
+            evm_config.evm_circuit.load_byte_table(&mut layouter)?;
+            evm_config.load_txs(&mut layouter, &self.block.txs, randomness)?;
+            evm_config
+                .bytecode_table
+                .load(&mut layouter, &self.block.bytecodes, randomness)?;
+            evm_config.block_table.assign_block_context(
+                &mut layouter,
+                &self.block.context,
+                randomness,
+            )?;
+            evm_config.load_ecrecovers(&mut layouter, &self.block.ecrecovers, randomness)?;
+
+            // The rw table is only assigned by StateCircuit, plus an
+            // all-zero row for the disabled lookups of EvmCircuit.
+            StateCircuit::new(randomness, self.state_rws.clone())
+                .synthesize(state_config, layouter.namespace(|| "state circuit"))?;
+            layouter.assign_region(
+                || "rw table all-zero row",
+                |mut region| {
+                    evm_config
+                        .rw_table
+                        .assign(&mut region, 0, &Default::default())
+                },
+            )?;
+
+            evm_config
+                .evm_circuit
+                .assign_block_exact(&mut layouter, &self.block)
+        }
+    }
+
+    /// Prove `block` on EvmCircuit and StateCircuit sharing the rw table,
+    /// which catches the mismatches between the rw lookups of the execution
+    /// gadgets and the rows of StateCircuit that proving each circuit alone
+    /// misses.
+    pub fn run_test_circuit_with_state_circuit(
+        block: Block<Fr>,
+        fixed_table_tags: Vec<FixedTableTag>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let block = with_env_randomness_seed(block);
+
+        // StateCircuit also proves the seeds of the initial values, which
+        // aren't looked up by EvmCircuit
+        let state_rws = block.state_circuit_rws();
+
+        // The u16 range table of StateCircuit takes 2^16 rows
+        let k = 18;
+        let n_rows = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::get_num_rows_required(&block)
+            .max(state_rws.0.values().map(|rws| rws.len()).sum::<usize>() + 2);
+        let power_of_randomness = (1..=MIN_N_POWER_OF_RANDOMNESS as u64)
+            .map(|exp| vec![block.randomness.pow(&[exp, 0, 0, 0]); (1 << k) - 64])
+            .collect::<Vec<_>>();
+        let circuit = SharedRwTableCircuit {
+            block,
+            state_rws,
+            fixed_table_tags,
+        };
+        let prover = MockProver::<Fr>::run(
+            k,
+            &circuit,
+            [power_of_randomness.clone(), power_of_randomness].concat(),
+        )
+        .unwrap();
+        prover.verify_at_rows(0..n_rows, 0..n_rows)
+    }
+
+    #[test]
+    fn opcodes_map_to_responsible_execution_states() {
+        use crate::evm_circuit::witness::opcode_execution_state;
+        use eth_types::evm_types::OpcodeId;
+        use std::convert::TryFrom;
+
+        let mut unmapped = vec![];
+        for byte in 0..=u8::MAX {
+            // Invalid opcodes always end in ErrorInvalidOpcode
+            let op = match OpcodeId::try_from(byte) {
+                Ok(OpcodeId::INVALID(_)) | Err(_) => continue,
+                Ok(op) => op,
+            };
+            match opcode_execution_state(op) {
+                Some(execution_state) => assert!(
+                    execution_state.responsible_opcodes().contains(&op),
+                    "{:?} maps to {:?}, which isn't responsible for it",
+                    op,
+                    execution_state
+                ),
+                None => unmapped.push(op),
+            }
+        }
+        // The opcodes whose gadgets aren't implemented yet, which should shrink
+        // as gadgets are added.
+        let expected = vec![
+            OpcodeId::SDIV,
+            OpcodeId::SMOD,
+            OpcodeId::ADDMOD,
+            OpcodeId::MULMOD,
+            OpcodeId::EXP,
+            OpcodeId::NOT,
+            OpcodeId::SHL,
+            OpcodeId::SHR,
+            OpcodeId::SAR,
+            OpcodeId::SHA3,
+            OpcodeId::ADDRESS,
+            OpcodeId::BALANCE,
+            OpcodeId::CODESIZE,
+            OpcodeId::EXTCODESIZE,
+            OpcodeId::EXTCODECOPY,
+            OpcodeId::BLOCKHASH,
+            OpcodeId::CREATE,
+            OpcodeId::CALLCODE,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CREATE2,
+            OpcodeId::STATICCALL,
+            OpcodeId::SELFDESTRUCT,
+        ];
+        assert_eq!(
+            unmapped, expected,
+            "the unmapped opcodes changed, update the expected list if a gadget was added"
+        );
+    }
+
+    #[test]
+    fn fixed_randomness_is_reproducible() {
+        use crate::test_util::build_witness_block;
+        use eth_types::{bytecode, geth_types::GethData};
+        use mock::TestContext;
+
+        let block = || {
+            let code = bytecode! {
+                PUSH1(0x20)
+                PUSH1(0x00)
+                MSTORE
+                STOP
+            };
+            let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+                .unwrap()
+                .into();
+            build_witness_block(block)
+        };
+
+        let [block_a, block_b] = [(); 2].map(|_| block().with_fixed_randomness(42));
+        assert_eq!(block_a.randomness, block_b.randomness);
+        assert_ne!(
+            block_a.randomness,
+            block().with_fixed_randomness(43).randomness
+        );
+
+        // Same seed gives the same assignments and the same verification result
+        let bytecode_assignments = |block: &Block<_>| {
+            block
+                .bytecodes
+                .iter()
+                .flat_map(|bytecode| bytecode.table_assignments(block.randomness))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            bytecode_assignments(&block_a),
+            bytecode_assignments(&block_b)
+        );
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(block_a),
+            run_test_circuit_incomplete_fixed_table(block_b)
+        );
+    }
+
+    #[test]
+    fn empty_block() {
+        use crate::test_util::{get_fixed_table, FixedTableConfig};
+
+        let mut block = Block::<Fr>::default();
+        assert_eq!(block.pad_to(16), Ok(()));
+
+        // The padding steps before the EndBlock keep the rw_counter of an
+        // empty block, which hasn't done any rw
+        let steps = &block.txs[0].steps;
+        assert_eq!(steps.len(), 16);
+        assert!(steps[..15]
+            .iter()
+            .all(|step| step.execution_state == ExecutionState::Padding));
+        assert_eq!(steps[15].execution_state, ExecutionState::EndBlock);
+        assert!(steps.iter().all(|step| step.rw_counter == 1));
+        assert!(block.rws.0.values().all(|rws| rws.is_empty()));
+
+        assert_eq!(
+            run_test_circuit_with_state_circuit(
+                block,
+                get_fixed_table(FixedTableConfig::Incomplete)
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn single_stop_tx() {
+        use crate::test_util::run_test_circuits;
+        use eth_types::bytecode;
+        use mock::TestContext;
+
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP }).unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
+    #[test]
+    fn discontinuous_step_transition_is_rejected() {
+        use crate::{
+            evm_circuit::witness::StepTransitionMismatch,
+            test_util::{
+                build_witness_block, test_circuits_using_witness_block, BytecodeTestConfig,
+                TestCircuitsError,
+            },
+        };
+        use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData};
+        use mock::TestContext;
+
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut block = build_witness_block(block);
+        assert_eq!(block.check_step_transitions(), Ok(()));
+
+        // Give the step after the first PUSH1 one more gas than the PUSH1 left
+        let step_index = block.txs[0]
+            .steps
+            .iter()
+            .position(|step| step.opcode == Some(OpcodeId::PUSH1))
+            .unwrap();
+        let next_step = &mut block.txs[0].steps[step_index + 1];
+        next_step.gas_left += 1;
+        let actual = next_step.gas_left;
+        assert_eq!(
+            block.check_step_transitions(),
+            Err(StepTransitionMismatch {
+                tx_id: block.txs[0].id,
+                step_index,
+                field: "gas_left",
+                expected: actual - 1,
+                actual,
+            })
+        );
+        assert!(run_test_circuit_incomplete_fixed_table(block.clone()).is_err());
+        assert!(matches!(
+            test_circuits_using_witness_block(block, BytecodeTestConfig::default()),
+            Err(TestCircuitsError::InconsistentWitness(
+                StepTransitionMismatch {
+                    field: "gas_left",
+                    ..
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn gas_cost_over_gas_left_is_rejected() {
+        use crate::{
+            evm_circuit::witness::StepTransitionMismatch,
+            test_util::{
+                build_witness_block, test_circuits_using_witness_block, BytecodeTestConfig,
+                TestCircuitsError,
+            },
+        };
+        use eth_types::{bytecode, evm_types::OpcodeId};
+        use mock::TestContext;
+
+        let code = bytecode! {
+            PUSH1(0x00)
+            STOP
+        };
+        let mut block =
+            build_witness_block(TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap());
+        let step_index = block.txs[0]
+            .steps
+            .iter()
+            .position(|step| step.opcode == Some(OpcodeId::PUSH1))
+            .unwrap();
+        let step = &mut block.txs[0].steps[step_index];
+        step.gas_cost = step.gas_left + 1;
+        let (gas_left, gas_cost) = (step.gas_left, step.gas_cost);
+        let tx_id = block.txs[0].id;
+
+        assert_eq!(
+            test_circuits_using_witness_block(block, BytecodeTestConfig::default()),
+            Err(TestCircuitsError::InconsistentWitness(
+                StepTransitionMismatch {
+                    tx_id,
+                    step_index,
+                    field: "gas_cost",
+                    expected: gas_left,
+                    actual: gas_cost,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn configure_with_more_power_of_randomness() {
+        use crate::test_util::{build_witness_block, get_fixed_table, FixedTableConfig};
+        use eth_types::{bytecode, geth_types::GethData};
+        use mock::TestContext;
+
+        let code = bytecode! {
+            PUSH32(Word::MAX)
+            PUSH1(0x00)
+            MSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let block = build_witness_block(block);
+
+        assert_eq!(
+            run_test_circuit_with_power_of_randomness::<_, 32>(
+                with_env_randomness_seed(block),
+                get_fixed_table(FixedTableConfig::Incomplete),
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "EvmCircuit requires at least 31 powers of randomness, but got 30")]
+    fn configure_with_too_few_power_of_randomness() {
+        use halo2_proofs::pairing::bn256::Fr;
+
+        TestCircuit::<Fr, 30>::configure(&mut ConstraintSystem::default());
+    }
+
+    /// Prove the multi-opcode program `code` on both circuits, which share the
+    /// rw table.
+    fn verify_code_with_shared_rw_table(code: eth_types::Bytecode) {
+        use crate::test_util::{build_witness_block, get_fixed_table, FixedTableConfig};
+        use eth_types::geth_types::GethData;
+        use mock::TestContext;
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let block = build_witness_block(block);
+
+        assert_eq!(
+            run_test_circuit_with_state_circuit(
+                block,
+                get_fixed_table(FixedTableConfig::Incomplete)
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn evm_and_state_circuits_share_rw_table() {
+        verify_code_with_shared_rw_table(eth_types::bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        });
+    }
+
+    #[test]
+    fn push_add_mstore_sload_sstore_prove_on_both_circuits() {
+        verify_code_with_shared_rw_table(eth_types::bytecode! {
+            // memory[0..32] = 0x20 + 0x03
+            PUSH1(0x03)
+            PUSH1(0x20)
+            ADD
+            PUSH1(0x00)
+            MSTORE
+            // storage[0] = storage[0] + 0x01
+            PUSH1(0x00)
+            SLOAD
+            PUSH1(0x01)
+            ADD
+            PUSH1(0x00)
+            SSTORE
+            // Read back the written slot, which is warm now
+            PUSH1(0x00)
+            SLOAD
+            STOP
+        });
+    }
+
+    #[test]
+    fn step_budget_exceeded() {
+        use crate::{
+            evm_circuit::RowBudgetExceeded,
+            test_util::{build_witness_block, get_fixed_table, FixedTableConfig},
+        };
+        use eth_types::{evm_types::OpcodeId, geth_types::GethData, Bytecode};
+        use halo2_proofs::pairing::bn256::Fr;
+        use mock::TestContext;
+
+        let mut code = Bytecode::default();
+        for _ in 0..100 {
+            code.push(1, Word::one());
+            code.write_op(OpcodeId::POP);
+        }
+        code.write_op(OpcodeId::STOP);
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut block = with_env_randomness_seed(build_witness_block(block));
+
+        let mut cs = ConstraintSystem::default();
+        let config = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::configure(&mut cs);
+        let needed = config.evm_circuit.get_num_rows_required(&block);
+
+        block.max_evm_rows = needed;
+        assert_eq!(config.evm_circuit.check_row_budget(&block), Ok(()));
+
+        block.max_evm_rows = 64;
+        let err = config.evm_circuit.check_row_budget(&block).unwrap_err();
+        assert_eq!(err, RowBudgetExceeded { needed, budget: 64 });
+        assert_eq!(
+            err.to_string(),
+            format!("step budget exceeded (needed {}, have 64)", needed)
+        );
+
+        // The assignment fails with the budget check instead of running out of
+        // rows
+        let k = 14;
+        assert!(needed < 1 << k);
+        let power_of_randomness = (1..=MIN_N_POWER_OF_RANDOMNESS as u64)
+            .map(|exp| vec![block.randomness.pow(&[exp, 0, 0, 0]); (1 << k) - 64])
+            .collect();
+        let circuit = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::new(
+            block,
+            get_fixed_table(FixedTableConfig::Incomplete),
+        );
+        assert!(matches!(
+            MockProver::<Fr>::run(k, &circuit, power_of_randomness),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn memory_only_block_requires_few_fixed_tables() {
+        use crate::test_util::build_witness_block;
+        use eth_types::{bytecode, geth_types::GethData};
+        use mock::TestContext;
+
+        let code = bytecode! {
+            PUSH32(Word::from(0xcafe))
+            PUSH1(0x40)
+            MSTORE
+            PUSH1(0x40)
+            MLOAD
+            PUSH32(Word::from(0xbe))
+            PUSH1(0x00)
+            MSTORE8
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let block = build_witness_block(block);
+
+        let mut cs = ConstraintSystem::default();
+        let config = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::configure(&mut cs);
+        let tags = config.evm_circuit.required_fixed_table_tags(&block);
+
+        for tag in [
+            FixedTableTag::Range1024,
+            FixedTableTag::SignByte,
+            FixedTableTag::BitwiseAnd,
+            FixedTableTag::BitwiseOr,
+            FixedTableTag::BitwiseXor,
+        ] {
+            assert!(!tags.contains(&tag), "{:?} not required", tag);
+        }
+        // And the block verifies with just the required tables
+        assert_eq!(run_test_circuit(block, tags), Ok(()));
+    }
+
+    #[test]
+    fn block_convert_skips_memory_word_ops() {
+        use crate::evm_circuit::{table::RwTableTag, witness::block_convert};
+        use bus_mapping::{circuit_input_builder::MemoryOpGranularity, mock::BlockData};
+        use eth_types::{bytecode, geth_types::GethData};
+        use mock::TestContext;
+
+        let code = bytecode! {
+            PUSH1(0x00)
+            MLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.memory_op_granularity = MemoryOpGranularity::Word;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // The word read of MLOAD is left out instead of panicking
+        let block = block_convert(&builder.block, &builder.code_db);
+        let mload = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::MEMORY)
+            .unwrap();
+        assert_eq!(
+            mload
+                .rw_indices
+                .iter()
+                .map(|(tag, _)| *tag)
+                .collect::<Vec<_>>(),
+            [RwTableTag::Stack, RwTableTag::Stack]
+        );
+    }
+
+    #[test]
+    fn rw_counter_deltas_match_execution_states() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let config = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::configure(&mut cs);
+        let mismatches = config.evm_circuit.rw_counter_delta_mismatches();
+        assert!(
+            mismatches.is_empty(),
+            "rw_counter deltas of {:?} differ from the ones in ExecutionState::rw_counter_deltas",
+            mismatches
+        );
+    }
+
+    #[cfg(not(feature = "strict"))]
+    #[test]
+    fn stub_states() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let config = TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::configure(&mut cs);
+        assert_eq!(
+            config.evm_circuit.stub_states(),
+            [ExecutionState::BeginTx, ExecutionState::PrecompileEcrecover]
+        );
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    #[should_panic(expected = "missing constraints for the execution states")]
+    fn strict_rejects_stub_states() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        TestCircuit::<Fr, MIN_N_POWER_OF_RANDOMNESS>::configure(&mut cs);
+    }
+
+    #[test]
+    fn witnesses_from_builder_prove_both_circuits() {
+        use crate::{
+            evm_circuit::witness::Witnesses,
+            state_circuit::StateCircuit,
+            test_util::{get_fixed_table, FixedTableConfig},
+        };
+        use bus_mapping::mock::BlockData;
+        use eth_types::{bytecode, geth_types::GethData};
+        use halo2_proofs::pairing::bn256::Fr;
+        use mock::TestContext;
+
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            SLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let Witnesses { block, rws } = Witnesses::from_builder(&builder, Fr::from(0x100)).unwrap();
+
+        // The state circuit rows are the ones of the evm circuit, with the same
+        // rw numbering, plus the seeds of the committed storage values
+        for (tag, block_rws) in block.rws.0.iter() {
+            let (state_rws, seeds) = rws.0[tag].split_at(block_rws.len());
+            assert_eq!(
+                state_rws
+                    .iter()
+                    .map(|rw| rw.rw_counter())
+                    .collect::<Vec<_>>(),
+                block_rws
+                    .iter()
+                    .map(|rw| rw.rw_counter())
+                    .collect::<Vec<_>>()
+            );
+            assert!(seeds.iter().all(|rw| rw.rw_counter() == 0));
+        }
+
+        let randomness = block.randomness;
+        assert_eq!(
+            run_test_circuit(block, get_fixed_table(FixedTableConfig::Incomplete)),
+            Ok(())
+        );
+
+        let circuit = StateCircuit::new(randomness, rws);
+        let power_of_randomness = circuit.instance();
+        let prover = MockProver::<Fr>::run(18, &circuit, power_of_randomness).unwrap();
+        let n_rows = circuit.rows.len() + 1;
+        assert_eq!(prover.verify_at_rows(0..n_rows, 0..n_rows), Ok(()));
     }
 }
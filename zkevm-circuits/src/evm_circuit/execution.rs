@@ -3,7 +3,7 @@ use crate::{
     evm_circuit::{
         param::{MAX_STEP_HEIGHT, STEP_WIDTH},
         step::{ExecutionState, Step},
-        table::{LookupTable, Table},
+        table::{FixedTableTag, LookupTable, Table},
         util::{
             constraint_builder::{BaseConstraintBuilder, ConstraintBuilder},
             rlc, CellType,
@@ -19,7 +19,10 @@ use halo2_proofs::{
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
     poly::Rotation,
 };
-use std::{collections::HashMap, convert::TryInto, iter};
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+};
 use strum::IntoEnumIterator;
 
 mod add_sub;
@@ -41,7 +44,12 @@ mod copy_to_log;
 mod dup;
 mod end_block;
 mod end_tx;
+mod error_depth;
+mod error_insufficient_balance;
+mod error_invalid_jump;
+mod error_invalid_opcode;
 mod error_oog_static_memory;
+mod error_return_data_out_of_bound;
 mod extcodehash;
 mod gas;
 mod gasprice;
@@ -55,9 +63,14 @@ mod memory_copy;
 mod msize;
 mod mul_div_mod;
 mod origin;
+mod padding;
 mod pc;
 mod pop;
+mod precompile_ecrecover;
+mod precompile_identity;
 mod push;
+mod returndatacopy;
+mod returndatasize;
 mod selfbalance;
 mod signed_comparator;
 mod signextend;
@@ -85,7 +98,12 @@ use copy_to_log::CopyToLogGadget;
 use dup::DupGadget;
 use end_block::EndBlockGadget;
 use end_tx::EndTxGadget;
+use error_depth::ErrorDepthGadget;
+use error_insufficient_balance::ErrorInsufficientBalanceGadget;
+use error_invalid_jump::ErrorInvalidJumpGadget;
+use error_invalid_opcode::ErrorInvalidOpcodeGadget;
 use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
+use error_return_data_out_of_bound::ErrorReturnDataOutOfBoundGadget;
 use extcodehash::ExtcodehashGadget;
 use gas::GasGadget;
 use gasprice::GasPriceGadget;
@@ -99,9 +117,14 @@ use memory_copy::CopyToMemoryGadget;
 use msize::MsizeGadget;
 use mul_div_mod::MulDivModGadget;
 use origin::OriginGadget;
+use padding::PaddingGadget;
 use pc::PcGadget;
 use pop::PopGadget;
+use precompile_ecrecover::PrecompileEcrecoverGadget;
+use precompile_identity::PrecompileIdentityGadget;
 use push::PushGadget;
+use returndatacopy::ReturnDataCopyGadget;
+use returndatasize::ReturnDataSizeGadget;
 use selfbalance::SelfbalanceGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
@@ -128,6 +151,57 @@ pub(crate) trait ExecutionGadget<F: FieldExt> {
     ) -> Result<(), Error>;
 }
 
+/// Error of assigning an execution gadget, which keeps the name of the gadget
+/// and the offset of the step for diagnosing which witness is bad.
+#[derive(Debug)]
+pub(crate) struct GadgetAssignError {
+    pub(crate) gadget: &'static str,
+    pub(crate) offset: usize,
+    pub(crate) error: String,
+}
+
+impl GadgetAssignError {
+    pub(crate) fn new(gadget: &'static str, offset: usize, error: impl std::fmt::Debug) -> Self {
+        Self {
+            gadget,
+            offset,
+            error: format!("{:?}", error),
+        }
+    }
+}
+
+impl std::fmt::Display for GadgetAssignError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to assign {} at offset {}: {}",
+            self.gadget, self.offset, self.error
+        )
+    }
+}
+
+impl std::error::Error for GadgetAssignError {}
+
+impl From<GadgetAssignError> for Error {
+    /// The synthesis only accepts [`Error`], so the context is logged before
+    /// it's dropped.
+    fn from(err: GadgetAssignError) -> Self {
+        log::error!("{}", err);
+        Error::Synthesis
+    }
+}
+
+/// Extension to attach the gadget name and offset to an assignment error.
+pub(crate) trait GadgetAssignResultExt<T> {
+    fn with_gadget(self, gadget: &'static str, offset: usize) -> Result<T, GadgetAssignError>;
+}
+
+impl<T, E: std::fmt::Debug> GadgetAssignResultExt<T> for Result<T, E> {
+    fn with_gadget(self, gadget: &'static str, offset: usize) -> Result<T, GadgetAssignError> {
+        self.map_err(|error| GadgetAssignError::new(gadget, offset, error))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct ExecutionConfig<F> {
     q_usable: Selector,
@@ -140,11 +214,16 @@ pub(crate) struct ExecutionConfig<F> {
     step: Step<F>,
     // internal state gadgets
     height_map: HashMap<ExecutionState, usize>,
+    fixed_table_tags_map: HashMap<ExecutionState, Vec<FixedTableTag>>,
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
+    stub_states: Vec<ExecutionState>,
+    rw_counter_delta_mismatches: Vec<ExecutionState>,
+    num_power_of_randomness: usize,
     begin_tx_gadget: BeginTxGadget<F>,
     copy_to_memory_gadget: CopyToMemoryGadget<F>,
     end_block_gadget: EndBlockGadget<F>,
     end_tx_gadget: EndTxGadget<F>,
+    padding_gadget: PaddingGadget<F>,
     // opcode gadgets
     add_sub_gadget: AddSubGadget<F>,
     bitwise_gadget: BitwiseGadget<F>,
@@ -176,6 +255,8 @@ pub(crate) struct ExecutionConfig<F> {
     pc_gadget: PcGadget<F>,
     pop_gadget: PopGadget<F>,
     push_gadget: PushGadget<F>,
+    returndatacopy_gadget: ReturnDataCopyGadget<F>,
+    returndatasize_gadget: ReturnDataSizeGadget<F>,
     selfbalance_gadget: SelfbalanceGadget<F>,
     signed_comparator_gadget: SignedComparatorGadget<F>,
     signextend_gadget: SignextendGadget<F>,
@@ -186,21 +267,30 @@ pub(crate) struct ExecutionConfig<F> {
     block_ctx_u64_gadget: BlockCtxU64Gadget<F>,
     block_ctx_u160_gadget: BlockCtxU160Gadget<F>,
     block_ctx_u256_gadget: BlockCtxU256Gadget<F>,
+    // precompile gadgets
+    precompile_ecrecover_gadget: PrecompileEcrecoverGadget<F>,
+    precompile_identity_gadget: PrecompileIdentityGadget<F>,
     // error gadgets
+    error_depth_gadget: ErrorDepthGadget<F>,
+    error_insufficient_balance_gadget: ErrorInsufficientBalanceGadget<F>,
+    error_invalid_jump_gadget: ErrorInvalidJumpGadget<F>,
+    error_invalid_opcode_gadget: ErrorInvalidOpcodeGadget<F>,
     error_oog_static_memory_gadget: ErrorOOGStaticMemoryGadget<F>,
+    error_return_data_out_of_bound_gadget: ErrorReturnDataOutOfBoundGadget<F>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn configure(
         meta: &mut ConstraintSystem<F>,
-        power_of_randomness: [Expression<F>; 31],
+        power_of_randomness: &[Expression<F>],
         fixed_table: &dyn LookupTable<F>,
         byte_table: &dyn LookupTable<F>,
         tx_table: &dyn LookupTable<F>,
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
+        ecrecover_table: &dyn LookupTable<F>,
     ) -> Self {
         let q_usable = meta.complex_selector();
         let q_step = meta.advice_column();
@@ -212,6 +302,9 @@ impl<F: Field> ExecutionConfig<F> {
 
         let step_curr = Step::new(meta, advices, 0);
         let mut height_map = HashMap::new();
+        let mut fixed_table_tags_map = HashMap::new();
+        let mut stub_states = Vec::new();
+        let mut rw_counter_delta_mismatches = Vec::new();
 
         meta.create_gate("Constrain execution state", |meta| {
             let q_usable = meta.query_selector(q_usable);
@@ -311,11 +404,14 @@ impl<F: Field> ExecutionConfig<F> {
                     num_rows_until_next_step,
                     q_step_first,
                     q_step_last,
-                    &power_of_randomness,
+                    power_of_randomness,
                     &step_curr,
                     &step_next,
                     &mut height_map,
+                    &mut fixed_table_tags_map,
                     &mut stored_expressions_map,
+                    &mut stub_states,
+                    &mut rw_counter_delta_mismatches,
                 )
             };
         }
@@ -336,6 +432,7 @@ impl<F: Field> ExecutionConfig<F> {
             copy_to_log_gadget: configure_gadget!(),
             end_block_gadget: configure_gadget!(),
             end_tx_gadget: configure_gadget!(),
+            padding_gadget: configure_gadget!(),
             // opcode gadgets
             add_sub_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
@@ -365,6 +462,8 @@ impl<F: Field> ExecutionConfig<F> {
             pc_gadget: configure_gadget!(),
             pop_gadget: configure_gadget!(),
             push_gadget: configure_gadget!(),
+            returndatacopy_gadget: configure_gadget!(),
+            returndatasize_gadget: configure_gadget!(),
             selfbalance_gadget: configure_gadget!(),
             signed_comparator_gadget: configure_gadget!(),
             signextend_gadget: configure_gadget!(),
@@ -375,14 +474,34 @@ impl<F: Field> ExecutionConfig<F> {
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
+            // precompile gadgets
+            precompile_ecrecover_gadget: configure_gadget!(),
+            precompile_identity_gadget: configure_gadget!(),
             // error gadgets
+            error_depth_gadget: configure_gadget!(),
+            error_insufficient_balance_gadget: configure_gadget!(),
+            error_invalid_jump_gadget: configure_gadget!(),
+            error_invalid_opcode_gadget: configure_gadget!(),
             error_oog_static_memory_gadget: configure_gadget!(),
+            error_return_data_out_of_bound_gadget: configure_gadget!(),
 
             // step and presets
             step: step_curr,
             height_map,
+            fixed_table_tags_map,
             stored_expressions_map,
+            stub_states,
+            rw_counter_delta_mismatches,
+            num_power_of_randomness: power_of_randomness.len(),
         };
+        // With the strict feature, a circuit relying on the incomplete
+        // constraints of some execution states can't be configured at all.
+        #[cfg(feature = "strict")]
+        assert!(
+            config.stub_states.is_empty(),
+            "missing constraints for the execution states {:?}",
+            config.stub_states
+        );
 
         Self::configure_lookup(
             meta,
@@ -392,7 +511,8 @@ impl<F: Field> ExecutionConfig<F> {
             rw_table,
             bytecode_table,
             block_table,
-            &power_of_randomness,
+            ecrecover_table,
+            power_of_randomness,
             &cell_manager,
         );
 
@@ -406,6 +526,39 @@ impl<F: Field> ExecutionConfig<F> {
             .unwrap_or_else(|| panic!("Execution state unknown: {:?}", execution_state))
     }
 
+    /// Execution states whose constraints are known to be incomplete, so the
+    /// circuit isn't sound for their steps yet.
+    pub(crate) fn stub_states(&self) -> &[ExecutionState] {
+        &self.stub_states
+    }
+
+    /// Execution states whose gadgets declare rw_counter deltas different from
+    /// the ones in [`ExecutionState::rw_counter_deltas`].
+    pub(crate) fn rw_counter_delta_mismatches(&self) -> &[ExecutionState] {
+        &self.rw_counter_delta_mismatches
+    }
+
+    /// The fixed table tags looked up by the execution states of the steps in
+    /// the block, which is the minimal set of fixed tables to load for it.
+    /// [`FixedTableTag::Zero`] is always required, as the disabled lookups
+    /// look it up.
+    pub fn required_fixed_table_tags(&self, block: &Block<F>) -> Vec<FixedTableTag> {
+        let mut tags = HashSet::new();
+        tags.insert(FixedTableTag::Zero);
+        for step in block.txs.iter().flat_map(|tx| tx.steps.iter()) {
+            tags.extend(
+                self.fixed_table_tags_map
+                    .get(&step.execution_state)
+                    .unwrap_or_else(|| {
+                        panic!("Execution state unknown: {:?}", step.execution_state)
+                    }),
+            );
+        }
+        FixedTableTag::iter()
+            .filter(|tag| tags.contains(tag))
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn configure_gadget<G: ExecutionGadget<F>>(
         meta: &mut ConstraintSystem<F>,
@@ -415,11 +568,14 @@ impl<F: Field> ExecutionConfig<F> {
         num_rows_until_next_step: Column<Advice>,
         q_step_first: Selector,
         q_step_last: Selector,
-        power_of_randomness: &[Expression<F>; 31],
+        power_of_randomness: &[Expression<F>],
         step_curr: &Step<F>,
         step_next: &Step<F>,
         height_map: &mut HashMap<ExecutionState, usize>,
+        fixed_table_tags_map: &mut HashMap<ExecutionState, Vec<FixedTableTag>>,
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
+        stub_states: &mut Vec<ExecutionState>,
+        rw_counter_delta_mismatches: &mut Vec<ExecutionState>,
     ) -> G {
         // Configure the gadget with the max height first so we can find out the actual
         // height
@@ -459,6 +615,20 @@ impl<F: Field> ExecutionConfig<F> {
             (height - 1).expr(),
         );
 
+        fixed_table_tags_map.insert(G::EXECUTION_STATE, cb.fixed_table_tags().to_vec());
+        if cb.is_stub() {
+            stub_states.push(G::EXECUTION_STATE);
+        }
+        if let Some(deltas) = G::EXECUTION_STATE.rw_counter_deltas() {
+            if !cb
+                .rw_counter_deltas()
+                .iter()
+                .map(evaluate_constant)
+                .eq(deltas.into_iter().map(|delta| Some(F::from(delta))))
+            {
+                rw_counter_delta_mismatches.push(G::EXECUTION_STATE);
+            }
+        }
         let (constraints, constraints_first_step, stored_expressions, _) = cb.build();
         debug_assert!(
             !height_map.contains_key(&G::EXECUTION_STATE),
@@ -502,14 +672,23 @@ impl<F: Field> ExecutionConfig<F> {
                 .chain(
                     IntoIterator::into_iter([
                         (
-                            "EndTx can only transit to BeginTx or EndBlock",
+                            "EndTx can only transit to BeginTx, Padding or EndBlock",
                             ExecutionState::EndTx,
-                            vec![ExecutionState::BeginTx, ExecutionState::EndBlock],
+                            vec![
+                                ExecutionState::BeginTx,
+                                ExecutionState::Padding,
+                                ExecutionState::EndBlock,
+                            ],
                         ),
                         (
-                            "EndBlock can only transit to EndBlock",
+                            "Padding can only transit to Padding or EndBlock",
+                            ExecutionState::Padding,
+                            vec![ExecutionState::Padding, ExecutionState::EndBlock],
+                        ),
+                        (
+                            "EndBlock can only transit to EndBlock or Padding",
                             ExecutionState::EndBlock,
-                            vec![ExecutionState::EndBlock],
+                            vec![ExecutionState::EndBlock, ExecutionState::Padding],
                         ),
                     ])
                     .filter(move |(_, from, _)| *from == G::EXECUTION_STATE)
@@ -533,14 +712,33 @@ impl<F: Field> ExecutionConfig<F> {
                                 .collect(),
                         ),
                         (
-                            "Only EndTx or EndBlock can transit to EndBlock",
+                            "Only EndTx, Padding or EndBlock can transit to Padding",
+                            ExecutionState::Padding,
+                            vec![
+                                ExecutionState::EndTx,
+                                ExecutionState::Padding,
+                                ExecutionState::EndBlock,
+                            ],
+                        ),
+                        (
+                            "Only EndTx, Padding or EndBlock can transit to EndBlock",
                             ExecutionState::EndBlock,
-                            vec![ExecutionState::EndTx, ExecutionState::EndBlock],
+                            vec![
+                                ExecutionState::EndTx,
+                                ExecutionState::Padding,
+                                ExecutionState::EndBlock,
+                            ],
                         ),
                         (
                             "Only ExecutionState which copies memory to memory can transit to CopyToMemory",
                             ExecutionState::CopyToMemory,
-                            vec![ExecutionState::CopyToMemory, ExecutionState::CALLDATACOPY],
+                            vec![
+                                ExecutionState::CopyToMemory,
+                                ExecutionState::CALLDATACOPY,
+                                ExecutionState::RETURNDATACOPY,
+                                ExecutionState::PrecompileEcrecover,
+                                ExecutionState::PrecompileIdentity,
+                            ],
                         ),
                     ])
                     .filter(move |(_, _, from)| !from.contains(&G::EXECUTION_STATE))
@@ -572,7 +770,8 @@ impl<F: Field> ExecutionConfig<F> {
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
-        power_of_randomness: &[Expression<F>; 31],
+        ecrecover_table: &dyn LookupTable<F>,
+        power_of_randomness: &[Expression<F>],
         cell_manager: &CellManager<F>,
     ) {
         for column in cell_manager.columns().iter() {
@@ -586,6 +785,7 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::Bytecode => bytecode_table,
                         Table::Block => block_table,
                         Table::Byte => byte_table,
+                        Table::Ecrecover => ecrecover_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -606,11 +806,9 @@ impl<F: Field> ExecutionConfig<F> {
         block: &Block<F>,
         _exact: bool,
     ) -> Result<(), Error> {
-        let power_of_randomness = (1..32)
+        let power_of_randomness = (1..=self.num_power_of_randomness as u64)
             .map(|exp| block.randomness.pow(&[exp, 0, 0, 0]))
-            .collect::<Vec<F>>()
-            .try_into()
-            .unwrap();
+            .collect::<Vec<F>>();
 
         layouter.assign_region(
             || "Execution step",
@@ -641,7 +839,7 @@ impl<F: Field> ExecutionConfig<F> {
                         step,
                         height,
                         steps.peek(),
-                        power_of_randomness,
+                        &power_of_randomness,
                     )?;
 
                     // q_step logic
@@ -711,7 +909,7 @@ impl<F: Field> ExecutionConfig<F> {
         step: &ExecStep,
         height: usize,
         next: Option<&(&Transaction, &ExecStep)>,
-        power_of_randomness: [F; 31],
+        power_of_randomness: &[F],
     ) -> Result<(), Error> {
         // Make the region large enough for the current step and the next step.
         // The next step's next step may also be accessed, so make the region large
@@ -769,7 +967,15 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::CopyToLog => assign_exec_step!(self.copy_to_log_gadget),
             ExecutionState::CopyToMemory => assign_exec_step!(self.copy_to_memory_gadget),
             ExecutionState::EndTx => assign_exec_step!(self.end_tx_gadget),
+            ExecutionState::Padding => assign_exec_step!(self.padding_gadget),
             ExecutionState::EndBlock => assign_exec_step!(self.end_block_gadget),
+            // precompiles
+            ExecutionState::PrecompileEcrecover => {
+                assign_exec_step!(self.precompile_ecrecover_gadget)
+            }
+            ExecutionState::PrecompileIdentity => {
+                assign_exec_step!(self.precompile_identity_gadget)
+            }
             // opcode
             ExecutionState::ADD_SUB => assign_exec_step!(self.add_sub_gadget),
             ExecutionState::BITWISE => assign_exec_step!(self.bitwise_gadget),
@@ -799,6 +1005,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::PC => assign_exec_step!(self.pc_gadget),
             ExecutionState::POP => assign_exec_step!(self.pop_gadget),
             ExecutionState::PUSH => assign_exec_step!(self.push_gadget),
+            ExecutionState::RETURNDATACOPY => assign_exec_step!(self.returndatacopy_gadget),
+            ExecutionState::RETURNDATASIZE => assign_exec_step!(self.returndatasize_gadget),
             ExecutionState::SCMP => assign_exec_step!(self.signed_comparator_gadget),
             ExecutionState::BLOCKCTXU64 => assign_exec_step!(self.block_ctx_u64_gadget),
             ExecutionState::BLOCKCTXU160 => assign_exec_step!(self.block_ctx_u160_gadget),
@@ -810,9 +1018,22 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
             // errors
+            ExecutionState::ErrorDepth => assign_exec_step!(self.error_depth_gadget),
+            ExecutionState::ErrorInsufficientBalance => {
+                assign_exec_step!(self.error_insufficient_balance_gadget)
+            }
+            ExecutionState::ErrorInvalidJump => {
+                assign_exec_step!(self.error_invalid_jump_gadget)
+            }
+            ExecutionState::ErrorInvalidOpcode => {
+                assign_exec_step!(self.error_invalid_opcode_gadget)
+            }
             ExecutionState::ErrorOutOfGasStaticMemoryExpansion => {
                 assign_exec_step!(self.error_oog_static_memory_gadget)
             }
+            ExecutionState::ErrorReturnDataOutOfBound => {
+                assign_exec_step!(self.error_return_data_out_of_bound_gadget)
+            }
             _ => unimplemented!(),
         }
 
@@ -828,3 +1049,18 @@ impl<F: Field> ExecutionConfig<F> {
         Ok(())
     }
 }
+
+/// Evaluate the expression when it doesn't query any column
+fn evaluate_constant<F: FieldExt>(expr: &Expression<F>) -> Option<F> {
+    expr.evaluate(
+        &|scalar| Some(scalar),
+        &|_| None,
+        &|_, _, _| None,
+        &|_, _, _| None,
+        &|_, _, _| None,
+        &|a: Option<F>| a.map(|a| -a),
+        &|a, b| a.zip(b).map(|(a, b)| a + b),
+        &|a, b| a.zip(b).map(|(a, b)| a * b),
+        &|a, scalar| a.map(|a| a * scalar),
+    )
+}
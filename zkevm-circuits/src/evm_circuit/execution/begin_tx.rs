@@ -33,6 +33,7 @@ pub(crate) struct BeginTxGadget<F> {
     tx_value: Word<F>,
     tx_call_data_length: Cell<F>,
     tx_call_data_gas_cost: Cell<F>,
+    tx_access_list_gas_cost: Cell<F>,
     reversion_info: ReversionInfo<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
@@ -62,6 +63,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 TxContextFieldTag::CallDataGasCost,
             ]
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+        // TODO: Tie the access list gas cost, and the access list seeds of the
+        // state circuit, to the access list of the transaction. Until then
+        // both are trusted from the witness, so the gadget is a stub.
+        let tx_access_list_gas_cost =
+            cb.tx_context(tx_id.expr(), TxContextFieldTag::AccessListGasCost, None);
+        cb.stub();
         let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
             .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
 
@@ -88,13 +95,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let mul_gas_fee_by_gas =
             MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), tx_gas.expr());
 
-        // TODO: Take gas cost of access list (EIP 2930) into consideration.
-        // Use intrinsic gas
+        // Use intrinsic gas, including the gas cost of access list (EIP 2930)
         let intrinsic_gas_cost = select::expr(
             tx_is_create.expr(),
             GasCost::CREATION_TX.expr(),
             GasCost::TX.expr(),
-        ) + tx_call_data_gas_cost.expr();
+        ) + tx_call_data_gas_cost.expr()
+            + tx_access_list_gas_cost.expr();
 
         // Check gas_left is sufficient
         let gas_left = tx_gas.expr() - intrinsic_gas_cost;
@@ -203,6 +210,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_value,
             tx_call_data_length,
             tx_call_data_gas_cost,
+            tx_access_list_gas_cost,
             reversion_info,
             sufficient_gas_left,
             transfer_with_gas_fee,
@@ -246,6 +254,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         )?;
         self.tx_call_data_gas_cost
             .assign(region, offset, Some(F::from(tx.call_data_gas_cost)))?;
+        self.tx_access_list_gas_cost.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_gas_cost)),
+        )?;
         self.reversion_info.assign(
             region,
             offset,
@@ -276,12 +289,18 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::{
-        test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
-        witness::block_convert,
+    use crate::{
+        evm_circuit::{
+            test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
+            witness::block_convert,
+        },
+        test_util::run_test_circuits,
     };
     use bus_mapping::{evm::OpcodeId, mock::BlockData};
-    use eth_types::{self, bytecode, evm_types::GasCost, geth_types::GethData, Word};
+    use eth_types::{
+        self, address, bytecode, evm_types::GasCost, geth_types::GethData, ToWord, Word, H256,
+    };
+    use ethers_core::types::transaction::eip2930::{AccessList, AccessListItem};
     use mock::{
         eth, gwei, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
     };
@@ -368,6 +387,53 @@ mod test {
         );
     }
 
+    #[test]
+    fn begin_tx_gadget_access_list() {
+        // The callee and the storage slot are in the access list, so the CALL
+        // and the SLOAD are both warm, and the access list is charged in the
+        // intrinsic gas
+        let callee = address!("0x00000000000000000000000000000000000000aa");
+        let code = bytecode! {
+            PUSH1(0)
+            SLOAD
+            PUSH1(0) // rd_length
+            PUSH1(0) // rd_offset
+            PUSH1(0) // cd_length
+            PUSH1(0) // cd_offset
+            PUSH1(0) // value
+            PUSH20(callee.to_word())
+            PUSH2(10000) // gas
+            CALL
+            STOP
+        };
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(callee).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .access_list(AccessList(vec![
+                        AccessListItem {
+                            address: accs[0].address,
+                            storage_keys: vec![H256::zero()],
+                        },
+                        AccessListItem {
+                            address: callee,
+                            storage_keys: vec![],
+                        },
+                    ]));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
     #[test]
     fn begin_tx_gadget_rand() {
         let random_amount = Word::from_little_endian(&rand_bytes(32)) % eth(1);
@@ -1,13 +1,13 @@
 use crate::{
     evm_circuit::{
-        execution::ExecutionGadget,
+        execution::{ExecutionGadget, GadgetAssignResultExt},
         param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64, N_BYTES_WORD},
         step::ExecutionState,
         table::BlockContextFieldTag,
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            from_bytes, CachedRegion, RandomLinearCombination,
+            CachedRegion, RandomLinearCombination,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -27,7 +27,17 @@ pub(crate) struct BlockCtxGadget<F, const N_BYTES: usize> {
 
 impl<F: Field, const N_BYTES: usize> BlockCtxGadget<F, N_BYTES> {
     fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
-        let value = cb.query_rlc();
+        // A word doesn't fit in a field element, so it's only looked up by
+        // its RLC.
+        let (value, value_expr) = if N_BYTES == N_BYTES_WORD {
+            let value = cb.query_rlc();
+            let value_expr = value.expr();
+            (value, value_expr)
+        } else {
+            let (value_expr, bytes) = cb.bytes_to_value();
+            let value = RandomLinearCombination::new(bytes, cb.power_of_randomness());
+            (value, value_expr)
+        };
 
         // Push the const generic parameter N_BYTES value to the stack
         cb.stack_push(value.expr());
@@ -39,11 +49,6 @@ impl<F: Field, const N_BYTES: usize> BlockCtxGadget<F, N_BYTES> {
 
         // Lookup block table with block context ops
         // TIMESTAMP/NUMBER/GASLIMIT, COINBASE and DIFFICULTY/BASEFEE
-        let value_expr = if N_BYTES == N_BYTES_WORD {
-            value.expr()
-        } else {
-            from_bytes::expr(&value.cells)
-        };
         cb.block_lookup(blockctx_tag, None, value_expr);
 
         // State transition
@@ -90,15 +95,16 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU64Gadget<F> {
     ) -> Result<(), Error> {
         self.value_u64
             .same_context
-            .assign_exec_step(region, offset, step)?;
+            .assign_exec_step(region, offset, step)
+            .with_gadget(Self::NAME, offset)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
+        let value = u64::try_from(value).with_gadget(Self::NAME, offset)?;
 
-        self.value_u64.value.assign(
-            region,
-            offset,
-            Some(u64::try_from(value).unwrap().to_le_bytes()),
-        )?;
+        self.value_u64
+            .value
+            .assign(region, offset, Some(value.to_le_bytes()))
+            .with_gadget(Self::NAME, offset)?;
 
         Ok(())
     }
@@ -190,9 +196,21 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::run_test_circuits;
-    use eth_types::bytecode;
-    use mock::TestContext;
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{block_convert, single_step_block, Block, Rw},
+        },
+        test_util::run_test_circuits,
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{HardFork, OpcodeId},
+        Hash, ToWord, Word,
+    };
+    use halo2_proofs::pairing::bn256::Fr;
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
     fn test_ok(bytecode: bytecode::Bytecode) {
         assert_eq!(
@@ -235,4 +253,118 @@ mod test {
         };
         test_ok(bytecode);
     }
+
+    fn u64_single_step_block(opcode: OpcodeId, value: Word) -> Block<Fr> {
+        single_step_block::<Fr>(
+            ExecutionState::BLOCKCTXU64,
+            opcode,
+            vec![Rw::Stack {
+                rw_counter: 1,
+                is_write: true,
+                call_id: 1,
+                stack_pointer: 1023,
+                value,
+            }],
+        )
+    }
+
+    fn u256_single_step_block(opcode: OpcodeId, value: Word) -> Block<Fr> {
+        single_step_block::<Fr>(
+            ExecutionState::BLOCKCTXU256,
+            opcode,
+            vec![Rw::Stack {
+                rw_counter: 1,
+                is_write: true,
+                call_id: 1,
+                stack_pointer: 1023,
+                value,
+            }],
+        )
+    }
+
+    #[test]
+    fn blockcxt_u64_gadget_single_step() {
+        let timestamp = Word::from(0x5f5e100u64);
+        let mut block = u64_single_step_block(OpcodeId::TIMESTAMP, timestamp);
+        block.context.timestamp = timestamp;
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn blockcxt_u64_gadget_number() {
+        let number = Word::from(0xcafeu64);
+        let mut block = u64_single_step_block(OpcodeId::NUMBER, number);
+        block.context.number = number;
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+
+        // The pushed value must be the number of the block
+        let mut block = u64_single_step_block(OpcodeId::NUMBER, number + 1);
+        block.context.number = number;
+        assert!(run_test_circuit_incomplete_fixed_table(block).is_err());
+    }
+
+    #[test]
+    fn blockcxt_u64_gadget_gaslimit() {
+        let gas_limit = u64::MAX;
+        let mut block = u64_single_step_block(OpcodeId::GASLIMIT, Word::from(gas_limit));
+        block.context.gas_limit = gas_limit;
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+
+        // The pushed value must be the gas limit of the block
+        let mut block = u64_single_step_block(OpcodeId::GASLIMIT, Word::from(gas_limit - 1));
+        block.context.gas_limit = gas_limit;
+        assert!(run_test_circuit_incomplete_fixed_table(block).is_err());
+    }
+
+    #[test]
+    fn blockcxt_u256_gadget_difficulty_by_fork() {
+        let difficulty = Word::from(0x20000u64);
+        let mix_hash = Hash::repeat_byte(0xab);
+        let block_data = bus_mapping::mock::BlockData::new_from_geth_data(
+            TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(bytecode! { DIFFICULTY STOP }),
+                |mut txs, accs| {
+                    txs[0].to(accs[0].address).from(accs[1].address);
+                },
+                |block, _tx| block.difficulty(difficulty).mix_hash(mix_hash),
+            )
+            .unwrap()
+            .into(),
+        );
+
+        for (fork, expected) in [
+            (HardFork::London, difficulty),
+            (HardFork::Merge, mix_hash.to_word()),
+        ] {
+            let mut builder = block_data.new_circuit_input_builder();
+            builder.block.fork = fork;
+            builder
+                .handle_block(&block_data.eth_block, &block_data.geth_traces)
+                .unwrap();
+            let block = block_convert::<Fr>(&builder.block, &builder.code_db);
+            assert_eq!(block.context.difficulty, expected);
+
+            // DIFFICULTY pushes the value of the fork
+            let mut single_step = u256_single_step_block(OpcodeId::DIFFICULTY, expected);
+            single_step.context = block.context.clone();
+            assert_eq!(run_test_circuit_incomplete_fixed_table(single_step), Ok(()));
+
+            let mut single_step = u256_single_step_block(OpcodeId::DIFFICULTY, expected + 1);
+            single_step.context = block.context;
+            assert!(run_test_circuit_incomplete_fixed_table(single_step).is_err());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Synthesis")]
+    fn block_ctx_u64_gadget_rejects_value_over_u64() {
+        // A TIMESTAMP which doesn't fit into u64 can't be assigned, and the
+        // gadget and the conversion error are logged before the synthesis
+        // fails
+        let timestamp = Word::from(u64::MAX) + 1;
+        let mut block = u64_single_step_block(OpcodeId::TIMESTAMP, timestamp);
+        block.context.timestamp = timestamp;
+        let _ = run_test_circuit_incomplete_fixed_table(block);
+    }
 }
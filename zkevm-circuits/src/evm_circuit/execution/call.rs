@@ -1,20 +1,20 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{
+            MAX_CALL_DEPTH, N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE,
+            PRECOMPILE_ECRECOVER, PRECOMPILE_IDENTITY,
+        },
         step::ExecutionState,
         table::{AccountFieldTag, CallContextFieldTag},
         util::{
-            common_gadget::TransferGadget,
+            common_gadget::{AccountEmptyGadget, TransferGadget},
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
             from_bytes,
-            math_gadget::{
-                BatchedIsZeroGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget,
-                MinMaxGadget,
-            },
+            math_gadget::{ConstantDivisionGadget, IsZeroGadget, MinMaxGadget, WordIsZeroGadget},
             memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
             select, sum, CachedRegion, Cell, Word,
         },
@@ -25,10 +25,9 @@ use crate::{
 use bus_mapping::evm::OpcodeId;
 use eth_types::{
     evm_types::{GasCost, GAS_STIPEND_CALL_WITH_VALUE},
-    Field, ToLittleEndian, ToScalar,
+    Field, ToAddress, ToLittleEndian, ToScalar,
 };
 use halo2_proofs::plonk::Error;
-use keccak256::EMPTY_HASH_LE;
 
 #[derive(Clone, Debug)]
 pub(crate) struct CallGadget<F> {
@@ -46,15 +45,16 @@ pub(crate) struct CallGadget<F> {
     is_warm: Cell<F>,
     is_warm_prev: Cell<F>,
     callee_reversion_info: ReversionInfo<F>,
-    value_is_zero: IsZeroGadget<F>,
+    value_is_zero: WordIsZeroGadget<F>,
     cd_address: MemoryAddressGadget<F>,
     rd_address: MemoryAddressGadget<F>,
     memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
     transfer: TransferGadget<F>,
     callee_nonce: Cell<F>,
     callee_code_hash: Cell<F>,
-    is_account_empty: BatchedIsZeroGadget<F, 2>,
-    is_empty_code_hash: IsEqualGadget<F>,
+    is_account_empty: AccountEmptyGadget<F>,
+    is_precompile_ecrecover: IsZeroGadget<F>,
+    is_precompile_identity: IsZeroGadget<F>,
     one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
     capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
 }
@@ -97,7 +97,9 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
         ]
         .map(|field_tag| cb.call_context(None, field_tag));
 
-        cb.range_lookup(depth.expr(), 1024);
+        // Verify depth is in range [1, 1024], otherwise the call fails with
+        // ErrorDepth
+        cb.range_lookup(depth.expr() - 1.expr(), MAX_CALL_DEPTH);
 
         // Lookup values from stack
         cb.stack_pop(gas_word.expr());
@@ -133,12 +135,14 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             Some(&mut reversion_info),
         );
 
-        // Propagate rw_counter_end_of_reversion and is_persistent
+        // Propagate rw_counter_end_of_reversion and is_persistent, and read
+        // the success flag pushed above from the callee
         let mut callee_reversion_info = cb.reversion_info(Some(callee_call_id.expr()));
-        cb.require_equal(
-            "callee_is_persistent == is_persistent ⋅ is_success",
-            callee_reversion_info.is_persistent(),
-            reversion_info.is_persistent() * is_success.expr(),
+        cb.callee_is_success(
+            callee_call_id.expr(),
+            is_success.expr(),
+            &reversion_info,
+            &callee_reversion_info,
         );
         cb.condition(is_success.expr() * (1.expr() - reversion_info.is_persistent()), |cb| {
             cb.require_equal(
@@ -149,7 +153,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
         });
 
         // Verify transfer
-        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let value_is_zero = WordIsZeroGadget::construct(cb, &value);
         let has_value = 1.expr() - value_is_zero.expr();
         cb.condition(has_value.clone(), |cb| {
             cb.require_zero(
@@ -172,21 +176,22 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
                 cb.account_read(callee_address.clone(), field_tag, value.expr());
                 value
             });
-        let is_account_empty = BatchedIsZeroGadget::construct(
-            cb,
-            [
-                callee_nonce.expr(),
-                transfer.receiver().balance_prev().expr(),
-            ],
-        );
-        let is_empty_code_hash = IsEqualGadget::construct(
+        let is_account_empty = AccountEmptyGadget::construct(
             cb,
+            callee_nonce.expr(),
+            transfer.receiver().balance_prev().expr(),
             callee_code_hash.expr(),
-            Word::random_linear_combine_expr(
-                (*EMPTY_HASH_LE).map(|byte| byte.expr()),
-                cb.power_of_randomness(),
-            ),
         );
+        // Precompiles have empty code but still enter the callee's context,
+        // which is handled by the precompile's own execution state.
+        // TODO: Dispatch other precompiles when they are implemented.
+        let is_precompile_ecrecover =
+            IsZeroGadget::construct(cb, callee_address.clone() - PRECOMPILE_ECRECOVER.expr());
+        let is_precompile_identity =
+            IsZeroGadget::construct(cb, callee_address.clone() - PRECOMPILE_IDENTITY.expr());
+        let is_empty_code = is_account_empty.is_empty_code_hash()
+            * (1.expr() - is_precompile_ecrecover.expr() - is_precompile_identity.expr());
+
         // Sum up gas cost
         let gas_cost = select::expr(
             is_warm_prev.expr(),
@@ -194,9 +199,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             GasCost::COLD_ACCOUNT_ACCESS.expr(),
         ) + has_value.clone()
             * (GasCost::CALL_WITH_VALUE.expr()
-                + is_account_empty.expr()
-                    * is_empty_code_hash.expr()
-                    * GasCost::NEW_ACCOUNT.expr())
+                + is_account_empty.expr() * GasCost::NEW_ACCOUNT.expr())
             + memory_expansion.gas_cost();
 
         // Apply EIP 150
@@ -210,9 +213,10 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             all_but_one_64th_gas,
         );
 
-        // TODO: Handle precompiled
+        cb.condition(is_empty_code.clone(), |cb| {
+            // A callee without code always succeeds
+            cb.require_equal("is_success == 1", is_success.expr(), 1.expr());
 
-        cb.condition(is_empty_code_hash.expr(), |cb| {
             // Save caller's call state
             for field_tag in [
                 CallContextFieldTag::LastCalleeId,
@@ -223,7 +227,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                rw_counter: Delta(24.expr()),
+                rw_counter: Delta(25.expr()),
                 program_counter: Delta(1.expr()),
                 stack_pointer: Delta(6.expr()),
                 gas_left: Delta(
@@ -235,7 +239,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             });
         });
 
-        cb.condition(1.expr() - is_empty_code_hash.expr(), |cb| {
+        cb.condition(1.expr() - is_empty_code, |cb| {
             // Save caller's call state
             for (field_tag, value) in [
                 (
@@ -274,7 +278,6 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
                 (CallContextFieldTag::ReturnDataOffset, rd_address.offset()),
                 (CallContextFieldTag::ReturnDataLength, rd_address.length()),
                 (CallContextFieldTag::Value, value.expr()),
-                (CallContextFieldTag::IsSuccess, is_success.expr()),
                 (CallContextFieldTag::IsStatic, is_static.expr()),
                 (CallContextFieldTag::LastCalleeId, 0.expr()),
                 (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
@@ -301,6 +304,17 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             });
         });
 
+        cb.constrain_next_step(
+            ExecutionState::PrecompileEcrecover,
+            Some(is_precompile_ecrecover.expr()),
+            |_| {},
+        );
+        cb.constrain_next_step(
+            ExecutionState::PrecompileIdentity,
+            Some(is_precompile_identity.expr()),
+            |_| {},
+        );
+
         Self {
             opcode,
             tx_id,
@@ -324,7 +338,8 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             callee_nonce,
             callee_code_hash,
             is_account_empty,
-            is_empty_code_hash,
+            is_precompile_ecrecover,
+            is_precompile_identity,
             one_64th_gas,
             capped_callee_gas_left,
         }
@@ -364,10 +379,10 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
         let (is_warm, is_warm_prev) = block.rws[step.rw_indices[14]].tx_access_list_value_pair();
         let [caller_balance_pair, callee_balance_pair, (callee_nonce, _), (callee_code_hash, _)] =
             [
-                step.rw_indices[17],
                 step.rw_indices[18],
                 step.rw_indices[19],
                 step.rw_indices[20],
+                step.rw_indices[21],
             ]
             .map(|idx| block.rws[idx].account_value_pair());
 
@@ -412,8 +427,7 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
             callee_rw_counter_end_of_reversion.low_u64() as usize,
             callee_is_persistent.low_u64() != 0,
         )?;
-        self.value_is_zero
-            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        self.value_is_zero.assign(region, offset, value)?;
         let cd_address =
             self.cd_address
                 .assign(region, offset, cd_offset, cd_length, block.randomness)?;
@@ -435,27 +449,28 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
         )?;
         self.callee_nonce
             .assign(region, offset, callee_nonce.to_scalar())?;
-        self.callee_code_hash.assign(
+        let callee_code_hash =
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness);
+        self.callee_code_hash
+            .assign(region, offset, Some(callee_code_hash))?;
+        let is_account_empty = self.is_account_empty.assign(
             region,
             offset,
-            Some(Word::random_linear_combine(
-                callee_code_hash.to_le_bytes(),
-                block.randomness,
-            )),
+            F::from(callee_nonce.low_u64()),
+            Word::random_linear_combine(callee_balance_pair.1.to_le_bytes(), block.randomness),
+            callee_code_hash,
+            block.randomness,
         )?;
-        let is_account_empty = self.is_account_empty.assign(
+        let callee_address_scalar: F = callee_address.to_address().to_scalar().unwrap();
+        self.is_precompile_ecrecover.assign(
             region,
             offset,
-            [
-                F::from(callee_nonce.low_u64()),
-                Word::random_linear_combine(callee_balance_pair.1.to_le_bytes(), block.randomness),
-            ],
+            callee_address_scalar - F::from(PRECOMPILE_ECRECOVER),
         )?;
-        self.is_empty_code_hash.assign(
+        self.is_precompile_identity.assign(
             region,
             offset,
-            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness),
-            Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness),
+            callee_address_scalar - F::from(PRECOMPILE_IDENTITY),
         )?;
         let has_value = !value.is_zero();
         let gas_cost = if is_warm_prev {
@@ -488,12 +503,14 @@ impl<F: Field> ExecutionGadget<F> for CallGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::evm_circuit::{
+        step::ExecutionState,
         test::{run_test_circuit_complete_fixed_table, run_test_circuit_incomplete_fixed_table},
-        witness::block_convert,
+        witness::{block_convert, Block},
     };
     use eth_types::{address, bytecode};
     use eth_types::{bytecode::Bytecode, evm_types::OpcodeId, geth_types::Account};
     use eth_types::{Address, ToWord, Word};
+    use halo2_proofs::pairing::bn256::Fr;
     use itertools::Itertools;
     use mock::TestContext;
     use std::default::Default;
@@ -558,7 +575,7 @@ mod test {
         }
     }
 
-    fn test_ok(caller: Account, callee: Account, use_complete_fixed_table: bool) {
+    fn test_block(caller: Account, callee: Account) -> Block<Fr> {
         let block = TestContext::<3, 1>::new(
             None,
             |accs| {
@@ -591,7 +608,11 @@ mod test {
         builder
             .handle_block(&block_data.eth_block, &block_data.geth_traces)
             .unwrap();
-        let block = block_convert(&builder.block, &builder.code_db);
+        block_convert(&builder.block, &builder.code_db)
+    }
+
+    fn test_ok(caller: Account, callee: Account, use_complete_fixed_table: bool) {
+        let block = test_block(caller, callee);
         assert_eq!(
             if use_complete_fixed_table {
                 run_test_circuit_complete_fixed_table(block)
@@ -650,6 +671,69 @@ mod test {
         }
     }
 
+    #[test]
+    fn call_gadget_stack_pointer_delta() {
+        let block = test_block(caller(Stack::default(), true), callee(bytecode! { STOP }));
+
+        // CALL pops 7 and pushes 1, so the caller's stack pointer is increased
+        // by 6 when it's resumed.
+        let steps = &block.txs[0].steps;
+        let call_steps = steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| step.execution_state == ExecutionState::CALL)
+            .collect::<Vec<_>>();
+        assert_eq!(call_steps.len(), 2);
+        for (idx, call_step) in call_steps {
+            let resumed_step = steps[idx + 1..]
+                .iter()
+                .find(|step| step.call_index == call_step.call_index)
+                .unwrap();
+            assert_eq!(resumed_step.stack_pointer, call_step.stack_pointer + 6);
+        }
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn call_gadget_memory_expansion_of_both_regions() {
+        use eth_types::evm_types::{gas_utils::memory_expansion_gas_cost, GasCost};
+
+        // The return region reaches 12 words, further than the 1 word of the
+        // args region, so the memory expands to 12 words at the first call.
+        let stack = Stack {
+            gas: 100000,
+            cd_offset: 0,
+            cd_length: 32,
+            rd_offset: 64,
+            rd_length: 320,
+            ..Default::default()
+        };
+        let block = test_block(caller(stack, true), callee(bytecode! { STOP }));
+
+        let steps = &block.txs[0].steps;
+        let idx = steps
+            .iter()
+            .position(|step| step.execution_state == ExecutionState::CALL)
+            .unwrap();
+        let (call_step, callee_step) = (&steps[idx], &steps[idx + 1]);
+        assert_eq!(call_step.memory_word_size(), 0);
+
+        // The callee gets all but one 64th of the gas left after the cost of
+        // the cold access and of the combined memory expansion
+        let gas_available = call_step.gas_left
+            - GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            - memory_expansion_gas_cost(0, 12);
+        assert_eq!(callee_step.gas_left, gas_available - gas_available / 64);
+        let resumed_step = steps[idx + 1..]
+            .iter()
+            .find(|step| step.call_index == call_step.call_index)
+            .unwrap();
+        assert_eq!(resumed_step.memory_word_size(), 12);
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
     #[test]
     fn call_gadget_nested() {
         let callers = vec![
@@ -680,6 +764,37 @@ mod test {
         }
     }
 
+    #[test]
+    fn call_gadget_success_flag() {
+        let stack = Stack {
+            gas: 100000,
+            ..Default::default()
+        };
+        for (callee, is_success) in [
+            (callee(bytecode! { PUSH1(0) PUSH1(0) RETURN }), 1),
+            (callee(bytecode! { PUSH1(0) PUSH1(0) REVERT }), 0),
+        ] {
+            let block = test_block(caller(stack, true), callee);
+
+            // The caller continues after the first call to do the second
+            // one, and both push the success flag of the callee.
+            let call_steps = block.txs[0]
+                .steps
+                .iter()
+                .filter(|step| step.execution_state == ExecutionState::CALL)
+                .collect::<Vec<_>>();
+            assert_eq!(call_steps.len(), 2);
+            for step in call_steps {
+                assert_eq!(
+                    block.rws[step.rw_indices[13]].stack_value(),
+                    Word::from(is_success)
+                );
+            }
+
+            assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+        }
+    }
+
     #[test]
     fn call_gadget_recursive() {
         test_ok(
@@ -236,8 +236,15 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::run_test_circuits};
-    use eth_types::{bytecode, ToWord, Word};
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::RwTableTag,
+            test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
+        },
+        test_util::{build_witness_block, run_test_circuits},
+    };
+    use eth_types::{bytecode, evm_types::GasCost, geth_types::GethData, ToWord, Word};
     use mock::test_ctx::{helpers::*, TestContext};
 
     fn test_ok_root(
@@ -352,4 +359,47 @@ mod test {
         test_ok_root(0x40, 0x40, 0x00, 0x00);
         test_ok_internal(0x40, 0x40, 0xA0, 0x10, 0x00);
     }
+
+    #[test]
+    fn calldatacopy_gadget_zero_length_has_no_memory_ops() {
+        // A zero length copy doesn't expand the memory, however large the
+        // memory offset is.
+        let bytecode = bytecode! {
+            PUSH32(0)
+            PUSH32(0)
+            PUSH32(0x1000)
+            CALLDATACOPY
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(rand_bytes(0x40).into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let block = build_witness_block(block);
+
+        let steps = &block.txs[0].steps;
+        let idx = steps
+            .iter()
+            .position(|step| step.execution_state == ExecutionState::CALLDATACOPY)
+            .unwrap();
+        assert_eq!(steps[idx].gas_cost, GasCost::FASTEST.as_u64());
+        assert_eq!(steps[idx + 1].execution_state, ExecutionState::STOP);
+        assert_eq!(steps[idx + 1].memory_size, 0);
+        assert!(block
+            .rws
+            .0
+            .get(&RwTableTag::Memory)
+            .map_or(true, |rws| rws.is_empty()));
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
 }
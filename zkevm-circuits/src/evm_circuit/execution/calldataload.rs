@@ -12,15 +12,16 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            memory_gadget::BufferReaderGadget,
-            CachedRegion, Cell, MemoryAddress, RandomLinearCombination,
+            math_gadget::IsZeroGadget,
+            memory_gadget::{address_high, address_low, BufferReaderGadget},
+            select, CachedRegion, Cell, RandomLinearCombination, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
 
-use super::ExecutionGadget;
+use super::{ExecutionGadget, GadgetAssignResultExt};
 
 // The offset in the RW indices that mark the start of memory lookups.
 const OFFSET_RW_MEMORY_INDICES: usize = 4usize;
@@ -33,7 +34,10 @@ pub(crate) struct CallDataLoadGadget<F> {
     /// an internal call.
     src_id: Cell<F>,
     /// The bytes offset in calldata, from which we load a 32-bytes word.
-    offset: MemoryAddress<F>,
+    offset: Word<F>,
+    /// Whether the offset fits into `N_BYTES_MEMORY_ADDRESS` bytes, a larger
+    /// offset is entirely past the end of call data.
+    offset_in_range: IsZeroGadget<F>,
     /// The size of the call's data (tx input for a root call or calldata length
     /// of an internal call).
     call_data_length: Cell<F>,
@@ -54,17 +58,24 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let offset = cb.query_rlc();
+        let offset = cb.query_word();
 
         // Pop the offset value from stack.
         cb.stack_pop(offset.expr());
+        let offset_in_range = IsZeroGadget::construct(cb, address_high::expr(&offset));
 
         // Add a lookup constrain for TxId in the RW table.
         let src_id = cb.query_cell();
         let call_data_length = cb.query_cell();
         let call_data_offset = cb.query_cell();
 
-        let src_addr = offset.expr() + call_data_offset.expr();
+        // An offset out of range is clamped to the call data length, so that
+        // all the bytes read are 0.
+        let src_addr = select::expr(
+            offset_in_range.expr(),
+            address_low::expr(&offset),
+            call_data_length.expr(),
+        ) + call_data_offset.expr();
         let src_addr_end = call_data_length.expr() + call_data_offset.expr();
 
         cb.condition(cb.curr.state.is_root.expr(), |cb| {
@@ -159,6 +170,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
         Self {
             same_context,
             offset,
+            offset_in_range,
             src_id,
             call_data_length,
             call_data_offset,
@@ -175,22 +187,20 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        self.same_context.assign_exec_step(region, offset, step)?;
+        self.same_context
+            .assign_exec_step(region, offset, step)
+            .with_gadget(Self::NAME, offset)?;
 
         // set the value for bytes offset in calldata. This is where we start
         // reading bytes from.
-        let data_offset = block.rws[step.rw_indices[0]].stack_value();
-
-        // assign the calldata start and end cells.
-        self.offset.assign(
-            region,
-            offset,
-            Some(
-                data_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
-                    .try_into()
-                    .unwrap(),
-            ),
-        )?;
+        let data_offset = block.rws[step.rw_indices[0]].stack_value().to_le_bytes();
+        self.offset
+            .assign(region, offset, Some(data_offset))
+            .with_gadget(Self::NAME, offset)?;
+        let data_offset_high = address_high::value::<F>(data_offset);
+        self.offset_in_range
+            .assign(region, offset, data_offset_high)
+            .with_gadget(Self::NAME, offset)?;
 
         // assign to the buffer reader gadget.
         let (calldata_length, calldata_offset, src_id) = if call.is_root {
@@ -202,39 +212,49 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
                 call.caller_id as u64,
             )
         };
-        self.src_id.assign(region, offset, Some(F::from(src_id)))?;
+        self.src_id
+            .assign(region, offset, Some(F::from(src_id)))
+            .with_gadget(Self::NAME, offset)?;
         self.call_data_length
-            .assign(region, offset, Some(F::from(calldata_length)))?;
+            .assign(region, offset, Some(F::from(calldata_length)))
+            .with_gadget(Self::NAME, offset)?;
         self.call_data_offset
-            .assign(region, offset, Some(F::from(calldata_offset)))?;
+            .assign(region, offset, Some(F::from(calldata_offset)))
+            .with_gadget(Self::NAME, offset)?;
 
         let mut calldata_bytes = vec![0u8; N_BYTES_WORD];
-        let (src_addr, src_addr_end) = (
-            data_offset.as_usize() + calldata_offset as usize,
-            calldata_length as usize + calldata_offset as usize,
-        );
+        let src_addr_end = calldata_length as usize + calldata_offset as usize;
+        // an offset out of range is clamped to the end of call data.
+        let src_addr = if data_offset_high == F::zero() {
+            address_low::value(data_offset) as usize + calldata_offset as usize
+        } else {
+            src_addr_end
+        };
 
         for (i, byte) in calldata_bytes.iter_mut().enumerate() {
+            // bytes past the end of call data are 0, which are neither looked
+            // up from tx call data nor memory.
+            if src_addr + i >= src_addr_end {
+                break;
+            }
             if call.is_root {
                 // fetch from tx call data
-                if src_addr + i < tx.call_data_length {
-                    *byte = tx.call_data[src_addr + i];
-                }
+                *byte = tx.call_data[src_addr + i];
             } else {
                 // fetch from memory
-                if src_addr + i < call.call_data_length as usize {
-                    *byte = block.rws[step.rw_indices[OFFSET_RW_MEMORY_INDICES + i]].memory_value();
-                }
+                *byte = block.rws[step.rw_indices[OFFSET_RW_MEMORY_INDICES + i]].memory_value();
             }
         }
-        self.buffer_reader.assign(
-            region,
-            offset,
-            src_addr as u64,
-            src_addr_end as u64,
-            &calldata_bytes,
-            &[true; N_BYTES_WORD],
-        )?;
+        self.buffer_reader
+            .assign(
+                region,
+                offset,
+                src_addr as u64,
+                src_addr_end as u64,
+                &calldata_bytes,
+                &[true; N_BYTES_WORD],
+            )
+            .with_gadget(Self::NAME, offset)?;
 
         Ok(())
     }
@@ -242,33 +262,50 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use eth_types::{bytecode, ToWord, Word};
-    use mock::TestContext;
-
-    use crate::{evm_circuit::test::rand_bytes, test_util::run_test_circuits};
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            test::{assert_gate_failure, rand_bytes, run_test_circuit_incomplete_fixed_table},
+            witness::Rw,
+        },
+        test_util::{build_witness_block, run_test_circuits},
+    };
 
-    fn test_root_ok(offset: usize) {
+    fn test_root_ok(call_data_length: usize, offset: impl Into<Word>) {
+        let offset: Word = offset.into();
         let bytecode = bytecode! {
-            PUSH32(Word::from(offset))
+            PUSH32(offset)
             CALLDATALOAD
             STOP
         };
 
-        assert_eq!(
-            run_test_circuits(
-                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
-                None
-            ),
-            Ok(())
-        );
+        let call_data = rand_bytes(call_data_length);
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(call_data.into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
     }
 
-    fn test_internal_ok(call_data_length: usize, call_data_offset: usize, offset: usize) {
+    fn test_internal_ok(call_data_length: usize, call_data_offset: usize, offset: impl Into<Word>) {
+        let offset: Word = offset.into();
         let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
 
         // code B gets called by code A, so the call is an internal call.
         let code_b = bytecode! {
-            PUSH32(Word::from(offset))
+            PUSH32(offset)
             CALLDATALOAD
             STOP
         };
@@ -310,11 +347,100 @@ mod test {
         assert_eq!(run_test_circuits(ctx, None), Ok(()));
     }
 
+    fn test_nested_internal_ok(call_data_length: usize, call_data_offset: usize, offset: usize) {
+        let (addr_a, addr_b, addr_c) = (
+            mock::MOCK_ACCOUNTS[0],
+            mock::MOCK_ACCOUNTS[1],
+            mock::MOCK_ACCOUNTS[2],
+        );
+
+        // code C gets called by code B, which is an internal call itself, so
+        // the call data is read from B's memory.
+        let code_c = bytecode! {
+            PUSH32(Word::from(offset))
+            CALLDATALOAD
+            STOP
+        };
+
+        let pushdata = rand_bytes(32);
+        let code_b = bytecode! {
+            // populate memory in B's context.
+            PUSH32(Word::from_big_endian(&pushdata))
+            PUSH1(0x00) // offset
+            MSTORE
+            // call addr_c
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(call_data_length) // argsLength
+            PUSH1(call_data_offset) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_c.to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            STOP
+        };
+
+        let code_a = bytecode! {
+            // call addr_b
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(0x2_0000) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_c).code(code_c);
+                accs[1].address(addr_b).code(code_b);
+                accs[2].address(addr_a).code(code_a);
+                accs[3]
+                    .address(mock::MOCK_ACCOUNTS[3])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[2].address).from(accs[3].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        assert_eq!(run_test_circuits(ctx, None), Ok(()));
+    }
+
     #[test]
     fn calldataload_gadget_root() {
-        test_root_ok(0x00);
-        test_root_ok(0x08);
-        test_root_ok(0x10);
+        test_root_ok(0x00, 0x00);
+        test_root_ok(0x40, 0x00);
+        test_root_ok(0x40, 0x08);
+        test_root_ok(0x40, 0x30);
+    }
+
+    #[test]
+    fn calldataload_gadget_root_offset_past_call_data() {
+        test_root_ok(0x20, 0x20);
+        test_root_ok(0x20, 0x40);
+    }
+
+    #[test]
+    fn calldataload_gadget_root_fully_in_bounds() {
+        // The word ends exactly at the end of call data
+        test_root_ok(0x40, 0x20);
+        test_root_ok(0x20, 0x00);
+        // The last byte of the word is past the end of call data
+        test_root_ok(0x40, 0x21);
+        test_root_ok(0x20, 0x01);
+    }
+
+    #[test]
+    fn calldataload_gadget_internal_fully_in_bounds() {
+        test_internal_ok(0x40, 0x00, 0x20);
+        test_internal_ok(0x40, 0x00, 0x21);
     }
 
     #[test]
@@ -323,4 +449,149 @@ mod test {
         test_internal_ok(0x20, 0x10, 0x10);
         test_internal_ok(0x40, 0x20, 0x08);
     }
+
+    #[test]
+    fn calldataload_gadget_nested_internal() {
+        test_nested_internal_ok(0x20, 0x00, 0x00);
+        test_nested_internal_ok(0x20, 0x10, 0x08);
+    }
+
+    #[test]
+    fn calldataload_gadget_internal_offset_past_call_data() {
+        test_internal_ok(0x10, 0x00, 0x10);
+        test_internal_ok(0x10, 0x08, 0x40);
+    }
+
+    #[test]
+    fn calldataload_gadget_offset_overflow() {
+        let offset = Word::one() << 200;
+        test_root_ok(0x40, offset);
+        test_internal_ok(0x20, 0x10, offset);
+    }
+
+    #[test]
+    fn calldataload_execution_trace() {
+        let bytecode = bytecode! {
+            PUSH1(0x00)
+            CALLDATALOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(rand_bytes(32).into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let block = build_witness_block(block);
+
+        let trace = block.execution_trace();
+        assert_eq!(
+            trace
+                .iter()
+                .map(|&(tx_id, pc, execution_state, opcode, _)| (
+                    tx_id,
+                    pc,
+                    execution_state,
+                    opcode
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 0, ExecutionState::BeginTx, None),
+                (1, 0, ExecutionState::PUSH, Some(OpcodeId::PUSH1)),
+                (
+                    1,
+                    2,
+                    ExecutionState::CALLDATALOAD,
+                    Some(OpcodeId::CALLDATALOAD)
+                ),
+                (1, 3, ExecutionState::STOP, Some(OpcodeId::STOP)),
+                (1, 0, ExecutionState::EndTx, None),
+                (1, 0, ExecutionState::EndBlock, None),
+            ]
+        );
+        // PUSH1 and CALLDATALOAD cost 3 gas each
+        assert_eq!(trace[1].4 - trace[2].4, 3);
+        assert_eq!(trace[2].4 - trace[3].4, 3);
+    }
+
+    #[test]
+    fn calldataload_gadget_wrong_gas_left() {
+        let bytecode = bytecode! {
+            PUSH1(0x00)
+            CALLDATALOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(rand_bytes(32).into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let mut block = build_witness_block(block);
+
+        // CALLDATALOAD costs 3 gas, so charging one more breaks its gas_left
+        // transition.
+        let steps = &mut block.txs[0].steps;
+        let idx = steps
+            .iter()
+            .position(|step| step.execution_state == ExecutionState::CALLDATALOAD)
+            .unwrap();
+        steps[idx + 1].gas_left -= 1;
+
+        assert_gate_failure(
+            run_test_circuit_incomplete_fixed_table(block),
+            "CALLDATALOAD",
+        );
+    }
+
+    #[test]
+    fn calldataload_gadget_pushed_word_mismatch() {
+        let bytecode = bytecode! {
+            PUSH1(0x00)
+            CALLDATALOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(rand_bytes(32).into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let mut block = build_witness_block(block);
+
+        // The word pushed differs in a byte from the call data read
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::CALLDATALOAD)
+            .unwrap();
+        let (tag, idx) = *step.rw_indices.last().unwrap();
+        match &mut block.rws.0.get_mut(&tag).unwrap()[idx] {
+            Rw::Stack { value, .. } => *value = *value ^ Word::one(),
+            rw => panic!("unexpected rw {:?}", rw),
+        }
+
+        assert!(run_test_circuit_incomplete_fixed_table(block).is_err());
+    }
 }
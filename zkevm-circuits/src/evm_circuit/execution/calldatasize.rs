@@ -90,9 +90,13 @@ impl<F: Field> ExecutionGadget<F> for CallDataSizeGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::{
-        test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
-        witness::block_convert,
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
+            witness::block_convert,
+        },
+        test_util::build_witness_block,
     };
     use eth_types::{address, bytecode, Word};
     use itertools::Itertools;
@@ -185,4 +189,64 @@ mod test {
             test_ok(call_data_size, is_root);
         }
     }
+
+    #[test]
+    fn calldatasize_gadget_internal_differs_from_tx() {
+        // The tx has call data of its own, but the internal call gets only the
+        // args passed by its caller
+        let (tx_call_data_size, call_data_size) = (7, 0x20);
+        let block = build_witness_block(
+            TestContext::<3, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(address!("0x0000000000000000000000000000000000000000"))
+                        .balance(Word::from(1u64 << 30));
+                    accs[1]
+                        .address(address!("0x0000000000000000000000000000000000000010"))
+                        .balance(Word::from(1u64 << 20))
+                        .code(bytecode! {
+                            PUSH1(0) // retLength
+                            PUSH1(0) // retOffset
+                            PUSH1(call_data_size) // argsLength
+                            PUSH1(0) // argsOffset
+                            PUSH1(0) // value
+                            PUSH1(0x20) // addr
+                            GAS
+                            CALL
+                            STOP
+                        });
+                    accs[2]
+                        .address(address!("0x0000000000000000000000000000000000000020"))
+                        .balance(Word::from(1u64 << 20))
+                        .code(bytecode! {
+                            CALLDATASIZE
+                            STOP
+                        });
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .to(accs[1].address)
+                        .input(rand_bytes(tx_call_data_size).into())
+                        .gas(Word::from(40000));
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap(),
+        );
+
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::CALLDATASIZE)
+            .unwrap();
+        assert!(!block.txs[0].calls[step.call_index].is_root);
+        assert_eq!(
+            block.rws[step.rw_indices[1]].stack_value(),
+            Word::from(call_data_size)
+        );
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
 }
@@ -235,4 +235,12 @@ mod tests {
         test_ok(0x20, 0x30, 0x30);
         test_ok(0x10, 0x20, 0x42);
     }
+
+    #[test]
+    fn codecopy_gadget_past_code_end() {
+        // The code of `test_ok` is 3 PUSH32, CODECOPY and STOP
+        let code_size = 3 * 33 + 2;
+        // The last 6 bytes of code followed by 10 bytes of padding
+        test_ok(0x00, code_size - 6, 16);
+    }
 }
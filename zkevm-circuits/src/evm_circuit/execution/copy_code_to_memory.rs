@@ -74,6 +74,13 @@ impl<F: Field> ExecutionGadget<F> for CopyCodeToMemoryGadget<F> {
                     buffer_reader.byte(idx),
                 );
             });
+            // The bytes beyond the end of the code are padded with 0.
+            cb.condition(
+                buffer_reader.has_data(idx) - buffer_reader.read_flag(idx),
+                |cb| {
+                    cb.require_zero("byte beyond code length is 0", buffer_reader.byte(idx));
+                },
+            );
             // Lookup the RW table for a memory write operation at the appropriate
             // destination memory address.
             cb.condition(buffer_reader.has_data(idx), |cb| {
@@ -370,7 +377,7 @@ pub(crate) mod test {
         }
     }
 
-    fn test_ok(src_addr: u64, dst_addr: u64, length: usize) {
+    fn copy_code_block(src_addr: u64, dst_addr: u64, length: usize) -> Block<Fr> {
         let randomness = Fr::rand();
         let call_id = 1;
         let mut rws = RwMap::default();
@@ -421,7 +428,7 @@ pub(crate) mod test {
             ..Default::default()
         });
 
-        let block = Block {
+        Block {
             randomness,
             txs: vec![Transaction {
                 id: 1,
@@ -438,8 +445,14 @@ pub(crate) mod test {
             rws,
             bytecodes: vec![dummy_code, code],
             ..Default::default()
-        };
-        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+        }
+    }
+
+    fn test_ok(src_addr: u64, dst_addr: u64, length: usize) {
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(copy_code_block(src_addr, dst_addr, length)),
+            Ok(())
+        );
     }
 
     #[test]
@@ -470,4 +483,23 @@ pub(crate) mod test {
             200,  // length
         );
     }
+
+    #[test]
+    fn copy_code_to_memory_past_code_end() {
+        // The last 10 bytes of the 170 bytes long bytecode, followed by 10 bytes
+        // of padding.
+        test_ok(
+            160,  // src_addr
+            0x20, // dst_addr
+            20,   // length
+        );
+
+        // The padding written to memory must be 0
+        let mut block = copy_code_block(160, 0x20, 20);
+        match block.rws.0.get_mut(&RwTableTag::Memory).unwrap().last_mut() {
+            Some(Rw::Memory { byte, .. }) => *byte = 0xff,
+            _ => unreachable!(),
+        }
+        assert!(run_test_circuit_incomplete_fixed_table(block).is_err());
+    }
 }
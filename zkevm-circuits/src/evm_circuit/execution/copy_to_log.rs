@@ -3,7 +3,7 @@ use crate::{
         execution::ExecutionGadget,
         param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
-        table::TxLogFieldTag,
+        table::{RwTableTag, TxLogFieldTag},
         util::{
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
             math_gadget::ComparisonGadget,
@@ -70,6 +70,7 @@ impl<F: Field> ExecutionGadget<F> for CopyToLogGadget<F> {
             cb.condition(buffer_reader.has_data(i) * is_persistent.expr(), |cb| {
                 cb.tx_log_lookup(
                     tx_id.expr(),
+                    cb.curr.state.log_id.expr(),
                     TxLogFieldTag::Data,
                     data_start_index.expr() + i.expr(),
                     buffer_reader.byte(i),
@@ -184,7 +185,10 @@ impl<F: Field> ExecutionGadget<F> for CopyToLogGadget<F> {
             .assign(region, offset, Some(F::from(data_start_index as u64)))?;
         // Retrieve the bytes and selectors
 
-        let mut rw_idx = 0;
+        let mut memory_indices = step
+            .rw_indices
+            .iter()
+            .filter(|(tag, _)| *tag == RwTableTag::Memory);
         let mut bytes = vec![0u8; MAX_COPY_BYTES];
         let mut selectors = vec![false; MAX_COPY_BYTES];
 
@@ -192,9 +196,7 @@ impl<F: Field> ExecutionGadget<F> for CopyToLogGadget<F> {
             let src_addr = aux.src_addr() as usize + idx;
             selectors[idx] = true;
             bytes[idx] = if selectors[idx] && src_addr < aux.src_addr_end() as usize {
-                let indice = step.rw_indices[rw_idx];
-                rw_idx += 1;
-                block.rws[indice].memory_value()
+                block.rws[*memory_indices.next().unwrap()].memory_value()
             } else {
                 0
             };
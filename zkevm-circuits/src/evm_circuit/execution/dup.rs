@@ -34,6 +34,9 @@ impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
         // The offset starts at 0 for DUP1
         let dup_offset = opcode.expr() - OpcodeId::DUP1.expr();
 
+        // DUPx needs at least x items on the stack
+        cb.require_stack_height(dup_offset.clone() + 1.expr());
+
         // Peek the value at `dup_offset` and push the value on the stack
         cb.stack_lookup(false.expr(), dup_offset, value.expr());
         cb.stack_push(value.expr());
@@ -82,9 +85,18 @@ impl<F: Field> ExecutionGadget<F> for DupGadget<F> {
 #[cfg(test)]
 
 mod test {
-    use crate::{evm_circuit::test::rand_word, test_util::run_test_circuits};
+    use crate::{
+        evm_circuit::{
+            param::STACK_CAPACITY,
+            step::ExecutionState,
+            test::{rand_word, run_test_circuit_incomplete_fixed_table},
+            witness::{single_step_block, Block, Rw},
+        },
+        test_util::run_test_circuits,
+    };
     use eth_types::evm_types::OpcodeId;
     use eth_types::{bytecode, Word};
+    use halo2_proofs::pairing::bn256::Fr;
     use mock::TestContext;
 
     fn test_ok(opcode: OpcodeId, value: Word) {
@@ -109,6 +121,54 @@ mod test {
         );
     }
 
+    /// A block of a DUPx step with only `height` items on the stack
+    fn dup_block(opcode: OpcodeId, height: usize) -> Block<Fr> {
+        let n = (opcode.as_u8() - OpcodeId::DUP1.as_u8() + 1) as usize;
+        let stack_pointer = STACK_CAPACITY - height;
+        let value = Word::from(0xcafe);
+        let mut block = single_step_block(
+            ExecutionState::DUP,
+            opcode,
+            vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: stack_pointer + n - 1,
+                    value,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: stack_pointer - 1,
+                    value,
+                },
+            ],
+        );
+        // The stack pointer inferred from the peeked item is off for x > 1
+        block.txs[0].steps[0].stack_pointer = stack_pointer;
+        block
+    }
+
+    #[test]
+    fn dup_gadget_stack_height() {
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(dup_block(OpcodeId::DUP3, 3)),
+            Ok(())
+        );
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(dup_block(OpcodeId::DUP5, 5)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn dup_gadget_stack_underflow() {
+        // DUP5 peeks past the bottom of a stack of 3 items
+        assert!(run_test_circuit_incomplete_fixed_table(dup_block(OpcodeId::DUP5, 3)).is_err());
+    }
+
     #[test]
     fn dup_gadget_simple() {
         test_ok(OpcodeId::DUP1, Word::max_value());
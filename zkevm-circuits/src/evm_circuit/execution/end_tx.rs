@@ -156,7 +156,8 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         );
 
         cb.condition(
-            cb.next.execution_state_selector([ExecutionState::EndBlock]),
+            cb.next
+                .execution_state_selector([ExecutionState::EndBlock, ExecutionState::Padding]),
             |cb| {
                 cb.require_step_state_transition(StepStateTransition {
                     rw_counter: Delta(9.expr() - is_first_tx.expr()),
@@ -193,6 +194,11 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
+        // The refund cap is a constant of the circuit, which all the forks share
+        assert_eq!(
+            block.fork.max_refund_quotient_of_gas_used(),
+            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64
+        );
         let gas_used = tx.gas - step.gas_left;
         let (refund, _) = block.rws[step.rw_indices[2]].tx_refund_value_pair();
         let [(caller_balance, caller_balance_prev), (coinbase_balance, coinbase_balance_prev)] =
@@ -279,15 +285,36 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::{
-        test::run_test_circuit_incomplete_fixed_table, witness::block_convert,
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::RwTableTag,
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{block_convert, Block, GasUsedMismatch, Rw, TxRefundMismatch},
+        },
+        test_util::build_witness_block,
+    };
+    use eth_types::{
+        self, bytecode,
+        evm_types::{GasCost, HardFork},
+        geth_types::GethData,
+        Word,
+    };
+    use halo2_proofs::pairing::bn256::Fr;
+    use mock::{
+        eth,
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext, MOCK_ACCOUNTS,
     };
-    use eth_types::{self, bytecode, geth_types::GethData};
-    use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
     fn test_ok(block: GethData) {
+        test_ok_with_fork(block, HardFork::default())
+    }
+
+    fn test_ok_with_fork(block: GethData, fork: HardFork) {
         let block_data = bus_mapping::mock::BlockData::new_from_geth_data(block);
         let mut builder = block_data.new_circuit_input_builder();
+        builder.block.fork = fork;
         builder
             .handle_block(&block_data.eth_block, &block_data.geth_traces)
             .unwrap();
@@ -298,20 +325,6 @@ mod test {
 
     #[test]
     fn end_tx_gadget_simple() {
-        // TODO: Enable this with respective code when SSTORE is implemented.
-        // Tx with non-capped refund
-        // test_ok(vec![mock_tx(
-        //     address!("0x00000000000000000000000000000000000000fe"),
-        //     Some(27000),
-        //     None,
-        // )]);
-        // Tx with capped refund
-        // test_ok(vec![mock_tx(
-        //     address!("0x00000000000000000000000000000000000000fe"),
-        //     Some(65000),
-        //     None,
-        // )]);
-
         // Multiple txs
         test_ok(
             // Get the execution steps from the external tracer
@@ -338,4 +351,125 @@ mod test {
             .into(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_after_merge() {
+        for fork in [HardFork::Merge, HardFork::Shanghai] {
+            test_ok_with_fork(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+                    .unwrap()
+                    .into(),
+                fork,
+            );
+        }
+    }
+
+    #[test]
+    fn end_tx_gadget_sstore_refunds() {
+        // Two SSTOREs clearing a slot, each refunding SSTORE_CLEARS_SCHEDULE
+        let code = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            PUSH1(0x01)
+            SSTORE
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code)
+                    .storage(
+                        vec![(Word::zero(), Word::one()), (Word::one(), Word::one())].into_iter(),
+                    );
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            tx_from_1_to_0,
+            |block, _txs| block,
+        )
+        .unwrap()
+        .into();
+        // Geth reports the gas used by the tx net of its capped refund
+        block.eth_block.gas_used = block.geth_traces[0].gas.0.into();
+        let block = build_witness_block(block);
+
+        let refunds = block.txs[0]
+            .steps
+            .iter()
+            .filter(|step| {
+                matches!(
+                    step.execution_state,
+                    ExecutionState::SSTORE | ExecutionState::EndTx
+                )
+            })
+            .flat_map(|step| step.rw_indices.iter())
+            .filter(|(tag, _)| *tag == RwTableTag::TxRefund)
+            .map(|idx| block.rws[*idx].tx_refund_value_pair())
+            .collect::<Vec<_>>();
+        let refund = GasCost::SSTORE_CLEARS_SCHEDULE.as_u64();
+        assert_eq!(
+            refunds,
+            vec![(refund, 0), (2 * refund, refund), (2 * refund, 2 * refund)]
+        );
+        assert_eq!(block.check_tx_refunds(), Ok(()));
+        assert_eq!(block.check_gas_used(), Ok(()));
+
+        // A refund read by EndTx which the SSTOREs don't add up to
+        let mut tampered = block.clone();
+        let end_tx = tampered.txs[0].steps.iter().rev().nth(1).unwrap();
+        assert_eq!(end_tx.execution_state, ExecutionState::EndTx);
+        let (_, idx) = end_tx.rw_indices[2];
+        match &mut tampered.rws.0.get_mut(&RwTableTag::TxRefund).unwrap()[idx] {
+            Rw::TxRefund { value, .. } => *value += 1,
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            tampered.check_tx_refunds(),
+            Err(TxRefundMismatch {
+                tx_id: 1,
+                refund: 2 * refund + 1,
+                refund_deltas: 2 * refund as i64,
+            })
+        );
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    /// Two transfers of 21000 gas each to an account whose code is `STOP`,
+    /// with `gas_used` reported by the block header.
+    fn two_txs_block(gas_used: u64) -> Block<Fr> {
+        let block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .value(eth(1));
+                txs[1]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .value(eth(1));
+            },
+            |block, _tx| block.gas_used(Word::from(gas_used)),
+        )
+        .unwrap()
+        .into();
+        build_witness_block(block)
+    }
+
+    #[test]
+    fn block_gas_used_is_sum_of_txs_gas_used() {
+        assert_eq!(two_txs_block(2 * 21000).check_gas_used(), Ok(()));
+        assert_eq!(
+            two_txs_block(2 * 21000 + 1).check_gas_used(),
+            Err(GasUsedMismatch {
+                block_gas_used: 2 * 21000 + 1,
+                txs_gas_used: 2 * 21000,
+            })
+        );
+    }
 }
@@ -0,0 +1,244 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::MAX_CALL_DEPTH,
+        step::ExecutionState,
+        util::{
+            common_gadget::CallPrecheckFailureGadget, constraint_builder::ConstraintBuilder,
+            CachedRegion,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+/// Gadget for CALL which fails the precheck because the call depth would
+/// exceed [`MAX_CALL_DEPTH`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorDepthGadget<F> {
+    call: CallPrecheckFailureGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorDepthGadget<F> {
+    const NAME: &'static str = "ErrorDepth";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorDepth;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let call = CallPrecheckFailureGadget::construct(cb);
+
+        // Verify the call would be made at depth 1025
+        cb.require_equal(
+            "depth == MAX_CALL_DEPTH + 1",
+            call.depth().expr(),
+            (MAX_CALL_DEPTH + 1).expr(),
+        );
+
+        Self { call }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.call
+            .assign_exec_step(region, offset, block, call, step, 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            param::MAX_CALL_DEPTH,
+            step::ExecutionState,
+            table::{AccountFieldTag, CallContextFieldTag},
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{single_step_block, Block, Rw},
+        },
+        test_util::build_witness_block,
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, Address, ToWord, Word};
+    use halo2_proofs::{dev::VerifyFailure, pairing::bn256::Fr};
+    use mock::TestContext;
+
+    /// A block with a CALL without value to an empty account made by a caller
+    /// at `depth`, which fails as ErrorDepth.
+    fn error_depth_block(depth: u64) -> Block<Fr> {
+        let callee_address = Address::repeat_byte(0xff);
+        let call_context = |rw_counter, is_write, field_tag, value: Word| Rw::CallContext {
+            rw_counter,
+            is_write,
+            call_id: 1,
+            field_tag,
+            value,
+        };
+        let stack_pop = |rw_counter, stack_pointer, value| Rw::Stack {
+            rw_counter,
+            is_write: false,
+            call_id: 1,
+            stack_pointer,
+            value,
+        };
+        let account_read = |rw_counter, field_tag| Rw::Account {
+            rw_counter,
+            is_write: false,
+            account_address: callee_address,
+            field_tag,
+            value: Word::zero(),
+            value_prev: Word::zero(),
+        };
+        let mut block = single_step_block(
+            ExecutionState::ErrorDepth,
+            OpcodeId::CALL,
+            vec![
+                call_context(1, false, CallContextFieldTag::TxId, Word::one()),
+                call_context(
+                    2,
+                    false,
+                    CallContextFieldTag::RwCounterEndOfReversion,
+                    Word::zero(),
+                ),
+                call_context(3, false, CallContextFieldTag::IsPersistent, Word::one()),
+                call_context(4, false, CallContextFieldTag::IsStatic, Word::zero()),
+                call_context(5, false, CallContextFieldTag::Depth, Word::from(depth)),
+                stack_pop(6, 1017, Word::from(10000)), // gas
+                stack_pop(7, 1018, callee_address.to_word()),
+                stack_pop(8, 1019, Word::zero()),  // value
+                stack_pop(9, 1020, Word::zero()),  // cd_offset
+                stack_pop(10, 1021, Word::zero()), // cd_length
+                stack_pop(11, 1022, Word::zero()), // rd_offset
+                stack_pop(12, 1023, Word::zero()), // rd_length
+                Rw::Stack {
+                    rw_counter: 13,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: Word::zero(),
+                },
+                Rw::TxAccessListAccount {
+                    rw_counter: 14,
+                    is_write: true,
+                    tx_id: 1,
+                    account_address: callee_address,
+                    is_warm: true,
+                    is_warm_prev: true,
+                },
+                account_read(15, AccountFieldTag::Nonce),
+                account_read(16, AccountFieldTag::Balance),
+                account_read(17, AccountFieldTag::CodeHash),
+                call_context(18, true, CallContextFieldTag::LastCalleeId, Word::zero()),
+                call_context(
+                    19,
+                    true,
+                    CallContextFieldTag::LastCalleeReturnDataOffset,
+                    Word::zero(),
+                ),
+                call_context(
+                    20,
+                    true,
+                    CallContextFieldTag::LastCalleeReturnDataLength,
+                    Word::zero(),
+                ),
+            ],
+        );
+
+        block.txs[0].calls[0].is_persistent = true;
+        block.txs[0].steps[1].reversible_write_counter = 1;
+        block
+    }
+
+    #[test]
+    fn error_depth_gadget_at_max_depth() {
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(error_depth_block(MAX_CALL_DEPTH + 1)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn error_depth_gadget_below_max_depth() {
+        let errors =
+            run_test_circuit_incomplete_fixed_table(error_depth_block(MAX_CALL_DEPTH)).unwrap_err();
+        assert!(
+            errors.iter().any(|error| match error {
+                VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+                    format!("{}", constraint).contains("depth == MAX_CALL_DEPTH + 1")
+                }
+                _ => false,
+            }),
+            "{:#?}",
+            errors
+        );
+    }
+
+    // Proving the 1025 nested calls is too heavy for CI, the depth gate is
+    // covered by the single step tests above.
+    #[test]
+    #[ignore]
+    fn call_exceeding_max_depth() {
+        // Recursive CALL to itself until the call depth is exhausted
+        let code = bytecode! {
+            PUSH1(0) // rd_length
+            PUSH1(0) // rd_offset
+            PUSH1(0) // cd_length
+            PUSH1(0) // cd_offset
+            PUSH1(0x42) // value
+            PUSH32(Address::repeat_byte(0xfe).to_word())
+            PUSH32(0x8_0000_0000_0000_u64) // gas
+            CALL
+            PUSH2(0xab)
+            STOP
+        };
+
+        let block = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(Address::repeat_byte(0xfe))
+                    .code(code)
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(10u64.pow(15)));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let block = build_witness_block(block);
+
+        // Only the CALL made at depth 1025 fails, and the caller keeps running
+        // without being reverted.
+        let tx = &block.txs[0];
+        let calls = tx
+            .steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| step.opcode == Some(OpcodeId::CALL))
+            .collect::<Vec<_>>();
+        assert_eq!(calls.len(), 1025);
+        let (idx, failed_call) = calls.last().unwrap();
+        assert_eq!(failed_call.execution_state, ExecutionState::ErrorDepth);
+        assert!(calls[..1024]
+            .iter()
+            .all(|(_, step)| step.execution_state == ExecutionState::CALL));
+        assert_eq!(tx.steps[idx + 1].execution_state, ExecutionState::PUSH);
+        assert_eq!(tx.steps[idx + 1].call_index, failed_call.call_index);
+        assert!(tx.calls[failed_call.call_index].is_persistent);
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::MAX_CALL_DEPTH,
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::CallPrecheckFailureGadget, constraint_builder::ConstraintBuilder,
+            math_gadget::LtWordGadget, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for CALL which fails the precheck because the caller doesn't have
+/// enough balance to transfer `value` to the callee.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInsufficientBalanceGadget<F> {
+    current_address: Cell<F>,
+    caller_balance: Word<F>,
+    call: CallPrecheckFailureGadget<F>,
+    is_insufficient_balance: LtWordGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInsufficientBalanceGadget<F> {
+    const NAME: &'static str = "ErrorInsufficientBalance";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInsufficientBalance;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        // Read caller's balance
+        let current_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        let caller_balance = cb.query_word();
+        cb.account_read(
+            current_address.expr(),
+            AccountFieldTag::Balance,
+            caller_balance.expr(),
+        );
+
+        let call = CallPrecheckFailureGadget::construct(cb);
+
+        // Call depth is checked before balance, so it must have been passed
+        cb.range_lookup(call.depth().expr() - 1.expr(), MAX_CALL_DEPTH);
+
+        // Verify caller's balance is less than value
+        let is_insufficient_balance = LtWordGadget::construct(cb, &caller_balance, call.value());
+        cb.require_equal(
+            "caller_balance < value",
+            is_insufficient_balance.expr(),
+            1.expr(),
+        );
+
+        Self {
+            current_address,
+            caller_balance,
+            call,
+            is_insufficient_balance,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let current_address = block.rws[step.rw_indices[0]].call_context_value();
+        let caller_balance = block.rws[step.rw_indices[1]].account_value_pair().0;
+        let value = block.rws[step.rw_indices[9]].stack_value();
+
+        self.current_address
+            .assign(region, offset, current_address.to_scalar())?;
+        self.caller_balance
+            .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
+        self.call
+            .assign_exec_step(region, offset, block, call, step, 2)?;
+        self.is_insufficient_balance
+            .assign(region, offset, caller_balance, value)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::test::run_test_circuit_incomplete_fixed_table, test_util::build_witness_block,
+    };
+    use eth_types::{address, bytecode, Address, ToWord, Word};
+    use mock::TestContext;
+
+    fn test_ok(caller_balance: Word, value: Word) {
+        let caller_code = bytecode! {
+            PUSH1(0) // rd_length
+            PUSH1(0) // rd_offset
+            PUSH1(0) // cd_length
+            PUSH1(0) // cd_offset
+            PUSH32(value)
+            PUSH32(Address::repeat_byte(0xff).to_word())
+            PUSH2(10000) // gas
+            CALL
+            STOP
+        };
+
+        let block = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(Address::repeat_byte(0xfe))
+                    .code(caller_code)
+                    .balance(caller_balance);
+                accs[2].address(Address::repeat_byte(0xff));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let block = build_witness_block(block);
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn call_with_sufficient_balance() {
+        test_ok(Word::from(10).pow(18.into()), Word::from(10).pow(18.into()));
+    }
+
+    #[test]
+    fn call_with_insufficient_balance() {
+        test_ok(Word::zero(), Word::one());
+        test_ok(
+            Word::from(10).pow(18.into()),
+            Word::from(10).pow(18.into()) + 1,
+        );
+    }
+}
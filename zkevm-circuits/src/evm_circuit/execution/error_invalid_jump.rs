@@ -0,0 +1,260 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_PROGRAM_COUNTER,
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, CodeSource, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for JUMP and JUMPI whose destination is not a JUMPDEST in code,
+/// which is either out of the bytecode, not a JUMPDEST, or a JUMPDEST byte in
+/// the data of a PUSH.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidJumpGadget<F> {
+    is_jumpi: IsEqualGadget<F>,
+    destination: Word<F>,
+    condition: Cell<F>,
+    is_condition_zero: IsZeroGadget<F>,
+    code_length: Cell<F>,
+    is_destination_high_zero: IsZeroGadget<F>,
+    is_destination_lt_code_length: LtGadget<F, N_BYTES_PROGRAM_COUNTER>,
+    value: Cell<F>,
+    is_code: Cell<F>,
+    is_jumpdest: IsEqualGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidJumpGadget<F> {
+    const NAME: &'static str = "ErrorInvalidJump";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidJump;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_jumpi = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::JUMPI.expr());
+
+        // Pop the destination, and the condition for JUMPI
+        let destination = cb.query_word();
+        let condition = cb.query_cell();
+        cb.stack_pop(destination.expr());
+        cb.condition(is_jumpi.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 1.expr(), condition.expr());
+        });
+
+        // JUMPI only jumps when the condition is non-zero
+        let is_condition_zero = IsZeroGadget::construct(cb, condition.expr());
+        cb.condition(is_jumpi.expr(), |cb| {
+            cb.require_zero("JUMPI condition is non-zero", is_condition_zero.expr());
+        });
+
+        // Check if the destination is within the bytecode
+        let code_length = cb.bytecode_length(cb.curr.state.code_hash.expr());
+        let destination_low = from_bytes::expr(&destination.cells[..N_BYTES_PROGRAM_COUNTER]);
+        let is_destination_high_zero =
+            IsZeroGadget::construct(cb, sum::expr(&destination.cells[N_BYTES_PROGRAM_COUNTER..]));
+        let is_destination_lt_code_length =
+            LtGadget::construct(cb, destination_low.clone(), code_length.expr());
+        let is_within_range =
+            is_destination_high_zero.expr() * is_destination_lt_code_length.expr();
+
+        // Lookup the byte at destination when it's within the bytecode, which
+        // must not be a JUMPDEST in code
+        let value = cb.query_cell();
+        let is_code = cb.query_bool();
+        cb.condition(is_within_range.clone(), |cb| {
+            cb.bytecode_lookup(
+                cb.curr.state.code_hash.expr(),
+                destination_low,
+                is_code.expr(),
+                value.expr(),
+            );
+        });
+        let is_jumpdest = IsEqualGadget::construct(cb, value.expr(), OpcodeId::JUMPDEST.expr());
+        cb.require_zero(
+            "Destination is not a JUMPDEST in code",
+            is_within_range * is_code.expr() * is_jumpdest.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            is_jumpi,
+            destination,
+            condition,
+            is_condition_zero,
+            code_length,
+            is_destination_high_zero,
+            is_destination_lt_code_length,
+            value,
+            is_code,
+            is_jumpdest,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_jumpi = opcode == OpcodeId::JUMPI;
+        self.is_jumpi.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::JUMPI.as_u64()),
+        )?;
+
+        let destination = block.rws[step.rw_indices[0]].stack_value();
+        let condition = if is_jumpi {
+            Word::random_linear_combine(
+                block.rws[step.rw_indices[1]].stack_value().to_le_bytes(),
+                block.randomness,
+            )
+        } else {
+            F::zero()
+        };
+        self.destination
+            .assign(region, offset, Some(destination.to_le_bytes()))?;
+        self.condition.assign(region, offset, Some(condition))?;
+        self.is_condition_zero.assign(region, offset, condition)?;
+
+        let code = block
+            .bytecodes
+            .iter()
+            .find(|b| {
+                let CodeSource::Account(code_hash) = &call.code_source;
+                b.hash == *code_hash
+            })
+            .expect("could not find current environment's bytecode");
+        let code_length = code.bytes.len() as u64;
+        self.code_length
+            .assign(region, offset, Some(F::from(code_length)))?;
+
+        let destination_bytes = destination.to_le_bytes();
+        self.is_destination_high_zero.assign(
+            region,
+            offset,
+            sum::value(&destination_bytes[N_BYTES_PROGRAM_COUNTER..]),
+        )?;
+        let destination_low = from_bytes::value(&destination_bytes[..N_BYTES_PROGRAM_COUNTER]);
+        self.is_destination_lt_code_length.assign(
+            region,
+            offset,
+            destination_low,
+            F::from(code_length),
+        )?;
+
+        // The byte at destination and whether it's in code, which are both
+        // zero when the destination is out of the bytecode
+        let (value, is_code) = if destination < code_length.into() {
+            let destination = destination.as_usize();
+            (
+                code.bytes[destination],
+                is_code_at(&code.bytes, destination),
+            )
+        } else {
+            (0, false)
+        };
+        self.value
+            .assign(region, offset, Some(F::from(value as u64)))?;
+        self.is_code
+            .assign(region, offset, Some(F::from(is_code as u64)))?;
+        self.is_jumpdest.assign(
+            region,
+            offset,
+            F::from(value as u64),
+            F::from(OpcodeId::JUMPDEST.as_u64()),
+        )?;
+
+        self.common_error_gadget
+            .assign_exec_step(region, offset, call, step)
+    }
+}
+
+/// Returns whether the byte at `index` is an opcode rather than the data of a
+/// PUSH.
+fn is_code_at(bytes: &[u8], index: usize) -> bool {
+    let mut idx = 0;
+    while idx < index {
+        let byte = bytes[idx];
+        idx += 1;
+        if (OpcodeId::PUSH1.as_u8()..=OpcodeId::PUSH32.as_u8()).contains(&byte) {
+            idx += (byte - OpcodeId::PUSH1.as_u8() + 1) as usize;
+        }
+    }
+    idx == index
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Bytecode, Word};
+    use mock::TestContext;
+
+    fn test_ok(bytecode: Bytecode) {
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn invalid_jump_into_push_data() {
+        // The destination is the JUMPDEST byte pushed by PUSH1
+        test_ok(bytecode! {
+            PUSH1(0x5b)
+            PUSH1(0x01)
+            JUMP
+            STOP
+        });
+        test_ok(bytecode! {
+            PUSH1(0x5b)
+            PUSH1(0x01) // condition
+            PUSH1(0x01) // destination
+            JUMPI
+            STOP
+        });
+    }
+
+    #[test]
+    fn invalid_jump_to_non_jumpdest() {
+        // The destination is the STOP
+        test_ok(bytecode! {
+            PUSH1(0x03)
+            JUMP
+            STOP
+        });
+        // The destination is out of the bytecode
+        test_ok(bytecode! {
+            PUSH32(Word::MAX)
+            JUMP
+            STOP
+        });
+        test_ok(bytecode! {
+            PUSH1(0x01) // condition
+            PUSH1(0x40) // destination
+            JUMPI
+            STOP
+        });
+    }
+}
@@ -0,0 +1,79 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{common_gadget::CommonErrorGadget, constraint_builder::ConstraintBuilder, CachedRegion},
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the designated invalid opcode `0xfe` and any undefined opcode,
+/// which are exactly the responsible opcodes of this execution state.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidOpcodeGadget<F> {
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidOpcode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidOpcode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        _: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.common_error_gadget
+            .assign_exec_step(region, offset, call, step)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+    use mock::TestContext;
+
+    fn test_ok(invalid_byte: u8) {
+        // The SSTORE is reverted since the transaction fails
+        let mut code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+        };
+        code.write(invalid_byte);
+
+        assert_eq!(
+            run_test_circuits(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+                None
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn invalid_opcode_designated() {
+        test_ok(0xfe);
+    }
+
+    #[test]
+    fn invalid_opcode_undefined() {
+        test_ok(0x0c);
+        test_ok(0xef);
+    }
+}
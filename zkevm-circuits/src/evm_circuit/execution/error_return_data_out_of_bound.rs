@@ -0,0 +1,132 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{AddWordsGadget, IsZeroGadget, LtGadget},
+            sum, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for RETURNDATACOPY reading past the end of the return data of the
+/// last callee, i.e. `data_offset + length > return_data_length`, where the
+/// addition is done in 256 bits so an overflowing sum is also out of bound.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorReturnDataOutOfBoundGadget<F> {
+    memory_offset: Cell<F>,
+    data_end: AddWordsGadget<F, 2, false>,
+    return_data_length: Cell<F>,
+    is_data_end_high_zero: IsZeroGadget<F>,
+    is_data_end_gt_return_data_length: LtGadget<F, N_BYTES_U64>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
+    const NAME: &'static str = "ErrorReturnDataOutOfBound";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorReturnDataOutOfBound;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Pop memory_offset, data_offset, length from stack
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_word();
+        let length = cb.query_word();
+        let data_end = cb.query_word();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Lookup the length of the return data of the last callee
+        let return_data_length =
+            cb.call_context(None, CallContextFieldTag::LastCalleeReturnDataLength);
+
+        // Check data_offset + length > return_data_length, which holds when
+        // the addition overflows, when the sum doesn't fit in 8 bytes, or
+        // when its low 8 bytes are greater than return_data_length.
+        let data_end = AddWordsGadget::construct(cb, [data_offset, length], data_end);
+        let is_data_end_high_zero =
+            IsZeroGadget::construct(cb, sum::expr(&data_end.sum().cells[N_BYTES_U64..]));
+        let is_data_end_gt_return_data_length = LtGadget::construct(
+            cb,
+            return_data_length.expr(),
+            from_bytes::expr(&data_end.sum().cells[..N_BYTES_U64]),
+        );
+        let carry = data_end.carry().as_ref().unwrap().expr();
+        cb.require_zero(
+            "data_offset + length > return_data_length",
+            (1.expr() - carry)
+                * is_data_end_high_zero.expr()
+                * (1.expr() - is_data_end_gt_return_data_length.expr()),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode);
+
+        Self {
+            memory_offset,
+            data_end,
+            return_data_length,
+            is_data_end_high_zero,
+            is_data_end_gt_return_data_length,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, data_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        let return_data_length = block.rws[step.rw_indices[3]].call_context_value();
+
+        self.memory_offset.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        let (data_end, _) = data_offset.overflowing_add(length);
+        self.data_end
+            .assign(region, offset, [data_offset, length], data_end)?;
+        let data_end_bytes = data_end.to_le_bytes();
+        self.is_data_end_high_zero.assign(
+            region,
+            offset,
+            sum::value(&data_end_bytes[N_BYTES_U64..]),
+        )?;
+
+        let return_data_length = return_data_length.to_scalar().unwrap();
+        self.return_data_length
+            .assign(region, offset, Some(return_data_length))?;
+        self.is_data_end_gt_return_data_length.assign(
+            region,
+            offset,
+            return_data_length,
+            from_bytes::value(&data_end_bytes[..N_BYTES_U64]),
+        )?;
+
+        self.common_error_gadget
+            .assign_exec_step(region, offset, call, step)
+    }
+}
@@ -5,13 +5,11 @@ use crate::{
         step::ExecutionState,
         table::{AccountFieldTag, CallContextFieldTag},
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{AccountEmptyGadget, SameContextGadget},
             constraint_builder::{
                 ConstraintBuilder, ReversionInfo, StepStateTransition, Transition::Delta,
             },
-            from_bytes,
-            math_gadget::BatchedIsZeroGadget,
-            CachedRegion, Cell, RandomLinearCombination, Word,
+            from_bytes, CachedRegion, Cell, RandomLinearCombination,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -19,7 +17,6 @@ use crate::{
 };
 use eth_types::{evm_types::GasCost, Field, ToAddress, ToScalar, U256};
 use halo2_proofs::plonk::Error;
-use keccak256::EMPTY_HASH_LE;
 
 #[derive(Clone, Debug)]
 pub(crate) struct ExtcodehashGadget<F> {
@@ -31,7 +28,7 @@ pub(crate) struct ExtcodehashGadget<F> {
     nonce: Cell<F>,
     balance: Cell<F>,
     code_hash: Cell<F>,
-    is_empty: BatchedIsZeroGadget<F, 3>, // boolean for if the external account is empty
+    is_empty: AccountEmptyGadget<F>, // boolean for if the external account is empty
 }
 
 impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
@@ -74,20 +71,8 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
             code_hash.expr(),
         );
 
-        let empty_code_hash_rlc = Word::random_linear_combine_expr(
-            (*EMPTY_HASH_LE).map(|byte| byte.expr()),
-            cb.power_of_randomness(),
-        );
-        // Note that balance is RLC encoded, but RLC(x) = 0 iff x = 0, so we don't need
-        // go to the work of writing out the RLC expression
-        let is_empty = BatchedIsZeroGadget::construct(
-            cb,
-            [
-                nonce.expr(),
-                balance.expr(),
-                code_hash.expr() - empty_code_hash_rlc,
-            ],
-        );
+        let is_empty =
+            AccountEmptyGadget::construct(cb, nonce.expr(), balance.expr(), code_hash.expr());
 
         // The stack push is 0 if the external account is empty. Otherwise, it's the
         // code hash
@@ -164,12 +149,8 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
         self.balance.assign(region, offset, Some(balance))?;
         self.code_hash.assign(region, offset, Some(code_hash))?;
 
-        let empty_code_hash_rlc = Word::random_linear_combine(*EMPTY_HASH_LE, block.randomness);
-        self.is_empty.assign(
-            region,
-            offset,
-            [nonce, balance, code_hash - empty_code_hash_rlc],
-        )?;
+        self.is_empty
+            .assign(region, offset, nonce, balance, code_hash, block.randomness)?;
 
         Ok(())
     }
@@ -178,12 +159,12 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::{
-        evm_circuit::witness::block_convert,
-        test_util::{test_circuits_using_witness_block, BytecodeTestConfig},
+        evm_circuit::param::KECCAK_EMPTY,
+        test_util::{build_witness_block, test_circuits_using_witness_block, BytecodeTestConfig},
     };
-    use bus_mapping::mock::BlockData;
     use eth_types::{
         address, bytecode,
+        evm_types::{GasCost, OpcodeId},
         geth_types::{Account, GethData},
         Address, Bytecode, Bytes, ToWord, Word, U256,
     };
@@ -195,7 +176,9 @@ mod test {
             address!("0xaabbccddee000000000000000000000000000000");
     }
 
-    fn test_ok(external_account: Option<Account>, is_warm: bool) {
+    /// Run EXTCODEHASH on `external_account`, or on an address without any
+    /// account, and return the pushed hash.
+    fn test_ok(external_account: Option<Account>, is_warm: bool) -> Word {
         let external_address = external_account
             .as_ref()
             .map(|a| a.address)
@@ -246,16 +229,17 @@ mod test {
         .unwrap()
         .into();
 
-        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
-        builder
-            .handle_block(&block.eth_block, &block.geth_traces)
-            .expect("could not handle block tx");
-
-        test_circuits_using_witness_block(
-            block_convert(&builder.block, &builder.code_db),
-            BytecodeTestConfig::default(),
-        )
-        .unwrap();
+        let block = build_witness_block(block);
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .rev()
+            .find(|step| step.opcode == Some(OpcodeId::EXTCODEHASH))
+            .unwrap();
+        let code_hash = block.rws[*step.rw_indices.last().unwrap()].stack_value();
+
+        test_circuits_using_witness_block(block, BytecodeTestConfig::default()).unwrap();
+        code_hash
     }
 
     #[test]
@@ -294,6 +278,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn extcodehash_nonexistent_and_empty_code_accounts() {
+        // A nonexistent account has no code hash
+        assert_eq!(test_ok(None, false), Word::zero());
+        // An existing account without code has the hash of the empty code
+        let balance_only_account = Account {
+            address: *EXTERNAL_ADDRESS,
+            balance: U256::from(200),
+            ..Default::default()
+        };
+        assert_eq!(
+            test_ok(Some(balance_only_account), false),
+            Word::from_little_endian(&*KECCAK_EMPTY)
+        );
+    }
+
     #[test]
     fn extcodehash_nonempty_account_edge_cases() {
         // EIP-158 defines empty accounts to be those with balance = 0, nonce = 0, and
@@ -328,4 +328,33 @@ mod test {
             test_ok(Some(account), false);
         }
     }
+
+    #[test]
+    fn extcodehash_precompile_is_warm() {
+        // The precompiles are warm from the start of the tx (EIP-2929), so even
+        // their first access is charged the warm gas.
+        // TODO: Change this to BALANCE once is implemented
+        let code = bytecode! {
+            PUSH20(0x04)
+            EXTCODEHASH
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let block = build_witness_block(block);
+
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.opcode == Some(OpcodeId::EXTCODEHASH))
+            .unwrap();
+        assert_eq!(step.gas_cost, GasCost::WARM_ACCESS.as_u64());
+        assert!(block
+            .prewarmed_addresses()
+            .contains(&Address::from_low_u64_be(0x04)));
+
+        // The state circuit seeds the precompile into the access list.
+        test_circuits_using_witness_block(block, BytecodeTestConfig::default()).unwrap();
+    }
 }
@@ -87,11 +87,10 @@ impl<F: Field> ExecutionGadget<F> for GasGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::{
-        evm_circuit::{test::run_test_circuit, witness::block_convert},
-        test_util::{run_test_circuits, BytecodeTestConfig},
+        evm_circuit::{step::ExecutionState, test::run_test_circuit},
+        test_util::{build_witness_block, run_test_circuits, BytecodeTestConfig},
     };
-    use bus_mapping::mock::BlockData;
-    use eth_types::{address, bytecode, geth_types::GethData, Word};
+    use eth_types::{address, bytecode, evm_types::GasCost, geth_types::GethData, Word};
     use mock::TestContext;
 
     fn test_ok() {
@@ -146,11 +145,7 @@ mod test {
         .unwrap()
         .into();
 
-        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
-        builder
-            .handle_block(&block.eth_block, &block.geth_traces)
-            .expect("could not handle block tx");
-        let mut block = block_convert(&builder.block, &builder.code_db);
+        let mut block = build_witness_block(block);
 
         // The above block has 2 steps (GAS and STOP). We forcefully assign a
         // wrong `gas_left` value for the second step, to assert that
@@ -160,4 +155,62 @@ mod test {
         block.txs[0].steps[2].gas_left -= 1;
         assert!(run_test_circuit(block, config.evm_circuit_lookup_tags).is_err());
     }
+
+    #[test]
+    fn gas_gadget_after_memory_expansion() {
+        // MSTORE at offset 0 expands the memory by one word, which costs 3 gas
+        // on top of its constant gas.
+        let bytecode = bytecode! {
+            PUSH32(0x1234)
+            PUSH1(0)
+            MSTORE
+            GAS
+            STOP
+        };
+
+        let config = BytecodeTestConfig::default();
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(bytecode);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000000"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .gas(Word::from(config.gas_limit));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let block = build_witness_block(block);
+
+        let gas_step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::GAS)
+            .unwrap();
+        let expected = config.gas_limit
+            - GasCost::TX.as_u64()
+            - 2 * GasCost::FASTEST.as_u64()
+            - (GasCost::FASTEST.as_u64() + GasCost::MEMORY_EXPANSION_LINEAR_COEFF.as_u64())
+            - GasCost::QUICK.as_u64();
+        assert_eq!(
+            block.rws[gas_step.rw_indices[0]].stack_value(),
+            Word::from(expected)
+        );
+
+        assert_eq!(
+            run_test_circuit(block, config.evm_circuit_lookup_tags),
+            Ok(())
+        );
+    }
 }
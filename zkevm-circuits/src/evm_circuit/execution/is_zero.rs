@@ -5,7 +5,8 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
-            math_gadget, CachedRegion, Cell, Word,
+            math_gadget::WordIsZeroGadget,
+            CachedRegion, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -18,8 +19,8 @@ use halo2_proofs::plonk::Error;
 #[derive(Clone, Debug)]
 pub(crate) struct IsZeroGadget<F> {
     same_context: SameContextGadget<F>,
-    value: Cell<F>,
-    is_zero: math_gadget::IsZeroGadget<F>,
+    value: Word<F>,
+    is_zero: WordIsZeroGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
@@ -30,8 +31,8 @@ impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let value = cb.query_cell();
-        let is_zero = math_gadget::IsZeroGadget::construct(cb, value.expr());
+        let value = cb.query_word();
+        let is_zero = WordIsZeroGadget::construct(cb, &value);
 
         cb.stack_pop(value.expr());
         cb.stack_push(is_zero.expr());
@@ -65,8 +66,8 @@ impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
-        let value = Word::random_linear_combine(value.to_le_bytes(), block.randomness);
-        self.value.assign(region, offset, Some(value))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
         self.is_zero.assign(region, offset, value)?;
 
         Ok(())
@@ -75,8 +76,16 @@ impl<F: Field> ExecutionGadget<F> for IsZeroGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::run_test_circuits;
-    use eth_types::{bytecode, Word};
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{single_step_block, Rw},
+        },
+        test_util::{build_witness_block, run_test_circuits},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use halo2_proofs::pairing::bn256::Fr;
     use mock::TestContext;
 
     fn test_ok(value: Word) {
@@ -100,4 +109,74 @@ mod test {
         test_ok(0x060504.into());
         test_ok(0x0.into());
     }
+
+    #[test]
+    fn is_zero_gadget_single_nonzero_byte() {
+        // Only the most significant byte is nonzero
+        test_ok(Word::one() << 255);
+        test_ok(Word::from(0xff) << 248);
+    }
+
+    #[test]
+    fn is_zero_gadget_rejects_wrong_result() {
+        let block = |value: Word, result: u64| {
+            single_step_block::<Fr>(
+                ExecutionState::ISZERO,
+                OpcodeId::ISZERO,
+                vec![
+                    Rw::Stack {
+                        rw_counter: 1,
+                        is_write: false,
+                        call_id: 1,
+                        stack_pointer: 1023,
+                        value,
+                    },
+                    Rw::Stack {
+                        rw_counter: 2,
+                        is_write: true,
+                        call_id: 1,
+                        stack_pointer: 1023,
+                        value: Word::from(result),
+                    },
+                ],
+            )
+        };
+        let value = Word::one() << 255;
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(block(value, 0)),
+            Ok(())
+        );
+        assert!(run_test_circuit_incomplete_fixed_table(block(value, 1)).is_err());
+        assert!(run_test_circuit_incomplete_fixed_table(block(Word::zero(), 0)).is_err());
+    }
+
+    #[test]
+    fn is_zero_gadget_rejects_other_opcode() {
+        let bytecode = bytecode! {
+            PUSH32(Word::from(0x060504))
+            ISZERO
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        let mut block = build_witness_block(block);
+
+        // Replace ISZERO with NOT in both the step and the bytecode, so only the
+        // responsible opcode lookup fails.
+        let step = block.txs[0]
+            .steps
+            .iter_mut()
+            .find(|step| step.execution_state == ExecutionState::ISZERO)
+            .unwrap();
+        step.opcode = Some(OpcodeId::NOT);
+        let pc = step.program_counter as usize;
+        for bytecode in block.bytecodes.iter_mut() {
+            if bytecode.bytes.get(pc) == Some(&OpcodeId::ISZERO.as_u8()) {
+                bytecode.bytes[pc] = OpcodeId::NOT.as_u8();
+            }
+        }
+
+        assert!(run_test_circuit_incomplete_fixed_table(block).is_err());
+    }
 }
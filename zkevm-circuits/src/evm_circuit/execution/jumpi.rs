@@ -10,8 +10,8 @@ use crate::{
                 Transition::{Delta, To},
             },
             from_bytes,
-            math_gadget::IsZeroGadget,
-            select, CachedRegion, Cell, RandomLinearCombination, Word,
+            math_gadget::WordIsZeroGadget,
+            select, CachedRegion, RandomLinearCombination, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -26,8 +26,8 @@ use std::convert::TryInto;
 pub(crate) struct JumpiGadget<F> {
     same_context: SameContextGadget<F>,
     destination: RandomLinearCombination<F, N_BYTES_PROGRAM_COUNTER>,
-    condition: Cell<F>,
-    is_condition_zero: IsZeroGadget<F>,
+    condition: Word<F>,
+    is_condition_zero: WordIsZeroGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
@@ -37,14 +37,14 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
 
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let destination = cb.query_rlc();
-        let condition = cb.query_cell();
+        let condition = cb.query_word();
 
         // Pop the value from the stack
         cb.stack_pop(destination.expr());
         cb.stack_pop(condition.expr());
 
         // Determine if the jump condition is met
-        let is_condition_zero = IsZeroGadget::construct(cb, condition.expr());
+        let is_condition_zero = WordIsZeroGadget::construct(cb, &condition);
         let should_jump = 1.expr() - is_condition_zero.expr();
 
         // Lookup opcode at destination when should_jump
@@ -96,7 +96,6 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
 
         let [destination, condition] =
             [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
-        let condition = Word::random_linear_combine(condition.to_le_bytes(), block.randomness);
 
         self.destination.assign(
             region,
@@ -107,7 +106,8 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
                     .unwrap(),
             ),
         )?;
-        self.condition.assign(region, offset, Some(condition))?;
+        self.condition
+            .assign(region, offset, Some(condition.to_le_bytes()))?;
         self.is_condition_zero.assign(region, offset, condition)?;
 
         Ok(())
@@ -62,9 +62,11 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         // use call context's  callee address as contract address
         let contract_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
         let is_persistent = cb.call_context(None, CallContextFieldTag::IsPersistent);
+        // the log is written with the log id increased by this step
         cb.condition(is_persistent.expr(), |cb| {
             cb.tx_log_lookup(
                 tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
                 TxLogFieldTag::Address,
                 0.expr(),
                 contract_address.expr(),
@@ -79,7 +81,13 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
                 cb.stack_pop(topic.expr());
             });
             cb.condition(topic_selectors[idx].expr() * is_persistent.expr(), |cb| {
-                cb.tx_log_lookup(tx_id.expr(), TxLogFieldTag::Topic, idx.expr(), topic.expr());
+                cb.tx_log_lookup(
+                    tx_id.expr(),
+                    cb.curr.state.log_id.expr() + 1.expr(),
+                    TxLogFieldTag::Topic,
+                    idx.expr(),
+                    topic.expr(),
+                );
             });
         }
 
@@ -215,13 +223,17 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         let topic_count = (opcode.as_u8() - OpcodeId::LOG0.as_u8()) as usize;
         assert!(topic_count <= 4);
 
-        let mut rws_stack_index = 1;
+        // topics are popped after mstart and msize
+        let mut topic_stack_indices = step
+            .rw_indices
+            .iter()
+            .filter(|(tag, _)| *tag == RwTableTag::Stack)
+            .skip(2);
         for i in 0..4 {
             let mut topic = Word::random_linear_combine([0; 32], block.randomness);
             if i < topic_count {
-                rws_stack_index += 1;
                 topic = Word::random_linear_combine(
-                    block.rws[(RwTableTag::Stack, rws_stack_index)]
+                    block.rws[*topic_stack_indices.next().unwrap()]
                         .stack_value()
                         .to_le_bytes(),
                     block.randomness,
@@ -249,19 +261,25 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::evm_circuit::{
-        execution::copy_to_log::test::make_log_copy_steps,
-        step::ExecutionState,
-        table::{CallContextFieldTag, RwTableTag, TxLogFieldTag},
-        test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
-        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+    use crate::{
+        evm_circuit::{
+            execution::copy_to_log::test::make_log_copy_steps,
+            step::ExecutionState,
+            table::{CallContextFieldTag, RwTableTag, TxLogFieldTag},
+            test::{rand_bytes, run_test_circuit_incomplete_fixed_table},
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::build_witness_block,
     };
     use eth_types::{
+        bytecode,
         evm_types::{gas_utils::memory_expansion_gas_cost, GasCost, OpcodeId},
+        geth_types::GethData,
         ToBigEndian, Word,
     };
     use halo2_proofs::arithmetic::BaseExt;
     use halo2_proofs::pairing::bn256::Fr;
+    use mock::test_ctx::{helpers::*, TestContext};
     use std::convert::TryInto;
 
     // make dynamic byte code sequence base on topics
@@ -362,7 +380,7 @@ mod test {
                 rw_counter,
                 is_write: true,
                 tx_id,
-                log_id: log_id.try_into().unwrap(),
+                log_id: (log_id + 1).try_into().unwrap(),
                 field_tag: TxLogFieldTag::Address,
                 index: 0,
                 value: contract_address,
@@ -386,7 +404,7 @@ mod test {
                     rw_counter,
                     is_write: true,
                     tx_id,
-                    log_id: log_id.try_into().unwrap(),
+                    log_id: (log_id + 1).try_into().unwrap(),
                     field_tag: TxLogFieldTag::Topic,
                     index: idx,
                     value: *topic,
@@ -618,4 +636,66 @@ mod test {
             false,
         );
     }
+
+    #[test]
+    fn log_gadget_log3_in_tx_log_table() {
+        let data = (1..=64).collect::<Vec<u8>>();
+        let topics = [0xA0u64, 0xef, 0xb0].map(Word::from);
+        let (mstart, msize) = (0x08, 0x30);
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&data[..32]))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(Word::from_big_endian(&data[32..]))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(topics[2])
+            PUSH32(topics[1])
+            PUSH32(topics[0])
+            PUSH1(msize)
+            PUSH1(mstart)
+            LOG3
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let block = build_witness_block(block);
+
+        // The three topics and the data bytes are written to the TxLog
+        let tx_logs = block.rws.0[&RwTableTag::TxLog]
+            .iter()
+            .map(|rw| match rw {
+                Rw::TxLog {
+                    log_id,
+                    field_tag,
+                    index,
+                    value,
+                    ..
+                } => (*log_id, *field_tag, *index, *value),
+                _ => unreachable!(),
+            })
+            .filter(|(_, field_tag, _, _)| *field_tag != TxLogFieldTag::Address)
+            .collect::<Vec<_>>();
+        let expected = topics
+            .iter()
+            .enumerate()
+            .map(|(idx, topic)| (1, TxLogFieldTag::Topic, idx, *topic))
+            .chain(
+                data[mstart..mstart + msize]
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, byte)| (1, TxLogFieldTag::Data, idx, Word::from(*byte))),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(tx_logs, expected);
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
 }
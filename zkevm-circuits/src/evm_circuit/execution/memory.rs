@@ -12,7 +12,7 @@ use crate::{
             from_bytes,
             math_gadget::IsEqualGadget,
             memory_gadget::MemoryExpansionGadget,
-            select, CachedRegion, MemoryAddress, Word,
+            CachedRegion, MemoryAddress, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -75,40 +75,32 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
 
         /* Memory operations */
         // Read/Write the value from memory at the specified address
-        // We always read/write 32 bytes, but for MSTORE8 this will be
-        // 32 lookups for the same LSB byte (at the same gc).
-        for idx in 0..32 {
-            // For MSTORE8 we write the LSB of value 32x times to the same
-            // address For MLOAD and MSTORE we read/write all the
-            // bytes of value at an increasing address value.
-            let byte = if idx == 31 {
-                value.cells[0].expr()
-            } else {
-                select::expr(
-                    is_mstore8.expr(),
-                    value.cells[0].expr(),
+        // For MLOAD and MSTORE we read/write all the 32 bytes of value at an
+        // increasing address, while for MSTORE8 only the LSB of value is
+        // written at the address, so the other 31 lookups are disabled.
+        for idx in 0..31 {
+            cb.condition(is_not_mstore8.clone(), |cb| {
+                cb.memory_lookup_with_counter(
+                    cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr() + idx.expr(),
+                    is_store.clone(),
+                    from_bytes::expr(&address.cells) + idx.expr(),
                     value.cells[31 - idx].expr(),
-                )
-            };
-
-            // We only increase the offset for MLOAD and MSTORE so that for
-            // MSTORE8 `gc` and `address` remain the same.
-            let offset = if idx == 0 {
-                0.expr()
-            } else {
-                is_not_mstore8.clone() * idx.expr()
-            };
-            cb.memory_lookup_with_counter(
-                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr() + offset.clone(),
-                is_store.clone(),
-                from_bytes::expr(&address.cells) + offset,
-                byte,
-            );
+                );
+            });
         }
+        // The LSB is at the last address for MLOAD and MSTORE, but it's the
+        // only byte written by MSTORE8, so `gc` and `address` remain the same.
+        let offset = is_not_mstore8.clone() * 31.expr();
+        cb.memory_lookup_with_counter(
+            cb.curr.state.rw_counter.expr() + cb.rw_counter_offset().expr() + offset.clone(),
+            is_store.clone(),
+            from_bytes::expr(&address.cells) + offset,
+            value.cells[0].expr(),
+        );
 
         // State transition
         // - `rw_counter` needs to be increased by 34 when is_not_mstore8, otherwise to
-        //   be increased by 31
+        //   be increased by 3
         // - `program_counter` needs to be increased by 1
         // - `stack_pointer` needs to be increased by 2 when is_store, otherwise to be
         //   same
@@ -192,11 +184,15 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::{
-        evm_circuit::test::rand_word,
-        test_util::{run_test_circuits, BytecodeTestConfig},
+        evm_circuit::{table::RwTableTag, test::rand_word},
+        test_util::{
+            build_witness_block, run_test_circuits, test_circuits_using_witness_block,
+            BytecodeTestConfig,
+        },
     };
     use eth_types::bytecode;
     use eth_types::evm_types::{GasCost, OpcodeId};
+    use eth_types::geth_types::GethData;
     use eth_types::Word;
     use mock::test_ctx::{helpers::*, TestContext};
     use std::iter;
@@ -290,4 +286,91 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn memory_gadget_gas_breakdown() {
+        let bytecode = bytecode! {
+            PUSH32(Word::from(0xcafe))
+            PUSH32(Word::from(0x40))
+            MSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        let block = build_witness_block(block);
+
+        let steps = &block.txs[0].steps;
+        let idx = steps
+            .iter()
+            .position(|step| step.opcode == Some(OpcodeId::MSTORE))
+            .unwrap();
+        let breakdown = steps[idx].gas_breakdown(&steps[idx + 1]);
+
+        // Memory is expanded from 0 to 3 words
+        assert_eq!(breakdown.constant, GasCost::FASTEST.as_u64());
+        assert_eq!(breakdown.memory_expansion, 3 * 3 + 3 * 3 / 512);
+        assert_eq!(breakdown.dynamic, 0);
+        assert_eq!(breakdown.total(), steps[idx].gas_cost);
+    }
+
+    #[test]
+    fn memory_gadget_mstore8_writes_single_byte() {
+        let bytecode = bytecode! {
+            PUSH32(Word::MAX)
+            PUSH1(0x00)
+            MSTORE
+            PUSH2(0x1234)
+            PUSH1(0x05)
+            MSTORE8
+            PUSH1(0x00)
+            MLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        let block = build_witness_block(block);
+
+        let steps = &block.txs[0].steps;
+        let step = |opcode| {
+            steps
+                .iter()
+                .find(|step| step.opcode == Some(opcode))
+                .unwrap()
+        };
+
+        // MSTORE8 writes only the LSB of the value and charges for 1 byte
+        let mstore8 = step(OpcodeId::MSTORE8);
+        let memory_rws = mstore8
+            .rw_indices
+            .iter()
+            .map(|&idx| &block.rws[idx])
+            .filter(|rw| rw.tag() == RwTableTag::Memory)
+            .collect::<Vec<_>>();
+        assert_eq!(memory_rws.len(), 1);
+        assert!(memory_rws[0].is_write());
+        assert_eq!(memory_rws[0].memory_value(), 0x34);
+        assert_eq!(mstore8.gas_cost, GasCost::FASTEST.as_u64());
+
+        // Only the target byte changes, its neighbours are kept
+        let mload = step(OpcodeId::MLOAD);
+        let mut loaded = [0u8; 32];
+        block.rws[mload.rw_indices[1]]
+            .stack_value()
+            .to_big_endian(&mut loaded);
+        assert_eq!(loaded[5], 0x34);
+        assert_eq!(loaded[4], 0xff);
+        assert_eq!(loaded[6], 0xff);
+        assert_eq!(
+            loaded.iter().filter(|byte| **byte != 0xff).count(),
+            1,
+            "only one byte is changed"
+        );
+
+        assert_eq!(
+            test_circuits_using_witness_block(block, BytecodeTestConfig::default()),
+            Ok(())
+        );
+    }
 }
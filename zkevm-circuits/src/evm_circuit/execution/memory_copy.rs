@@ -172,9 +172,12 @@ impl<F: Field> ExecutionGadget<F> for CopyToMemoryGadget<F> {
             step.aux_data.unwrap()
         };
 
-        let from_tx = match aux.copy_details() {
-            CopyDetails::TxCallData(root_call) => root_call,
-            _ => unreachable!("the source has to come from calldata and not code"),
+        let (from_tx, src_id) = match aux.copy_details() {
+            CopyDetails::TxCallData(root_call) => {
+                (root_call, if call.is_root { tx.id } else { call.caller_id })
+            }
+            CopyDetails::Memory(src_id) => (false, src_id),
+            _ => unreachable!("the source has to come from calldata or memory and not code"),
         };
 
         self.src_addr
@@ -187,7 +190,6 @@ impl<F: Field> ExecutionGadget<F> for CopyToMemoryGadget<F> {
             .assign(region, offset, Some(F::from(aux.src_addr_end())))?;
         self.from_tx
             .assign(region, offset, Some(F::from(from_tx as u64)))?;
-        let src_id = if call.is_root { tx.id } else { call.caller_id };
         self.src_id
             .assign(region, offset, Some(F::from(src_id as u64)))?;
 
@@ -0,0 +1,179 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstraintBuilder, StepStateTransition},
+        CachedRegion,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+use std::marker::PhantomData;
+
+/// Gadget for the no-op steps filling the rows around `EndBlock`, e.g. the ones
+/// [`Block::pad_to`] inserts so a block lays out the same number of steps
+/// regardless of the length of its trace. A padding step does no rw operation
+/// and keeps the step state frozen.
+#[derive(Clone, Debug)]
+pub(crate) struct PaddingGadget<F> {
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for PaddingGadget<F> {
+    const NAME: &'static str = "Padding";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::Padding;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        // Padding does no rw operation, and passes the step state unchanged
+        // to the next step, unless it's the last one.
+        cb.condition(
+            cb.next
+                .execution_state_selector([ExecutionState::Padding, ExecutionState::EndBlock]),
+            |cb| cb.require_step_state_transition(StepStateTransition::default()),
+        );
+
+        Self {
+            _marker: PhantomData,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        _region: &mut CachedRegion<'_, '_, F>,
+        _offset: usize,
+        _: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        _step: &ExecStep,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            test::{rand_word, run_test_circuit_incomplete_fixed_table},
+            witness::{self, Block, PaddingError},
+        },
+        test_util::build_witness_block,
+    };
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::pairing::bn256::Fr;
+    use mock::TestContext;
+
+    fn block() -> Block<Fr> {
+        let bytecode = bytecode! {
+            PUSH32(rand_word())
+            PUSH32(rand_word())
+            ADD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        build_witness_block(block)
+    }
+
+    /// Return the block with `n` padding steps after its `EndBlock`
+    fn block_ending_in_padding(n: usize) -> Block<Fr> {
+        let mut block = block();
+        let steps = &mut block.txs[0].steps;
+        let end_block = steps.last().unwrap().clone();
+        steps.extend((0..n).map(|_| witness::ExecStep {
+            execution_state: ExecutionState::Padding,
+            ..end_block.clone()
+        }));
+        block
+    }
+
+    fn test_ok(n_padding_steps: usize) {
+        let mut block = block();
+        let n_steps = block.txs[0].steps.len() + n_padding_steps;
+
+        assert_eq!(block.pad_to(n_steps), Ok(()));
+
+        let steps = &block.txs[0].steps;
+        assert_eq!(steps.len(), n_steps);
+        assert_eq!(
+            steps.last().map(|step| step.execution_state),
+            Some(ExecutionState::EndBlock)
+        );
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn padding_gadget_simple() {
+        test_ok(8);
+        test_ok(16);
+    }
+
+    #[test]
+    fn padding_gadget_no_padding_needed() {
+        test_ok(0);
+    }
+
+    #[test]
+    fn padding_gadget_empty_block() {
+        let mut block = Block::<Fr>::default();
+
+        assert_eq!(block.pad_to(4), Ok(()));
+
+        assert_eq!(block.txs.len(), 1);
+        assert_eq!(
+            block.txs[0]
+                .steps
+                .iter()
+                .map(|step| step.execution_state)
+                .collect::<Vec<_>>(),
+            vec![
+                ExecutionState::Padding,
+                ExecutionState::Padding,
+                ExecutionState::Padding,
+                ExecutionState::EndBlock,
+            ]
+        );
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn padding_gadget_rejects_small_target() {
+        let mut block = block();
+        let needed = block.txs[0].steps.len();
+        assert_eq!(
+            block.pad_to(needed - 1),
+            Err(PaddingError::StepBudgetExceeded {
+                needed,
+                budget: needed - 1,
+            })
+        );
+        assert_eq!(block.txs[0].steps.len(), needed);
+    }
+
+    #[test]
+    fn padding_gadget_rejects_missing_end_block() {
+        let mut block = block();
+        block.txs[0].steps.pop();
+        assert_eq!(block.pad_to(512), Err(PaddingError::MissingEndBlock));
+    }
+
+    #[test]
+    fn padding_gadget_after_end_block() {
+        let block = block_ending_in_padding(3);
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn padding_gadget_with_rw_rejected() {
+        // The second last padding step advances the rw_counter as if it did an
+        // rw lookup
+        let mut block = block_ending_in_padding(3);
+        block.txs[0].steps.last_mut().unwrap().rw_counter += 1;
+        assert!(run_test_circuit_incomplete_fixed_table(block).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_WORD},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsZeroGadget, MinMaxGadget, RangeCheckGadget},
+            CachedRegion, Cell, RandomLinearCombination, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the ecrecover precompile (address `0x01`), which is entered from
+/// `CALL` as the first step of the callee context. It charges the fixed 3000
+/// gas and looks up the recovered address of the input `(hash, v, r, s)` in
+/// the ecrecover table, whose elliptic curve operations are verified by the
+/// signature circuit. The recovered address is written to callee's memory as
+/// 32 bytes return data and copied to the caller's return data region in the
+/// following `CopyToMemory` steps, or the return data is empty if the recovery
+/// fails. The input isn't bound to the call data yet, so the gadget is marked
+/// as a stub and rejected with the `strict` feature.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileEcrecoverGadget<F> {
+    caller_id: Cell<F>,
+    rd_offset: Cell<F>,
+    rd_length: Cell<F>,
+    msg_hash: Word<F>,
+    sig_v: Word<F>,
+    sig_r: Word<F>,
+    sig_s: Word<F>,
+    recovered_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    is_recovery_failed: IsZeroGadget<F>,
+    sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for PrecompileEcrecoverGadget<F> {
+    const NAME: &'static str = "PrecompileEcrecover";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileEcrecover;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let [caller_id, rd_offset, rd_length] = [
+            CallContextFieldTag::CallerId,
+            CallContextFieldTag::ReturnDataOffset,
+            CallContextFieldTag::ReturnDataLength,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+
+        // TODO: Verify the input is the call data in caller's memory right
+        // padded with zeros to 128 bytes. Until then the recovered address
+        // isn't bound to the call data, so the gadget is a stub.
+        cb.stub();
+        let [msg_hash, sig_v, sig_r, sig_s] = [(); 4].map(|_| cb.query_word());
+        let recovered_address = cb.query_rlc();
+        let recovered_address_expr = from_bytes::expr(&recovered_address.cells);
+        cb.ecrecover_lookup(
+            msg_hash.expr(),
+            sig_v.expr(),
+            sig_r.expr(),
+            sig_s.expr(),
+            recovered_address_expr.clone(),
+        );
+        let is_recovery_failed = IsZeroGadget::construct(cb, recovered_address_expr);
+        let is_recovered = 1.expr() - is_recovery_failed.expr();
+
+        // Verify gas cost
+        let sufficient_gas_left = RangeCheckGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr() - GasCost::PRECOMPILE_ECRECOVER.expr(),
+        );
+
+        // The return data is the recovered address left padded to 32 bytes
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            caller_id.expr(),
+            is_recovered.clone() * N_BYTES_WORD.expr(),
+            GasCost::PRECOMPILE_ECRECOVER.expr(),
+            is_recovered.clone() * N_BYTES_WORD.expr(),
+        );
+        cb.condition(is_recovered.clone(), |cb| {
+            for idx in 0..N_BYTES_WORD {
+                let byte = if idx < N_BYTES_WORD - N_BYTES_ACCOUNT_ADDRESS {
+                    0.expr()
+                } else {
+                    recovered_address.cells[N_BYTES_WORD - 1 - idx].expr()
+                };
+                cb.memory_lookup(true.expr(), idx.expr(), byte, None);
+            }
+        });
+
+        // Copy min(32, rd_length) bytes from the return data to caller's
+        // memory
+        let copy_length = MinMaxGadget::construct(cb, rd_length.expr(), N_BYTES_WORD.expr());
+        let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+        cb.constrain_next_step(
+            ExecutionState::CopyToMemory,
+            Some(is_recovered * (1.expr() - copy_length_is_zero.expr())),
+            |cb| {
+                let next_src_addr = cb.query_cell();
+                let next_dst_addr = cb.query_cell();
+                let next_bytes_left = cb.query_cell();
+                let next_src_addr_end = cb.query_cell();
+                let next_from_tx = cb.query_cell();
+                let next_src_id = cb.query_cell();
+                cb.require_zero("next_src_addr = 0", next_src_addr.expr());
+                cb.require_equal(
+                    "next_dst_addr = rd_offset",
+                    next_dst_addr.expr(),
+                    rd_offset.expr(),
+                );
+                cb.require_equal(
+                    "next_bytes_left = min(32, rd_length)",
+                    next_bytes_left.expr(),
+                    copy_length.min(),
+                );
+                cb.require_equal(
+                    "next_src_addr_end = 32",
+                    next_src_addr_end.expr(),
+                    N_BYTES_WORD.expr(),
+                );
+                cb.require_zero("next_from_tx = false", next_from_tx.expr());
+                cb.require_equal(
+                    "next_src_id = call_id",
+                    next_src_id.expr(),
+                    cb.curr.state.call_id.expr(),
+                );
+            },
+        );
+
+        Self {
+            caller_id,
+            rd_offset,
+            rd_length,
+            msg_hash,
+            sig_v,
+            sig_r,
+            sig_s,
+            recovered_address,
+            is_recovery_failed,
+            sufficient_gas_left,
+            restore_context,
+            copy_length,
+            copy_length_is_zero,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [caller_id, rd_offset, rd_length] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        for (cell, value) in [
+            (&self.caller_id, caller_id),
+            (&self.rd_offset, rd_offset),
+            (&self.rd_length, rd_length),
+        ] {
+            cell.assign(region, offset, Some(F::from(value.low_u64())))?;
+        }
+
+        let ecrecover = block
+            .ecrecovers
+            .iter()
+            .find(|ecrecover| ecrecover.call_id == call.id)
+            .unwrap();
+        for (word, value) in [
+            (&self.msg_hash, ecrecover.msg_hash),
+            (&self.sig_v, ecrecover.v),
+            (&self.sig_r, ecrecover.r),
+            (&self.sig_s, ecrecover.s),
+        ] {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        let mut recovered_address = ecrecover.recovered_address.to_fixed_bytes();
+        recovered_address.reverse();
+        self.recovered_address
+            .assign(region, offset, Some(recovered_address))?;
+        self.is_recovery_failed.assign(
+            region,
+            offset,
+            ecrecover.recovered_address.to_scalar().unwrap(),
+        )?;
+
+        self.sufficient_gas_left.assign(
+            region,
+            offset,
+            F::from(step.gas_left - GasCost::PRECOMPILE_ECRECOVER.as_u64()),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, step, 3)?;
+
+        let (copy_length, _) = self.copy_length.assign(
+            region,
+            offset,
+            F::from(rd_length.low_u64()),
+            F::from(N_BYTES_WORD as u64),
+        )?;
+        self.copy_length_is_zero
+            .assign(region, offset, copy_length)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState, test::run_test_circuit_incomplete_fixed_table, witness::block_convert,
+    };
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, ToWord, Word,
+    };
+    use mock::TestContext;
+
+    // Test vector of a valid signature from go-ethereum's precompile tests
+    const MSG_HASH: &str = "18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c";
+    const SIG_R: &str = "73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f";
+    const SIG_S: &str = "eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549";
+
+    fn test_ok(sig_v: u64, recovered_address: Option<Address>) {
+        let word = |hex: &str| Word::from_str_radix(hex, 16).unwrap();
+        let code = bytecode! {
+            PUSH32(word(MSG_HASH))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(sig_v)
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(word(SIG_R))
+            PUSH1(0x40)
+            MSTORE
+            PUSH32(word(SIG_S))
+            PUSH1(0x60)
+            MSTORE
+            PUSH1(0x20) // rd_length
+            PUSH1(0x80) // rd_offset
+            PUSH1(0x80) // cd_length
+            PUSH1(0x00) // cd_offset
+            PUSH1(0x00) // value
+            PUSH1(0x01) // ecrecover
+            PUSH2(10000) // gas
+            CALL
+            PUSH1(0x80)
+            MLOAD
+            STOP
+        };
+
+        let block = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(Address::repeat_byte(0xfe))
+                    .code(code)
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let block_data = bus_mapping::mock::BlockData::new_from_geth_data(block);
+
+        // The address returned by geth is the expected one
+        let struct_logs = &block_data.geth_traces[0].struct_logs;
+        let mload = struct_logs
+            .iter()
+            .position(|step| step.op == OpcodeId::MLOAD)
+            .unwrap();
+        assert_eq!(
+            struct_logs[mload + 1].stack.last().unwrap(),
+            recovered_address.unwrap_or_default().to_word()
+        );
+
+        let mut builder = block_data.new_circuit_input_builder();
+        builder
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .unwrap();
+        assert_eq!(
+            builder.block.ecrecover_events[0].recovered_address,
+            recovered_address
+        );
+        let block = block_convert(&builder.block, &builder.code_db);
+
+        // The precompile is charged the fixed 3000 gas
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::PrecompileEcrecover)
+            .unwrap();
+        assert_eq!(step.gas_cost, GasCost::PRECOMPILE_ECRECOVER.as_u64());
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn precompile_ecrecover_valid_signature() {
+        test_ok(
+            28,
+            Some(address!("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b")),
+        );
+    }
+
+    #[test]
+    fn precompile_ecrecover_invalid_signature() {
+        test_ok(29, None);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, PRECOMPILE_IDENTITY},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsZeroGadget, MinMaxGadget, RangeCheckGadget},
+            memory_gadget::MemoryWordSizeGadget,
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the identity precompile (address `0x04`), which is entered from
+/// `CALL` as the first step of the callee context. It charges
+/// `15 + 3 * words` gas, restores the caller's context and copies the call
+/// data to the caller's return data region in the following `CopyToMemory`
+/// steps.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileIdentityGadget<F> {
+    caller_id: Cell<F>,
+    cd_offset: Cell<F>,
+    cd_length: Cell<F>,
+    rd_offset: Cell<F>,
+    rd_length: Cell<F>,
+    words: MemoryWordSizeGadget<F>,
+    sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for PrecompileIdentityGadget<F> {
+    const NAME: &'static str = "PrecompileIdentity";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileIdentity;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let [caller_id, cd_offset, cd_length, rd_offset, rd_length] = [
+            CallContextFieldTag::CallerId,
+            CallContextFieldTag::CallDataOffset,
+            CallContextFieldTag::CallDataLength,
+            CallContextFieldTag::ReturnDataOffset,
+            CallContextFieldTag::ReturnDataLength,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+        // Bind the step to the identity precompile being the callee
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::CalleeAddress,
+            PRECOMPILE_IDENTITY.expr(),
+        );
+
+        // Verify gas cost
+        let words = MemoryWordSizeGadget::construct(cb, cd_length.expr());
+        let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.expr()
+            + GasCost::PRECOMPILE_IDENTITY_PER_WORD.expr() * words.expr();
+        let sufficient_gas_left =
+            RangeCheckGadget::construct(cb, cb.curr.state.gas_left.expr() - gas_cost.clone());
+
+        // The call data is returned as is
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            caller_id.expr(),
+            cd_length.expr(),
+            gas_cost,
+            0.expr(),
+        );
+
+        // Copy min(cd_length, rd_length) bytes from call data to return data
+        // in caller's memory
+        let copy_length = MinMaxGadget::construct(cb, cd_length.expr(), rd_length.expr());
+        let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+        cb.constrain_next_step(
+            ExecutionState::CopyToMemory,
+            Some(1.expr() - copy_length_is_zero.expr()),
+            |cb| {
+                let next_src_addr = cb.query_cell();
+                let next_dst_addr = cb.query_cell();
+                let next_bytes_left = cb.query_cell();
+                let next_src_addr_end = cb.query_cell();
+                let next_from_tx = cb.query_cell();
+                let next_src_id = cb.query_cell();
+                cb.require_equal(
+                    "next_src_addr = cd_offset",
+                    next_src_addr.expr(),
+                    cd_offset.expr(),
+                );
+                cb.require_equal(
+                    "next_dst_addr = rd_offset",
+                    next_dst_addr.expr(),
+                    rd_offset.expr(),
+                );
+                cb.require_equal(
+                    "next_bytes_left = min(cd_length, rd_length)",
+                    next_bytes_left.expr(),
+                    copy_length.min(),
+                );
+                cb.require_equal(
+                    "next_src_addr_end = cd_offset + cd_length",
+                    next_src_addr_end.expr(),
+                    cd_offset.expr() + cd_length.expr(),
+                );
+                cb.require_zero("next_from_tx = false", next_from_tx.expr());
+                cb.require_equal(
+                    "next_src_id = caller_id",
+                    next_src_id.expr(),
+                    caller_id.expr(),
+                );
+            },
+        );
+
+        Self {
+            caller_id,
+            cd_offset,
+            cd_length,
+            rd_offset,
+            rd_length,
+            words,
+            sufficient_gas_left,
+            restore_context,
+            copy_length,
+            copy_length_is_zero,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [caller_id, cd_offset, cd_length, rd_offset, rd_length] =
+            [0, 1, 2, 3, 4].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        for (cell, value) in [
+            (&self.caller_id, caller_id),
+            (&self.cd_offset, cd_offset),
+            (&self.cd_length, cd_length),
+            (&self.rd_offset, rd_offset),
+            (&self.rd_length, rd_length),
+        ] {
+            cell.assign(region, offset, Some(F::from(value.low_u64())))?;
+        }
+
+        let (cd_length, rd_length) = (cd_length.low_u64(), rd_length.low_u64());
+        let words = self.words.assign(region, offset, cd_length)?;
+        let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+            + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * words;
+        self.sufficient_gas_left
+            .assign(region, offset, F::from(step.gas_left - gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, step, 6)?;
+
+        let (copy_length, _) =
+            self.copy_length
+                .assign(region, offset, F::from(cd_length), F::from(rd_length))?;
+        self.copy_length_is_zero
+            .assign(region, offset, copy_length)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState, table::CallContextFieldTag,
+            test::run_test_circuit_incomplete_fixed_table, witness::Rw,
+        },
+        test_util::build_witness_block,
+    };
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        geth_types::GethData,
+        Address, ToWord, Word,
+    };
+    use mock::TestContext;
+
+    fn test_ok(cd_length: u64, rd_length: u64) {
+        let data = (1..=32).collect::<Vec<u8>>();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&data))
+            PUSH1(0)
+            MSTORE
+            PUSH1(rd_length)
+            PUSH1(0x40) // rd_offset
+            PUSH1(cd_length)
+            PUSH1(0) // cd_offset
+            PUSH1(0) // value
+            PUSH32(Address::from_low_u64_be(0x04).to_word())
+            PUSH2(10000) // gas
+            CALL
+            PUSH1(0x40)
+            MLOAD
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(Address::repeat_byte(0xfe))
+                    .code(code)
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        // The bytes returned by geth are the copied call data
+        let struct_logs = &block.geth_traces[0].struct_logs;
+        let mload = struct_logs
+            .iter()
+            .position(|step| step.op == OpcodeId::MLOAD)
+            .unwrap();
+        let copied = cd_length.min(rd_length) as usize;
+        let mut expected = [0u8; 32];
+        expected[..copied].copy_from_slice(&data[..copied]);
+        assert_eq!(
+            struct_logs[mload + 1].stack.last().unwrap(),
+            Word::from_big_endian(&expected)
+        );
+
+        let block = build_witness_block(block);
+
+        // The precompile is charged 15 + 3 * words gas
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::PrecompileIdentity)
+            .unwrap();
+        assert_eq!(
+            step.gas_cost,
+            GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * ((cd_length + 31) / 32)
+        );
+        // and reads its own address from the call context
+        assert!(matches!(
+            block.rws[step.rw_indices[5]],
+            Rw::CallContext {
+                field_tag: CallContextFieldTag::CalleeAddress,
+                value,
+                ..
+            } if value == Word::from(0x04)
+        ));
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn precompile_identity_simple() {
+        test_ok(32, 32);
+    }
+
+    #[test]
+    fn precompile_identity_partial_return() {
+        test_ok(32, 5);
+        test_ok(5, 32);
+    }
+
+    #[test]
+    fn precompile_identity_empty_input() {
+        test_ok(0, 32);
+    }
+}
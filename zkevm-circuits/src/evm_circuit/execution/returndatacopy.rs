@@ -0,0 +1,355 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::LtGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            CachedRegion, Cell, MemoryAddress,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian, ToScalar};
+use halo2_proofs::plonk::Error;
+
+use std::convert::TryInto;
+
+/// Gadget for RETURNDATACOPY which reads within the return data of the last
+/// callee, whose bytes are then copied from the memory of the callee by the
+/// following `CopyToMemory` steps. Reading past the end of the return data is
+/// handled by `ErrorReturnDataOutOfBoundGadget` instead.
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnDataCopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    data_offset: MemoryAddress<F>,
+    return_data_length: Cell<F>,
+    last_callee_id: Cell<F>,
+    return_data_offset: Cell<F>,
+    is_data_end_gt_return_data_length: LtGadget<F, N_BYTES_U64>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
+    const NAME: &'static str = "RETURNDATACOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURNDATACOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_rlc();
+        let length = cb.query_rlc();
+
+        // Pop memory_offset, data_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        // Lookup the return data of the last callee in the call context
+        let return_data_length = cb.query_cell();
+        let last_callee_id = cb.query_cell();
+        let return_data_offset = cb.query_cell();
+        for (field_tag, value) in [
+            (
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                return_data_length.expr(),
+            ),
+            (CallContextFieldTag::LastCalleeId, last_callee_id.expr()),
+            (
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                return_data_offset.expr(),
+            ),
+        ] {
+            cb.call_context_lookup(false.expr(), None, field_tag, value);
+        }
+
+        // The copied bytes are within the return data. Both data_offset and
+        // length fit in 5 bytes, so their sum can't overflow.
+        let is_data_end_gt_return_data_length = LtGadget::construct(
+            cb,
+            return_data_length.expr(),
+            from_bytes::expr(&data_offset.cells) + memory_address.length(),
+        );
+        cb.require_zero(
+            "data_offset + length <= return_data_length",
+            is_data_end_gt_return_data_length.expr(),
+        );
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // Constrain the next step CopyToMemory if length != 0
+        cb.constrain_next_step(
+            ExecutionState::CopyToMemory,
+            Some(memory_address.has_length()),
+            |cb| {
+                let next_src_addr = cb.query_cell();
+                let next_dst_addr = cb.query_cell();
+                let next_bytes_left = cb.query_cell();
+                let next_src_addr_end = cb.query_cell();
+                let next_from_tx = cb.query_cell();
+                let next_src_id = cb.query_cell();
+                cb.require_equal(
+                    "next_src_addr = return_data_offset + data_offset",
+                    next_src_addr.expr(),
+                    return_data_offset.expr() + from_bytes::expr(&data_offset.cells),
+                );
+                cb.require_equal(
+                    "next_dst_addr = memory_offset",
+                    next_dst_addr.expr(),
+                    memory_address.offset(),
+                );
+                cb.require_equal(
+                    "next_bytes_left = length",
+                    next_bytes_left.expr(),
+                    memory_address.length(),
+                );
+                cb.require_equal(
+                    "next_src_addr_end = return_data_offset + return_data_length",
+                    next_src_addr_end.expr(),
+                    return_data_offset.expr() + return_data_length.expr(),
+                );
+                cb.require_zero("next_from_tx = false", next_from_tx.expr());
+                cb.require_equal(
+                    "next_src_id = last_callee_id",
+                    next_src_id.expr(),
+                    last_callee_id.expr(),
+                );
+            },
+        );
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 3 stack pops + 3 call context lookups
+            rw_counter: Delta(6.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::RETURNDATACOPY.constant_gas_cost().expr()
+                    + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            memory_address,
+            data_offset,
+            return_data_length,
+            last_callee_id,
+            return_data_offset,
+            is_data_end_gt_return_data_length,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [memory_offset, data_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        let [return_data_length, last_callee_id, return_data_offset] =
+            [step.rw_indices[3], step.rw_indices[4], step.rw_indices[5]]
+                .map(|idx| block.rws[idx].call_context_value().to_scalar().unwrap());
+
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+        self.data_offset.assign(
+            region,
+            offset,
+            Some(
+                data_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+        self.return_data_length
+            .assign(region, offset, Some(return_data_length))?;
+        self.last_callee_id
+            .assign(region, offset, Some(last_callee_id))?;
+        self.return_data_offset
+            .assign(region, offset, Some(return_data_offset))?;
+        self.is_data_end_gt_return_data_length.assign(
+            region,
+            offset,
+            return_data_length,
+            F::from(data_offset.as_u64() + length.as_u64()),
+        )?;
+
+        // Memory expansion
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost as u64,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        test_util::{build_witness_block, test_circuits_using_witness_block, BytecodeTestConfig},
+    };
+    use eth_types::{bytecode, geth_types::GethData, word, ToWord, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
+
+    /// Return the execution state of the RETURNDATACOPY step which copies
+    /// `length` bytes from `data_offset` of the 32 bytes returned by the
+    /// callee, after checking the block verifies.
+    fn test_ok(data_offset: Word, length: Word) -> ExecutionState {
+        let code_b = bytecode! {
+            PUSH32(word!("0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20) // length
+            PUSH1(0x00) // offset
+            RETURN
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            PUSH32(length)
+            PUSH32(data_offset)
+            PUSH32(0x40) // memory_offset
+            RETURNDATACOPY
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let block = build_witness_block(block);
+
+        let execution_state = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.opcode == Some(bus_mapping::evm::OpcodeId::RETURNDATACOPY))
+            .unwrap()
+            .execution_state;
+        assert_eq!(
+            test_circuits_using_witness_block(block, BytecodeTestConfig::default()),
+            Ok(())
+        );
+        execution_state
+    }
+
+    #[test]
+    fn returndatacopy_gadget_simple() {
+        assert_eq!(
+            test_ok(0x10.into(), 0x08.into()),
+            ExecutionState::RETURNDATACOPY
+        );
+        assert_eq!(
+            test_ok(0x00.into(), 0x20.into()),
+            ExecutionState::RETURNDATACOPY
+        );
+    }
+
+    #[test]
+    fn returndatacopy_gadget_zero_length() {
+        assert_eq!(
+            test_ok(0x20.into(), 0x00.into()),
+            ExecutionState::RETURNDATACOPY
+        );
+    }
+
+    #[test]
+    fn returndatacopy_gadget_at_boundary() {
+        // data_offset + length == the length of the return data
+        assert_eq!(
+            test_ok(0x18.into(), 0x08.into()),
+            ExecutionState::RETURNDATACOPY
+        );
+    }
+
+    #[test]
+    fn returndatacopy_gadget_one_past_boundary() {
+        // data_offset + length == the length of the return data + 1
+        assert_eq!(
+            test_ok(0x18.into(), 0x09.into()),
+            ExecutionState::ErrorReturnDataOutOfBound
+        );
+    }
+
+    #[test]
+    fn returndatacopy_gadget_overflow() {
+        // data_offset + length doesn't fit in 8 bytes, and overflows a word
+        assert_eq!(
+            test_ok(0x10.into(), u64::MAX.into()),
+            ExecutionState::ErrorReturnDataOutOfBound
+        );
+        assert_eq!(
+            test_ok(0x10.into(), Word::MAX),
+            ExecutionState::ErrorReturnDataOutOfBound
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes, CachedRegion, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnDataSizeGadget<F> {
+    same_context: SameContextGadget<F>,
+    return_data_size: RandomLinearCombination<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnDataSizeGadget<F> {
+    const NAME: &'static str = "RETURNDATASIZE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURNDATASIZE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Add lookup constraint in the call context for the length of the
+        // return data set by the last callee.
+        let return_data_size = cb.query_rlc();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+            from_bytes::expr(&return_data_size.cells),
+        );
+
+        // The returndatasize should be pushed to the top of the stack.
+        cb.stack_push(return_data_size.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(2.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            gas_left: Delta(-OpcodeId::RETURNDATASIZE.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            return_data_size,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let return_data_size = block.rws[step.rw_indices[1]].stack_value();
+
+        self.return_data_size.assign(
+            region,
+            offset,
+            Some(
+                return_data_size.to_le_bytes()[..N_BYTES_U64]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{
+        build_witness_block, test_circuits_using_witness_block, BytecodeTestConfig,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
+
+    fn test_ok(return_data_size: usize) {
+        // code B returns `return_data_size` bytes to code A
+        let code_b = bytecode! {
+            PUSH32(return_data_size) // length
+            PUSH1(0x00) // offset
+            RETURN
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            RETURNDATASIZE
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        // The size pushed by RETURNDATASIZE is the one returned by code B
+        let block = build_witness_block(block);
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.opcode == Some(OpcodeId::RETURNDATASIZE))
+            .unwrap();
+        assert_eq!(
+            block.rws[step.rw_indices[1]].stack_value(),
+            Word::from(return_data_size)
+        );
+
+        assert_eq!(
+            test_circuits_using_witness_block(block, BytecodeTestConfig::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn returndatasize_gadget_simple() {
+        test_ok(0x40);
+    }
+
+    #[test]
+    fn returndatasize_gadget_empty() {
+        test_ok(0);
+    }
+}
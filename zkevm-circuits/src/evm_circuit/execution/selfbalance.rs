@@ -87,9 +87,12 @@ impl<F: Field> ExecutionGadget<F> for SelfbalanceGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::run_test_circuits;
-    use eth_types::bytecode;
-    use mock::TestContext;
+    use crate::{
+        evm_circuit::{step::ExecutionState, test::run_test_circuit_incomplete_fixed_table},
+        test_util::{build_witness_block, run_test_circuits},
+    };
+    use eth_types::{bytecode, geth_types::GethData, ToWord, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
 
     #[test]
     fn selfbalance_gadget_test() {
@@ -106,4 +109,59 @@ mod test {
             Ok(())
         );
     }
+
+    #[test]
+    fn selfbalance_gadget_internal_call() {
+        let (caller_balance, callee_balance) = (Word::from(0xca11e4u64), Word::from(0xca11eeu64));
+        let code_b = bytecode! {
+            SELFBALANCE
+            STOP
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            STOP
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .code(code_a)
+                    .balance(caller_balance);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .code(code_b)
+                    .balance(callee_balance);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let block = build_witness_block(block);
+
+        // SELFBALANCE in the callee pushes the balance of the callee, not the
+        // one of its caller
+        let step = block.txs[0]
+            .steps
+            .iter()
+            .find(|step| step.execution_state == ExecutionState::SELFBALANCE)
+            .unwrap();
+        assert_eq!(block.rws[step.rw_indices[2]].stack_value(), callee_balance);
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
 }
@@ -95,6 +95,7 @@ impl<F: Field> ExecutionGadget<F> for SignextendGadget<F> {
         // This will use the most significant bit of the selected byte to return
         // the sign byte, which is a byte with all its bits set to the
         // sign of the selected byte.
+        cb.use_fixed_table_tags([FixedTableTag::SignByte]);
         cb.add_lookup(
             "SignByte lookup",
             Lookup::Fixed {
@@ -589,11 +589,121 @@ fn calc_expected_tx_refund(
 #[cfg(test)]
 mod test {
 
-    use crate::test_util::{run_test_circuits, BytecodeTestConfig};
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::CallContextFieldTag,
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{single_step_block, Block, Rw},
+        },
+        test_util::{run_test_circuits, BytecodeTestConfig},
+    };
 
-    use eth_types::{bytecode, Word};
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, OpcodeId},
+        ToWord, Word,
+    };
+    use halo2_proofs::pairing::bn256::Fr;
     use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
 
+    /// A single SSTORE step of `value` over `value_prev` at `key`, whose value
+    /// before the tx was `original_value`, to a slot that is already warm.
+    fn warm_sstore_block(
+        key: Word,
+        value: Word,
+        value_prev: Word,
+        original_value: Word,
+        gas_cost: GasCost,
+    ) -> Block<Fr> {
+        let (tx_id, callee_address) = (1, MOCK_ACCOUNTS[0]);
+        let call_context = |rw_counter, field_tag, value: Word| Rw::CallContext {
+            rw_counter,
+            is_write: false,
+            call_id: 1,
+            field_tag,
+            value,
+        };
+        let stack_pop = |rw_counter, stack_pointer, value| Rw::Stack {
+            rw_counter,
+            is_write: false,
+            call_id: 1,
+            stack_pointer,
+            value,
+        };
+        let mut block = single_step_block(
+            ExecutionState::SSTORE,
+            OpcodeId::SSTORE,
+            vec![
+                call_context(1, CallContextFieldTag::TxId, Word::from(tx_id)),
+                call_context(2, CallContextFieldTag::IsStatic, Word::zero()),
+                call_context(
+                    3,
+                    CallContextFieldTag::RwCounterEndOfReversion,
+                    Word::zero(),
+                ),
+                call_context(4, CallContextFieldTag::IsPersistent, Word::one()),
+                call_context(
+                    5,
+                    CallContextFieldTag::CalleeAddress,
+                    callee_address.to_word(),
+                ),
+                stack_pop(6, 1022, key),
+                stack_pop(7, 1023, value),
+                Rw::storage(8, tx_id, callee_address, key, value_prev, original_value)
+                    .into_write(value),
+                Rw::tx_access_list_account_storage(9, tx_id, callee_address, key, true),
+                Rw::TxRefund {
+                    rw_counter: 10,
+                    is_write: true,
+                    tx_id,
+                    value: 0,
+                    value_prev: 0,
+                },
+            ],
+        );
+
+        let call = &mut block.txs[0].calls[0];
+        call.is_persistent = true;
+        call.callee_address = callee_address;
+        let steps = &mut block.txs[0].steps;
+        steps[0].gas_cost = gas_cost.as_u64();
+        steps[1].gas_left = steps[0].gas_left - gas_cost.as_u64();
+        steps[1].reversible_write_counter = 3;
+        block
+    }
+
+    #[test]
+    fn sstore_gadget_warm_dirty_slot() {
+        // value_prev != value, original_value != value_prev, so a warm SSTORE
+        // costs WARM_ACCESS without any refund
+        let (key, value, value_prev, original_value) = (
+            Word::from(0x030201),
+            Word::from(3),
+            Word::from(2),
+            Word::from(1),
+        );
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(warm_sstore_block(
+                key,
+                value,
+                value_prev,
+                original_value,
+                GasCost::WARM_ACCESS,
+            )),
+            Ok(())
+        );
+        // Overwriting the original value would cost SSTORE_RESET instead
+        assert!(run_test_circuit_incomplete_fixed_table(warm_sstore_block(
+            key,
+            value,
+            original_value,
+            original_value,
+            GasCost::WARM_ACCESS,
+        ))
+        .is_err());
+    }
+
     #[test]
     fn sstore_gadget_no_refund() {
         // value_prev == value
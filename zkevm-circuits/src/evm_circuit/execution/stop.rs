@@ -23,6 +23,7 @@ impl<F: Field> ExecutionGadget<F> for StopGadget<F> {
     fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
         cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.responsible_opcode_lookup(opcode.expr());
 
         // Other constraints are ignored now for STOP to serve as a mocking
         // terminator
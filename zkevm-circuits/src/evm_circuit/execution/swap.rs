@@ -34,6 +34,9 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
         // 'swapx' The offset starts at 1 for SWAP1
         let swap_offset = opcode.expr() - (OpcodeId::SWAP1.as_u64() - 1).expr();
 
+        // SWAPx needs at least x + 1 items on the stack
+        cb.require_stack_height(swap_offset.clone() + 1.expr());
+
         // Peek the value at `swap_offset`
         cb.stack_lookup(false.expr(), swap_offset.clone(), values[0].expr());
         // Peek the value at the top of the stack
@@ -90,9 +93,18 @@ impl<F: Field> ExecutionGadget<F> for SwapGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_word, test_util::run_test_circuits};
+    use crate::{
+        evm_circuit::{
+            param::STACK_CAPACITY,
+            step::ExecutionState,
+            test::{rand_word, run_test_circuit_incomplete_fixed_table},
+            witness::{single_step_block, Block, Rw},
+        },
+        test_util::run_test_circuits,
+    };
     use eth_types::evm_types::OpcodeId;
     use eth_types::{bytecode, Word};
+    use halo2_proofs::pairing::bn256::Fr;
     use mock::TestContext;
 
     fn test_ok(opcode: OpcodeId, lhs: Word, rhs: Word) {
@@ -119,6 +131,47 @@ mod test {
         );
     }
 
+    /// A block of a SWAPx step with only `height` items on the stack
+    fn swap_block(opcode: OpcodeId, height: usize) -> Block<Fr> {
+        let n = (opcode.as_u8() - OpcodeId::SWAP1.as_u8() + 1) as usize;
+        let stack_pointer = STACK_CAPACITY - height;
+        let (lhs, rhs) = (Word::from(0x030201), Word::from(0x040506));
+        single_step_block(
+            ExecutionState::SWAP,
+            opcode,
+            [
+                (false, stack_pointer + n, lhs),
+                (false, stack_pointer, rhs),
+                (true, stack_pointer + n, rhs),
+                (true, stack_pointer, lhs),
+            ]
+            .iter()
+            .enumerate()
+            .map(|(idx, &(is_write, stack_pointer, value))| Rw::Stack {
+                rw_counter: idx + 1,
+                is_write,
+                call_id: 1,
+                stack_pointer,
+                value,
+            })
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn swap_gadget_stack_height() {
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(swap_block(OpcodeId::SWAP2, 3)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn swap_gadget_stack_underflow() {
+        // SWAP3 swaps with an item past the bottom of a stack of 3 items
+        assert!(run_test_circuit_incomplete_fixed_table(swap_block(OpcodeId::SWAP3, 3)).is_err());
+    }
+
     #[test]
     fn swap_gadget_simple() {
         test_ok(OpcodeId::SWAP1, Word::from(0x030201), Word::from(0x040506));
@@ -14,6 +14,7 @@ pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Bytecode, 4),
     (Table::Block, 1),
     (Table::Byte, 24),
+    (Table::Ecrecover, 1),
 ];
 
 /// Maximum number of bytes that an integer can fit in field without wrapping
@@ -23,6 +24,10 @@ pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
 // Number of bytes an EVM word has.
 pub(crate) const N_BYTES_WORD: usize = 32;
 
+/// Minimum number of powers of randomness, which are required to compose the
+/// random linear combination of an EVM word.
+pub const MIN_N_POWER_OF_RANDOMNESS: usize = N_BYTES_WORD - 1;
+
 // Number of bytes an u64 has.
 pub(crate) const N_BYTES_U64: usize = 8;
 
@@ -36,6 +41,9 @@ pub(crate) const N_BYTES_MEMORY_WORD_SIZE: usize = 4;
 
 pub(crate) const STACK_CAPACITY: usize = 1024;
 
+/// Maximum call depth, a call made from this depth fails the precheck.
+pub(crate) const MAX_CALL_DEPTH: u64 = 1024;
+
 // Number of bytes that will be used of prorgam counter. Although the maximum
 // size of execution bytecode could be at most 128kB due to the size limit of a
 // transaction, which could be covered by 3 bytes, we still support program
@@ -48,3 +56,13 @@ pub(crate) const N_BYTES_GAS: usize = N_BYTES_U64;
 
 // Number of bytes that will be used for call data's size.
 pub(crate) const N_BYTES_CALLDATASIZE: usize = N_BYTES_U64;
+
+/// Address of the ecrecover precompile
+pub(crate) const PRECOMPILE_ECRECOVER: u64 = 0x01;
+/// Address of the identity precompile
+pub(crate) const PRECOMPILE_IDENTITY: u64 = 0x04;
+
+/// Keccak256 hash of the empty input, which is the code hash of the accounts
+/// without code, in little-endian bytes to be packed into a field element by
+/// random linear combination like the other words.
+pub(crate) use keccak256::EMPTY_HASH_LE as KECCAK_EMPTY;
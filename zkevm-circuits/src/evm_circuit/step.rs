@@ -23,9 +23,13 @@ pub enum ExecutionState {
     BeginTx,
     EndTx,
     EndBlock,
+    Padding,
     CopyCodeToMemory,
     CopyToMemory,
     CopyToLog,
+    // Precompiles
+    PrecompileEcrecover,
+    PrecompileIdentity,
     // Opcode successful cases
     STOP,
     ADD_SUB,     // ADD, SUB
@@ -149,8 +153,6 @@ impl ExecutionState {
                 | Self::ErrorStackOverflow
                 | Self::ErrorStackUnderflow
                 | Self::ErrorWriteProtection
-                | Self::ErrorDepth
-                | Self::ErrorInsufficientBalance
                 | Self::ErrorContractAddressCollision
                 | Self::ErrorInvalidCreationCode
                 | Self::ErrorMaxCodeSizeExceeded
@@ -179,7 +181,9 @@ impl ExecutionState {
 
     pub(crate) fn responsible_opcodes(&self) -> Vec<OpcodeId> {
         match self {
-            Self::STOP => vec![OpcodeId::STOP],
+            // TODO: Remove RETURN and REVERT once they are handled by their own
+            // gadgets instead of the STOP terminator.
+            Self::STOP => vec![OpcodeId::STOP, OpcodeId::RETURN, OpcodeId::REVERT],
             Self::ADD_SUB => vec![OpcodeId::ADD, OpcodeId::SUB],
             Self::MUL_DIV_MOD => vec![OpcodeId::MUL, OpcodeId::DIV, OpcodeId::MOD],
             Self::SDIV => vec![OpcodeId::SDIV],
@@ -318,9 +322,79 @@ impl ExecutionState {
             Self::STATICCALL => vec![OpcodeId::STATICCALL],
             Self::REVERT => vec![OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
+            Self::ErrorInvalidJump => vec![OpcodeId::JUMP, OpcodeId::JUMPI],
+            Self::ErrorReturnDataOutOfBound => vec![OpcodeId::RETURNDATACOPY],
+            Self::ErrorInvalidOpcode => (0..=u8::MAX)
+                .filter(|byte| {
+                    matches!(OpcodeId::try_from(*byte), Ok(OpcodeId::INVALID(_)) | Err(_))
+                })
+                .map(OpcodeId::INVALID)
+                .collect(),
             _ => vec![],
         }
     }
+
+    /// The rw_counter deltas declared by the step state transitions of the
+    /// execution state in order, with a `Same` transition counted as 0, or
+    /// `None` when they depend on the rw lookups done by the step, e.g. on the
+    /// number of bytes copied.
+    pub(crate) fn rw_counter_deltas(&self) -> Option<Vec<u64>> {
+        match self {
+            Self::Padding | Self::JUMPDEST => Some(vec![0]),
+            Self::BLOCKCTXU64
+            | Self::BLOCKCTXU160
+            | Self::BLOCKCTXU256
+            | Self::CHAINID
+            | Self::GAS
+            | Self::JUMP
+            | Self::MSIZE
+            | Self::PC
+            | Self::POP
+            | Self::PUSH => Some(vec![1]),
+            Self::CALLDATASIZE
+            | Self::CALLER
+            | Self::CALLVALUE
+            | Self::DUP
+            | Self::GASPRICE
+            | Self::ISZERO
+            | Self::JUMPI
+            | Self::ORIGIN
+            | Self::RETURNDATASIZE => Some(vec![2]),
+            Self::ADD_SUB
+            | Self::BITWISE
+            | Self::BYTE
+            | Self::CMP
+            | Self::MUL_DIV_MOD
+            | Self::SCMP
+            | Self::SELFBALANCE
+            | Self::SIGNEXTEND => Some(vec![3]),
+            Self::SWAP => Some(vec![4]),
+            // 3 stack pops and 3 call context lookups
+            Self::RETURNDATACOPY => Some(vec![6]),
+            Self::SLOAD => Some(vec![8]),
+            Self::SSTORE => Some(vec![10]),
+            Self::ErrorDepth => Some(vec![20]),
+            Self::BeginTx | Self::ErrorInsufficientBalance => Some(vec![22]),
+            // Calling an account without code, and one with code
+            Self::CALL => Some(vec![25, 44]),
+            Self::EndTx
+            | Self::CopyCodeToMemory
+            | Self::CopyToMemory
+            | Self::CopyToLog
+            | Self::PrecompileEcrecover
+            | Self::PrecompileIdentity
+            | Self::CALLDATALOAD
+            | Self::CALLDATACOPY
+            | Self::CODECOPY
+            | Self::EXTCODEHASH
+            | Self::MEMORY
+            | Self::LOG
+            | Self::ErrorInvalidOpcode
+            | Self::ErrorInvalidJump
+            | Self::ErrorReturnDataOutOfBound => None,
+            _ => Some(vec![]),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -27,7 +27,7 @@ impl<F: FieldExt, const W: usize> LookupTable<F> for [Column<Fixed>; W] {
     }
 }
 
-#[derive(Clone, Copy, Debug, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
 pub enum FixedTableTag {
     Zero = 0,
     Range5,
@@ -118,10 +118,11 @@ pub enum TxContextFieldTag {
     CallDataLength,
     CallDataGasCost,
     CallData,
+    AccessListGasCost,
 }
 
 // Keep the sequence consistent with OpcodeId for scalar
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, EnumIter)]
 pub enum BlockContextFieldTag {
     Coinbase = 1,
     Timestamp,
@@ -240,6 +241,7 @@ pub(crate) enum Table {
     Bytecode,
     Block,
     Byte,
+    Ecrecover,
 }
 
 #[derive(Clone, Debug)]
@@ -309,6 +311,21 @@ pub(crate) enum Lookup<F> {
         /// Value of the field.
         value: Expression<F>,
     },
+    /// Lookup to ecrecover table, which contains the input and output of
+    /// calls to the ecrecover precompile in this block. The elliptic curve
+    /// operations are verified by the signature circuit instead.
+    Ecrecover {
+        /// Hash of the signed message.
+        msg_hash: Expression<F>,
+        /// Recovery id of the signature, which is 27 or 28 for a valid one.
+        v: Expression<F>,
+        /// R component of the signature.
+        r: Expression<F>,
+        /// S component of the signature.
+        s: Expression<F>,
+        /// Recovered address, which is 0 when the recovery fails.
+        recovered_address: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -326,6 +343,7 @@ impl<F: FieldExt> Lookup<F> {
             Self::Bytecode { .. } => Table::Bytecode,
             Self::Block { .. } => Table::Block,
             Self::Byte { .. } => Table::Byte,
+            Self::Ecrecover { .. } => Table::Ecrecover,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -374,6 +392,19 @@ impl<F: FieldExt> Lookup<F> {
             Self::Byte { value } => {
                 vec![value.clone()]
             }
+            Self::Ecrecover {
+                msg_hash,
+                v,
+                r,
+                s,
+                recovered_address,
+            } => vec![
+                msg_hash.clone(),
+                v.clone(),
+                r.clone(),
+                s.clone(),
+                recovered_address.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
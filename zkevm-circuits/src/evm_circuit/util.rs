@@ -72,7 +72,7 @@ impl<F: FieldExt> Expr<F> for &Cell<F> {
 pub struct CachedRegion<'r, 'b, F: FieldExt> {
     region: &'r mut Region<'b, F>,
     advice: Vec<Vec<F>>,
-    power_of_randomness: [F; 31],
+    power_of_randomness: &'r [F],
     width_start: usize,
     height_start: usize,
 }
@@ -81,7 +81,7 @@ impl<'r, 'b, F: FieldExt> CachedRegion<'r, 'b, F> {
     /// New cached region
     pub(crate) fn new(
         region: &'r mut Region<'b, F>,
-        power_of_randomness: [F; 31],
+        power_of_randomness: &'r [F],
         width: usize,
         height: usize,
         width_start: usize,
@@ -1,18 +1,27 @@
 use super::CachedRegion;
 use crate::{
     evm_circuit::{
-        param::N_BYTES_GAS,
-        table::{AccountFieldTag, FixedTableTag, Lookup},
+        param::{KECCAK_EMPTY, N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
         util::{
-            constraint_builder::{ConstraintBuilder, ReversionInfo, StepStateTransition},
-            math_gadget::{AddWordsGadget, RangeCheckGadget},
-            Cell, Word,
+            constraint_builder::{
+                ConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same, To},
+            },
+            from_bytes,
+            math_gadget::{AddWordsGadget, BatchedIsZeroGadget, IsZeroGadget, RangeCheckGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+            select, sum, Cell, Word,
         },
-        witness::ExecStep,
+        witness::{Block, Call, ExecStep},
     },
     util::Expr,
 };
-use eth_types::{Field, U256};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId, GAS_STIPEND_CALL_WITH_VALUE},
+    Field, ToLittleEndian, ToScalar, U256,
+};
 use halo2_proofs::plonk::{Error, Expression};
 use std::convert::TryInto;
 
@@ -32,17 +41,7 @@ impl<F: Field> SameContextGadget<F> {
         step_state_transition: StepStateTransition<F>,
     ) -> Self {
         cb.opcode_lookup(opcode.expr(), 1.expr());
-        cb.add_lookup(
-            "Responsible opcode lookup",
-            Lookup::Fixed {
-                tag: FixedTableTag::ResponsibleOpcode.expr(),
-                values: [
-                    cb.execution_state().as_u64().expr(),
-                    opcode.expr(),
-                    0.expr(),
-                ],
-            },
-        );
+        cb.responsible_opcode_lookup(opcode.expr());
 
         // Check gas_left is sufficient
         let sufficient_gas_left = RangeCheckGadget::construct(cb, cb.next.state.gas_left.expr());
@@ -214,6 +213,11 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
     }
 }
 
+/// Transfers `value` from sender to receiver as reversible balance writes.
+/// Sender's balance can't underflow because `balance + value == balance_prev`
+/// is verified without overflow, so the caller needs to route the case
+/// `balance_prev < value` to a failure execution state (e.g.
+/// `ErrorInsufficientBalance`) instead of using this gadget.
 #[derive(Clone, Debug)]
 pub(crate) struct TransferGadget<F> {
     sender: UpdateBalanceGadget<F, 2, false>,
@@ -269,3 +273,609 @@ impl<F: Field> TransferGadget<F> {
         Ok(())
     }
 }
+
+/// Checks whether an account is empty as defined in EIP-161, i.e. it has a
+/// zero nonce, a zero balance and the code hash [`KECCAK_EMPTY`]. The balance
+/// and the code hash are random linear combinations, which are zero iff the
+/// words are zero.
+#[derive(Clone, Debug)]
+pub(crate) struct AccountEmptyGadget<F> {
+    is_nonce_and_balance_zero: BatchedIsZeroGadget<F, 2>,
+    is_empty_code_hash: IsZeroGadget<F>,
+}
+
+impl<F: Field> AccountEmptyGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        nonce: Expression<F>,
+        balance: Expression<F>,
+        code_hash: Expression<F>,
+    ) -> Self {
+        let keccak_empty = Word::random_linear_combine_expr(
+            KECCAK_EMPTY.map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let is_nonce_and_balance_zero = BatchedIsZeroGadget::construct(cb, [nonce, balance]);
+        let is_empty_code_hash = IsZeroGadget::construct(cb, code_hash - keccak_empty);
+
+        Self {
+            is_nonce_and_balance_zero,
+            is_empty_code_hash,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.is_nonce_and_balance_zero.expr() * self.is_empty_code_hash.expr()
+    }
+
+    /// Whether the account has no code, regardless of its nonce and balance
+    pub(crate) fn is_empty_code_hash(&self) -> Expression<F> {
+        self.is_empty_code_hash.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        nonce: F,
+        balance: F,
+        code_hash: F,
+        randomness: F,
+    ) -> Result<F, Error> {
+        let keccak_empty = Word::random_linear_combine(*KECCAK_EMPTY, randomness);
+        let is_nonce_and_balance_zero =
+            self.is_nonce_and_balance_zero
+                .assign(region, offset, [nonce, balance])?;
+        let is_empty_code_hash =
+            self.is_empty_code_hash
+                .assign(region, offset, code_hash - keccak_empty)?;
+        Ok(is_nonce_and_balance_zero * is_empty_code_hash)
+    }
+}
+
+/// Construction of the end of a precompile call, which restores caller's
+/// context saved by `CALL` and sets the return data of the call, which is at
+/// offset 0 in callee's memory. The precompile is assumed to be successful, so
+/// the callee's reversible writes are accumulated to the caller's.
+#[derive(Clone, Debug)]
+pub(crate) struct RestoreContextGadget<F> {
+    caller_is_root: Cell<F>,
+    caller_is_create: Cell<F>,
+    caller_code_source: Cell<F>,
+    caller_pc: Cell<F>,
+    caller_sp: Cell<F>,
+    caller_gas_left: Cell<F>,
+    caller_memory_size: Cell<F>,
+    caller_rwc: Cell<F>,
+}
+
+impl<F: Field> RestoreContextGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        caller_id: Expression<F>,
+        return_data_length: Expression<F>,
+        gas_cost: Expression<F>,
+        subsequent_rw_lookups: Expression<F>,
+    ) -> Self {
+        // Read caller's call context and the call state saved by CALL
+        let [caller_is_root, caller_is_create, caller_code_source] = [
+            CallContextFieldTag::IsRoot,
+            CallContextFieldTag::IsCreate,
+            CallContextFieldTag::CodeSource,
+        ]
+        .map(|field_tag| cb.call_context(Some(caller_id.clone()), field_tag));
+        let [caller_pc, caller_sp, caller_gas_left, caller_memory_size, caller_rwc] = [
+            CallContextFieldTag::ProgramCounter,
+            CallContextFieldTag::StackPointer,
+            CallContextFieldTag::GasLeft,
+            CallContextFieldTag::MemorySize,
+            CallContextFieldTag::StateWriteCounter,
+        ]
+        .map(|field_tag| cb.call_context(Some(caller_id.clone()), field_tag));
+
+        // Set the return data of the call for caller
+        for (field_tag, value) in [
+            (
+                CallContextFieldTag::LastCalleeId,
+                cb.curr.state.call_id.expr(),
+            ),
+            (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+            (
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                return_data_length,
+            ),
+        ] {
+            cb.call_context_lookup(true.expr(), Some(caller_id.clone()), field_tag, value);
+        }
+
+        // Return to caller with the gas left
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset() + subsequent_rw_lookups),
+            call_id: To(caller_id),
+            is_root: To(caller_is_root.expr()),
+            is_create: To(caller_is_create.expr()),
+            code_hash: To(caller_code_source.expr()),
+            program_counter: To(caller_pc.expr()),
+            stack_pointer: To(caller_sp.expr()),
+            gas_left: To(caller_gas_left.expr() + cb.curr.state.gas_left.expr() - gas_cost),
+            memory_word_size: To(caller_memory_size.expr()),
+            reversible_write_counter: To(
+                caller_rwc.expr() + cb.curr.state.reversible_write_counter.expr()
+            ),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            caller_is_root,
+            caller_is_create,
+            caller_code_source,
+            caller_pc,
+            caller_sp,
+            caller_gas_left,
+            caller_memory_size,
+            caller_rwc,
+        }
+    }
+
+    /// Assign the values of the rw lookups starting at `rw_offset` in the
+    /// step's rw indices.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        rw_offset: usize,
+    ) -> Result<(), Error> {
+        let value = |idx: usize| block.rws[step.rw_indices[rw_offset + idx]].call_context_value();
+
+        for (idx, cell) in [
+            (0, &self.caller_is_root),
+            (1, &self.caller_is_create),
+            (3, &self.caller_pc),
+            (4, &self.caller_sp),
+            (5, &self.caller_gas_left),
+            (6, &self.caller_memory_size),
+            (7, &self.caller_rwc),
+        ] {
+            cell.assign(region, offset, Some(F::from(value(idx).low_u64())))?;
+        }
+        self.caller_code_source.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                value(2).to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Construction of execution state that fails with an error, which lookups the
+/// opcode and verifies the execution state is responsible for it, then
+/// consumes all the gas left and reverts the current call. It should be
+/// constructed after all the other rw lookups of the step, since the
+/// reversion happens right after them.
+#[derive(Clone, Debug)]
+pub(crate) struct CommonErrorGadget<F> {
+    opcode: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+}
+
+impl<F: Field> CommonErrorGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, opcode: Cell<F>) -> Self {
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.responsible_opcode_lookup(opcode.expr());
+
+        // The call fails, so all its reversible writes are reverted right
+        // after this step
+        let reversion_info = cb.reversion_info(None);
+        cb.require_zero("Call fails", reversion_info.is_persistent());
+        cb.require_equal(
+            "rw_counter_end_of_reversion == rw_counter + rw_counter_offset + reversible_write_counter - 1",
+            reversion_info.rw_counter_end_of_reversion(),
+            cb.curr.state.rw_counter.expr()
+                + cb.rw_counter_offset()
+                + cb.curr.state.reversible_write_counter.expr()
+                - 1.expr(),
+        );
+
+        // When it's a root call, the transaction ends with all gas consumed
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_next_state(ExecutionState::EndTx);
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Delta(
+                    cb.rw_counter_offset() + cb.curr.state.reversible_write_counter.expr(),
+                ),
+                call_id: Same,
+                gas_left: To(0.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // TODO: Use ContextSwitchGadget to switch call context to caller's
+        // when it's an internal call.
+
+        Self {
+            opcode,
+            reversion_info,
+        }
+    }
+
+    pub(crate) fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Construction of a CALL which fails the precheck, either because of the call
+/// depth or the caller's balance. The operands are popped off the stack and
+/// `0` is pushed back, the callee is added into access list, and the
+/// execution continues in the caller with the gas reserved for the callee
+/// returned, so the caller is not reverted. The reads specific to the failure
+/// should be done before it, since the step state transition is constrained
+/// at its end.
+#[derive(Clone, Debug)]
+pub(crate) struct CallPrecheckFailureGadget<F> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_static: Cell<F>,
+    depth: Cell<F>,
+    gas: Word<F>,
+    callee_address: Word<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    is_warm_prev: Cell<F>,
+    cd_address: MemoryAddressGadget<F>,
+    rd_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    callee_nonce: Cell<F>,
+    callee_balance: Word<F>,
+    callee_code_hash: Cell<F>,
+    is_account_empty: AccountEmptyGadget<F>,
+}
+
+impl<F: Field> CallPrecheckFailureGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // TODO: Support CALLCODE, DELEGATECALL, STATICCALL and CREATE/CREATE2
+        // when they are implemented.
+        cb.require_equal(
+            "Opcode should be CALL",
+            opcode.expr(),
+            OpcodeId::CALL.expr(),
+        );
+
+        let gas = cb.query_word();
+        let callee_address = cb.query_word();
+        let value = cb.query_word();
+        let cd_offset = cb.query_cell();
+        let cd_length = cb.query_rlc();
+        let rd_offset = cb.query_cell();
+        let rd_length = cb.query_rlc();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info(None);
+        let [is_static, depth] = [CallContextFieldTag::IsStatic, CallContextFieldTag::Depth]
+            .map(|field_tag| cb.call_context(None, field_tag));
+
+        // Write protection is checked before depth and balance, so it must
+        // have been passed
+        let value_is_zero = IsZeroGadget::construct(cb, sum::expr(&value.cells));
+        let has_value = 1.expr() - value_is_zero.expr();
+        cb.condition(has_value.clone(), |cb| {
+            cb.require_zero(
+                "CALL with value must not be in static call stack",
+                is_static.expr(),
+            );
+        });
+
+        // Lookup values from stack
+        cb.stack_pop(gas.expr());
+        cb.stack_pop(callee_address.expr());
+        cb.stack_pop(value.expr());
+        cb.stack_pop(cd_offset.expr());
+        cb.stack_pop(cd_length.expr());
+        cb.stack_pop(rd_offset.expr());
+        cb.stack_pop(rd_length.expr());
+        cb.stack_push(0.expr());
+
+        let callee_address_expr =
+            from_bytes::expr(&callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let cd_address = MemoryAddressGadget::construct(cb, cd_offset, cd_length);
+        let rd_address = MemoryAddressGadget::construct(cb, rd_offset, rd_length);
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [cd_address.address(), rd_address.address()],
+        );
+
+        // Callee is still added to access list even though the call fails
+        let is_warm_prev = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            callee_address_expr.clone(),
+            1.expr(),
+            is_warm_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // Verify gas cost
+        let callee_nonce = cb.query_cell();
+        let callee_balance = cb.query_word();
+        let callee_code_hash = cb.query_cell();
+        for (field_tag, value) in [
+            (AccountFieldTag::Nonce, callee_nonce.expr()),
+            (AccountFieldTag::Balance, callee_balance.expr()),
+            (AccountFieldTag::CodeHash, callee_code_hash.expr()),
+        ] {
+            cb.account_read(callee_address_expr.clone(), field_tag, value);
+        }
+        let is_account_empty = AccountEmptyGadget::construct(
+            cb,
+            callee_nonce.expr(),
+            callee_balance.expr(),
+            callee_code_hash.expr(),
+        );
+        let gas_cost = select::expr(
+            is_warm_prev.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        ) + has_value.clone()
+            * (GasCost::CALL_WITH_VALUE.expr()
+                + is_account_empty.expr() * GasCost::NEW_ACCOUNT.expr())
+            + memory_expansion.gas_cost();
+
+        // Caller's return data is cleared
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+        }
+
+        // The gas reserved for callee is returned together with the stipend
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(6.expr()),
+            gas_left: Delta(has_value * GAS_STIPEND_CALL_WITH_VALUE.expr() - gas_cost),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            reversible_write_counter: Delta(1.expr()),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            opcode,
+            tx_id,
+            reversion_info,
+            is_static,
+            depth,
+            gas,
+            callee_address,
+            value,
+            value_is_zero,
+            is_warm_prev,
+            cd_address,
+            rd_address,
+            memory_expansion,
+            callee_nonce,
+            callee_balance,
+            callee_code_hash,
+            is_account_empty,
+        }
+    }
+
+    /// The depth of the caller
+    pub(crate) fn depth(&self) -> &Cell<F> {
+        &self.depth
+    }
+
+    /// The value to transfer to the callee
+    pub(crate) fn value(&self) -> &Word<F> {
+        &self.value
+    }
+
+    /// Assign the witness of the step, whose rws from `rw_offset` are the ones
+    /// looked up by this gadget.
+    pub(crate) fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        call: &Call,
+        step: &ExecStep,
+        rw_offset: usize,
+    ) -> Result<(), Error> {
+        let rw_indices = &step.rw_indices[rw_offset..];
+        let [tx_id, is_static, depth] = [rw_indices[0], rw_indices[3], rw_indices[4]]
+            .map(|idx| block.rws[idx].call_context_value());
+        let [gas, callee_address, value, cd_offset, cd_length, rd_offset, rd_length] = [
+            rw_indices[5],
+            rw_indices[6],
+            rw_indices[7],
+            rw_indices[8],
+            rw_indices[9],
+            rw_indices[10],
+            rw_indices[11],
+        ]
+        .map(|idx| block.rws[idx].stack_value());
+        let (_, is_warm_prev) = block.rws[rw_indices[13]].tx_access_list_value_pair();
+        let [callee_nonce, callee_balance, callee_code_hash] =
+            [rw_indices[14], rw_indices[15], rw_indices[16]]
+                .map(|idx| block.rws[idx].account_value_pair().0);
+
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id.low_u64())))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.is_static
+            .assign(region, offset, Some(F::from(is_static.low_u64())))?;
+        self.depth
+            .assign(region, offset, Some(F::from(depth.low_u64())))?;
+
+        self.gas.assign(region, offset, Some(gas.to_le_bytes()))?;
+        self.callee_address
+            .assign(region, offset, Some(callee_address.to_le_bytes()))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        self.is_warm_prev
+            .assign(region, offset, Some(F::from(is_warm_prev as u64)))?;
+        let cd_address =
+            self.cd_address
+                .assign(region, offset, cd_offset, cd_length, block.randomness)?;
+        let rd_address =
+            self.rd_address
+                .assign(region, offset, rd_offset, rd_length, block.randomness)?;
+        self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [cd_address, rd_address],
+        )?;
+
+        self.callee_nonce
+            .assign(region, offset, callee_nonce.to_scalar())?;
+        self.callee_balance
+            .assign(region, offset, Some(callee_balance.to_le_bytes()))?;
+        let callee_code_hash =
+            Word::random_linear_combine(callee_code_hash.to_le_bytes(), block.randomness);
+        self.callee_code_hash
+            .assign(region, offset, Some(callee_code_hash))?;
+        self.is_account_empty.assign(
+            region,
+            offset,
+            F::from(callee_nonce.low_u64()),
+            Word::random_linear_combine(callee_balance.to_le_bytes(), block.randomness),
+            callee_code_hash,
+            block.randomness,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        test::run_test_circuit_incomplete_fixed_table,
+        witness::{single_step_block, Block, Call, CodeSource, ExecStep, Rw},
+    };
+    use eth_types::{evm_types::OpcodeId, Word};
+    use halo2_proofs::{dev::VerifyFailure, pairing::bn256::Fr};
+
+    /// A block with a PC step followed by a STOP step, where PC relies on
+    /// `SameContextGadget` to constrain the transition to the next step.
+    fn same_context_block() -> Block<Fr> {
+        single_step_block(
+            ExecutionState::PC,
+            OpcodeId::PC,
+            vec![Rw::Stack {
+                rw_counter: 1,
+                is_write: true,
+                call_id: 1,
+                stack_pointer: 1023,
+                value: Word::zero(),
+            }],
+        )
+    }
+
+    fn assert_transition_rejected(block: Block<Fr>, field: &str) {
+        let name = format!("State transition constraint of {}", field);
+        let errors = run_test_circuit_incomplete_fixed_table(block).unwrap_err();
+        assert!(
+            errors.iter().any(|error| match error {
+                VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+                    format!("{}", constraint).contains(&name)
+                }
+                _ => false,
+            }),
+            "{} is not enforced: {:#?}",
+            field,
+            errors
+        );
+    }
+
+    /// Assert that the transition is rejected when `mutate` changes the state
+    /// of the next step.
+    fn assert_next_step_rejected(field: &str, mutate: impl FnOnce(&mut ExecStep)) {
+        let mut block = same_context_block();
+        mutate(&mut block.txs[0].steps[1]);
+        assert_transition_rejected(block, field);
+    }
+
+    /// Assert that the transition is rejected when the next step switches to
+    /// a copy of the call modified by `mutate`.
+    fn assert_next_call_rejected(field: &str, mutate: impl FnOnce(&mut Call)) {
+        let mut block = same_context_block();
+        let mut call = block.txs[0].calls[0].clone();
+        mutate(&mut call);
+        block.txs[0].calls.push(call);
+        block.txs[0].steps[1].call_index = 1;
+        assert_transition_rejected(block, field);
+    }
+
+    #[test]
+    fn same_context_gadget_valid_transition() {
+        assert_eq!(
+            run_test_circuit_incomplete_fixed_table(same_context_block()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn same_context_gadget_rejects_wrong_step_state() {
+        assert_next_step_rejected("rw_counter", |step| step.rw_counter += 1);
+        assert_next_step_rejected("program_counter", |step| step.program_counter += 1);
+        assert_next_step_rejected("stack_pointer", |step| step.stack_pointer -= 1);
+        assert_next_step_rejected("gas_left", |step| step.gas_left -= 1);
+        assert_next_step_rejected("memory_word_size", |step| step.memory_size = 32);
+        assert_next_step_rejected("reversible_write_counter", |step| {
+            step.reversible_write_counter += 1
+        });
+        assert_next_step_rejected("log_id", |step| step.log_id += 1);
+    }
+
+    #[test]
+    fn same_context_gadget_rejects_wrong_call() {
+        assert_next_call_rejected("call_id", |call| call.id += 1);
+        assert_next_call_rejected("is_root", |call| call.is_root = false);
+        assert_next_call_rejected("is_create", |call| call.is_create = true);
+        assert_next_call_rejected("code_hash", |call| {
+            call.code_source = CodeSource::Account(Word::from(0xcafe))
+        });
+    }
+}
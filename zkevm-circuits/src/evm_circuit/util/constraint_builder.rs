@@ -19,7 +19,7 @@ use halo2_proofs::{
 };
 use std::convert::TryInto;
 
-use super::{rlc, CachedRegion, CellType, StoredExpression};
+use super::{from_bytes, rlc, CachedRegion, CellType, StoredExpression};
 
 // Max degree allowed in all expressions passing through the ConstraintBuilder.
 // It aims to cap `extended_k` to 2, which allows constraint degree to 2^2+1,
@@ -243,7 +243,7 @@ pub(crate) struct ConstraintBuilder<'a, F> {
     pub max_degree: usize,
     pub(crate) curr: Step<F>,
     pub(crate) next: Step<F>,
-    power_of_randomness: &'a [Expression<F>; 31],
+    power_of_randomness: &'a [Expression<F>],
     execution_state: ExecutionState,
     constraints: Vec<(&'static str, Expression<F>)>,
     constraints_first_step: Vec<(&'static str, Expression<F>)>,
@@ -254,13 +254,16 @@ pub(crate) struct ConstraintBuilder<'a, F> {
     in_next_step: bool,
     condition: Option<Expression<F>>,
     stored_expressions: Vec<StoredExpression<F>>,
+    fixed_table_tags: Vec<FixedTableTag>,
+    rw_counter_deltas: Vec<Expression<F>>,
+    is_stub: bool,
 }
 
 impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
     pub(crate) fn new(
         curr: Step<F>,
         next: Step<F>,
-        power_of_randomness: &'a [Expression<F>; 31],
+        power_of_randomness: &'a [Expression<F>],
         execution_state: ExecutionState,
     ) -> Self {
         Self {
@@ -278,6 +281,9 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             in_next_step: false,
             condition: None,
             stored_expressions: Vec::new(),
+            fixed_table_tags: Vec::new(),
+            rw_counter_deltas: Vec::new(),
+            is_stub: false,
         }
     }
 
@@ -313,6 +319,27 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         self.execution_state
     }
 
+    /// Fixed table tags looked up by the execution state so far
+    pub(crate) fn fixed_table_tags(&self) -> &[FixedTableTag] {
+        &self.fixed_table_tags
+    }
+
+    /// The rw_counter deltas declared by the step state transitions so far,
+    /// with a `Same` transition declared as a delta of 0
+    pub(crate) fn rw_counter_deltas(&self) -> &[Expression<F>] {
+        &self.rw_counter_deltas
+    }
+
+    /// Mark the constraints of the execution state as known to be
+    /// incomplete, so the circuit isn't sound for its steps yet.
+    pub(crate) fn stub(&mut self) {
+        self.is_stub = true;
+    }
+
+    pub(crate) fn is_stub(&self) -> bool {
+        self.is_stub
+    }
+
     pub(crate) fn rw_counter_offset(&self) -> Expression<F> {
         self.rw_counter_offset.clone()
     }
@@ -363,6 +390,13 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         self.query_cells(CellType::Lookup(Table::Byte), count)
     }
 
+    /// Query `N` byte cells, each range checked by the byte lookup, and return
+    /// the value they compose in little-endian along with them.
+    pub(crate) fn bytes_to_value<const N: usize>(&mut self) -> (Expression<F>, [Cell<F>; N]) {
+        let bytes = self.query_bytes();
+        (from_bytes::expr(&bytes), bytes)
+    }
+
     pub(crate) fn query_cell(&mut self) -> Cell<F> {
         self.query_cell_with_type(CellType::Storage)
     }
@@ -425,6 +459,12 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         &mut self,
         step_state_transition: StepStateTransition<F>,
     ) {
+        match &step_state_transition.rw_counter {
+            Transition::Same => self.rw_counter_deltas.push(0.expr()),
+            Transition::Delta(delta) => self.rw_counter_deltas.push(delta.clone()),
+            _ => {}
+        }
+
         macro_rules! constrain {
             ($name:tt) => {
                 match step_state_transition.$name {
@@ -463,6 +503,17 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
 
     // Fixed
 
+    /// Record `tags` as looked up by the execution state, so the fixed tables
+    /// a block needs can be derived from its steps. It must be called along
+    /// every [`Lookup::Fixed`].
+    pub(crate) fn use_fixed_table_tags(&mut self, tags: impl IntoIterator<Item = FixedTableTag>) {
+        for tag in tags {
+            if !self.fixed_table_tags.contains(&tag) {
+                self.fixed_table_tags.push(tag);
+            }
+        }
+    }
+
     pub(crate) fn range_lookup(&mut self, value: Expression<F>, range: u64) {
         let (name, tag) = match range {
             5 => ("Range5", FixedTableTag::Range5),
@@ -474,6 +525,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             1024 => ("Range1024", FixedTableTag::Range1024),
             _ => unimplemented!(),
         };
+        self.use_fixed_table_tags([tag]);
         self.add_lookup(
             name,
             Lookup::Fixed {
@@ -518,6 +570,19 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    /// Verify the opcode is one of the responsible opcodes of the current
+    /// execution state.
+    pub(crate) fn responsible_opcode_lookup(&mut self, opcode: Expression<F>) {
+        self.use_fixed_table_tags([FixedTableTag::ResponsibleOpcode]);
+        self.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [self.execution_state.as_u64().expr(), opcode, 0.expr()],
+            },
+        );
+    }
+
     // Bytecode table
 
     pub(crate) fn bytecode_lookup(
@@ -613,6 +678,28 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    // ecrecover
+
+    pub(crate) fn ecrecover_lookup(
+        &mut self,
+        msg_hash: Expression<F>,
+        v: Expression<F>,
+        r: Expression<F>,
+        s: Expression<F>,
+        recovered_address: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Ecrecover lookup",
+            Lookup::Ecrecover {
+                msg_hash,
+                v,
+                r,
+                s,
+                recovered_address,
+            },
+        );
+    }
+
     // Rw
 
     /// Add a Lookup::Rw without increasing the rw_counter_offset, which is
@@ -860,13 +947,13 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             false.expr(),
             RwTableTag::AccountStorage,
             [
-                tx_id,
+                0.expr(),
                 account_address,
                 0.expr(),
                 key,
                 value.clone(),
                 value,
-                0.expr(),
+                tx_id,
                 committed_value,
             ],
         );
@@ -887,13 +974,13 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             "AccountStorage write",
             RwTableTag::AccountStorage,
             [
-                tx_id,
+                0.expr(),
                 account_address,
                 0.expr(),
                 key,
                 value,
                 value_prev,
-                0.expr(),
+                tx_id,
                 committed_value,
             ],
             reversion_info,
@@ -953,8 +1040,43 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         }
     }
 
+    /// Lookup the `IsSuccess` of the callee `callee_call_id` as `is_success`,
+    /// which is the flag pushed onto the caller's stack when the callee
+    /// returns. The callee is persistent only when both the caller is
+    /// persistent and the callee succeeds, so a callee which reverts pushes
+    /// `0` while the caller continues.
+    pub(crate) fn callee_is_success(
+        &mut self,
+        callee_call_id: Expression<F>,
+        is_success: Expression<F>,
+        reversion_info: &ReversionInfo<F>,
+        callee_reversion_info: &ReversionInfo<F>,
+    ) {
+        self.call_context_lookup(
+            false.expr(),
+            Some(callee_call_id),
+            CallContextFieldTag::IsSuccess,
+            is_success.clone(),
+        );
+        self.require_equal(
+            "callee_is_persistent == is_persistent ⋅ is_success",
+            callee_reversion_info.is_persistent(),
+            reversion_info.is_persistent() * is_success,
+        );
+    }
+
     // Stack
 
+    /// Constrain the stack to have at least `height` items, for the steps
+    /// which peek deeper than what they pop, where a shallower stack is a
+    /// stack underflow.
+    pub(crate) fn require_stack_height(&mut self, height: Expression<F>) {
+        self.range_lookup(
+            STACK_CAPACITY.expr() - self.curr.state.stack_pointer.expr() - height,
+            STACK_CAPACITY as u64,
+        );
+    }
+
     pub(crate) fn stack_pop(&mut self, value: Expression<F>) {
         self.stack_lookup(false.expr(), self.stack_pointer_offset.expr(), value);
         self.stack_pointer_offset += 1;
@@ -1042,6 +1164,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
     pub(crate) fn tx_log_lookup(
         &mut self,
         tx_id: Expression<F>,
+        log_id: Expression<F>,
         tag: TxLogFieldTag,
         index: Expression<F>,
         value: Expression<F>,
@@ -1052,7 +1175,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             RwTableTag::TxLog,
             [
                 tx_id,
-                index + (1u64 << 8).expr() * self.curr.state.log_id.expr(),
+                index + (1u64 << 8).expr() * log_id,
                 tag.expr(),
                 0.expr(),
                 value,
@@ -6,6 +6,7 @@ use crate::{
     },
     util::Expr,
 };
+use array_init::array_init;
 use eth_types::{Field, ToLittleEndian, ToScalar, Word};
 use halo2_proofs::plonk::{Error, Expression};
 use std::convert::TryFrom;
@@ -55,6 +56,125 @@ impl<F: Field> IsZeroGadget<F> {
     }
 }
 
+/// Returns `1` when the 256-bit `word == 0`, and returns `0` otherwise.
+/// The check is on the sum of the bytes instead of the RLC of the word: the
+/// bytes of a `Word` are range checked, so their sum is at most `32 * 255`,
+/// which can't wrap around and is 0 iff all the bytes are 0. The RLC is 0 for
+/// nonzero bytes only with negligible probability over the randomness, and
+/// says nothing at all about a value that isn't decomposed into range checked
+/// bytes, so it isn't relied on.
+#[derive(Clone, Debug)]
+pub struct WordIsZeroGadget<F> {
+    is_zero: IsZeroGadget<F>,
+}
+
+impl<F: Field> WordIsZeroGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, word: &util::Word<F>) -> Self {
+        Self {
+            is_zero: IsZeroGadget::construct(cb, sum::expr(&word.cells)),
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        word: Word,
+    ) -> Result<F, Error> {
+        self.is_zero
+            .assign(region, offset, sum::value(&word.to_le_bytes()))
+    }
+}
+
+/// Returns the number of bytes of a 256-bit `word` without its leading zero
+/// bytes, i.e. the index of its most significant nonzero byte plus 1, or 0
+/// when `word == 0`. It's used for length dependent gas like the
+/// `50 * byte_size(exponent)` of EXP.
+#[derive(Clone, Debug)]
+pub struct ByteSizeGadget<F> {
+    /// Exactly one of them is 1, at the index of the byte size
+    byte_size_selectors: [Cell<F>; 33],
+    /// Inverse of the most significant nonzero byte, or 0 when `word == 0`
+    most_significant_nonzero_byte_inverse: Cell<F>,
+}
+
+impl<F: Field> ByteSizeGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, word: &util::Word<F>) -> Self {
+        let byte_size_selectors = array_init(|_| cb.query_bool());
+        let most_significant_nonzero_byte_inverse = cb.query_cell();
+
+        cb.require_equal(
+            "Exactly one byte size is selected",
+            sum::expr(&byte_size_selectors),
+            1.expr(),
+        );
+        // The bytes at and above the byte size are all 0
+        for (idx, byte) in word.cells.iter().enumerate() {
+            cb.require_zero(
+                "Byte above the byte size is 0",
+                sum::expr(&byte_size_selectors[..=idx]) * byte.expr(),
+            );
+        }
+        // The byte right below the byte size is nonzero, unless the byte size
+        // is 0
+        cb.require_equal(
+            "Most significant nonzero byte ⋅ inverse is 1",
+            sum::expr(
+                byte_size_selectors[1..]
+                    .iter()
+                    .zip(word.cells.iter())
+                    .map(|(selector, byte)| selector.expr() * byte.expr()),
+            ) * most_significant_nonzero_byte_inverse.expr(),
+            1.expr() - byte_size_selectors[0].expr(),
+        );
+
+        Self {
+            byte_size_selectors,
+            most_significant_nonzero_byte_inverse,
+        }
+    }
+
+    pub(crate) fn byte_size(&self) -> Expression<F> {
+        sum::expr(
+            self.byte_size_selectors
+                .iter()
+                .enumerate()
+                .map(|(idx, selector)| idx.expr() * selector.expr()),
+        )
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        word: Word,
+    ) -> Result<usize, Error> {
+        let byte_size = (word.bits() + 7) / 8;
+        for (idx, selector) in self.byte_size_selectors.iter().enumerate() {
+            selector.assign(region, offset, Some(F::from((idx == byte_size) as u64)))?;
+        }
+        let most_significant_nonzero_byte = if byte_size == 0 {
+            0
+        } else {
+            word.byte(byte_size - 1)
+        };
+        self.most_significant_nonzero_byte_inverse.assign(
+            region,
+            offset,
+            Some(
+                F::from(most_significant_nonzero_byte as u64)
+                    .invert()
+                    .unwrap_or(F::zero()),
+            ),
+        )?;
+        Ok(byte_size)
+    }
+}
+
 /// Returns `1` when `lhs == rhs`, and returns `0` otherwise.
 #[derive(Clone, Debug)]
 pub struct IsEqualGadget<F> {
@@ -896,3 +1016,549 @@ impl<F: Field> MulAddWordsGadget<F> {
         self.overflow.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evm_circuit::{
+        param::{MAX_STEP_HEIGHT, STEP_WIDTH},
+        step::{ExecutionState, Step},
+        table::Table,
+        util::CellType,
+    };
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pairing::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Fixed, Selector},
+        poly::Rotation,
+    };
+    use std::marker::PhantomData;
+
+    /// A gadget under test along with the cells of its inputs and expected
+    /// outputs, which are assigned from the witnesses of [`UnitTestCircuit`].
+    trait MathGadgetContainer: Clone {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self;
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error>;
+    }
+
+    #[derive(Clone)]
+    struct UnitTestConfig<G> {
+        q_usable: Selector,
+        advices: [Column<Advice>; STEP_WIDTH],
+        byte_table: Column<Fixed>,
+        step: Step<Fr>,
+        container: G,
+    }
+
+    /// Circuit of a single step which checks the constraints of the gadget
+    /// container `G` on `witnesses`.
+    struct UnitTestCircuit<G> {
+        witnesses: Vec<Word>,
+        _marker: PhantomData<G>,
+    }
+
+    impl<G: MathGadgetContainer> Circuit<Fr> for UnitTestCircuit<G> {
+        type Config = UnitTestConfig<G>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                witnesses: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let q_usable = meta.selector();
+            let advices = [(); STEP_WIDTH].map(|_| meta.advice_column());
+            let byte_table = meta.fixed_column();
+
+            let step = Step::new(meta, advices, 0);
+            let power_of_randomness = [(); 31].map(|_| 0.expr());
+            let mut cb = ConstraintBuilder::new(
+                step.clone(),
+                step.clone(),
+                &power_of_randomness,
+                ExecutionState::EXP,
+            );
+            let container = G::configure_gadget_container(&mut cb);
+            let (constraints, _, stored_expressions, _) = cb.build();
+            assert!(stored_expressions.is_empty());
+
+            meta.create_gate("MathGadgetContainer", |meta| {
+                let q_usable = meta.query_selector(q_usable);
+                constraints
+                    .into_iter()
+                    .map(move |(name, constraint)| (name, q_usable.clone() * constraint))
+            });
+            for column in step.cell_manager.columns() {
+                if column.cell_type == CellType::Lookup(Table::Byte) {
+                    meta.lookup_any("Byte", |meta| {
+                        vec![(column.expr(), meta.query_fixed(byte_table, Rotation::cur()))]
+                    });
+                }
+            }
+
+            UnitTestConfig {
+                q_usable,
+                advices,
+                byte_table,
+                step,
+                container,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "byte table",
+                |mut region| {
+                    for byte in 0..256 {
+                        region.assign_fixed(
+                            || "byte",
+                            config.byte_table,
+                            byte,
+                            || Ok(Fr::from(byte as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "step",
+                |mut region| {
+                    config.q_usable.enable(&mut region, 0)?;
+                    let region = &mut CachedRegion::new(
+                        &mut region,
+                        &[],
+                        STEP_WIDTH,
+                        MAX_STEP_HEIGHT,
+                        config.advices[0].index(),
+                        0,
+                    );
+                    config.step.state.execution_state[ExecutionState::EXP as usize].assign(
+                        region,
+                        0,
+                        Some(Fr::one()),
+                    )?;
+                    config
+                        .container
+                        .assign_gadget_container(region, &self.witnesses)
+                },
+            )
+        }
+    }
+
+    fn verify<G: MathGadgetContainer>(witnesses: Vec<Word>) -> bool {
+        let circuit = UnitTestCircuit::<G> {
+            witnesses,
+            _marker: PhantomData,
+        };
+        MockProver::<Fr>::run(9, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    /// Constrains `byte_size` to be the byte size of `word`.
+    #[derive(Clone)]
+    struct ByteSizeGadgetContainer {
+        word: util::Word<Fr>,
+        byte_size: Cell<Fr>,
+        gadget: ByteSizeGadget<Fr>,
+    }
+
+    impl MathGadgetContainer for ByteSizeGadgetContainer {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self {
+            let word = cb.query_word();
+            let gadget = ByteSizeGadget::construct(cb, &word);
+            let byte_size = cb.query_cell();
+            cb.require_equal(
+                "byte_size is the byte size of word",
+                byte_size.expr(),
+                gadget.byte_size(),
+            );
+            Self {
+                word,
+                byte_size,
+                gadget,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error> {
+            let [word, byte_size] = [witnesses[0], witnesses[1]];
+            self.word.assign(region, 0, Some(word.to_le_bytes()))?;
+            self.gadget.assign(region, 0, word)?;
+            self.byte_size
+                .assign(region, 0, Some(Fr::from(byte_size.as_u64())))?;
+            Ok(())
+        }
+    }
+
+    fn verify_byte_size(word: Word, byte_size: u64) -> bool {
+        verify::<ByteSizeGadgetContainer>(vec![word, Word::from(byte_size)])
+    }
+
+    /// Constrains `eq` to be whether `lhs == rhs`, for field elements.
+    #[derive(Clone)]
+    struct IsEqualGadgetContainer {
+        lhs: Cell<Fr>,
+        rhs: Cell<Fr>,
+        eq: Cell<Fr>,
+        gadget: IsEqualGadget<Fr>,
+    }
+
+    impl MathGadgetContainer for IsEqualGadgetContainer {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self {
+            let lhs = cb.query_cell();
+            let rhs = cb.query_cell();
+            let gadget = IsEqualGadget::construct(cb, lhs.expr(), rhs.expr());
+            let eq = cb.query_cell();
+            cb.require_equal("eq is whether lhs == rhs", eq.expr(), gadget.expr());
+            Self {
+                lhs,
+                rhs,
+                eq,
+                gadget,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error> {
+            let [lhs, rhs, eq] = [0, 1, 2].map(|idx| witnesses[idx].to_scalar().unwrap());
+            self.lhs.assign(region, 0, Some(lhs))?;
+            self.rhs.assign(region, 0, Some(rhs))?;
+            self.gadget.assign(region, 0, lhs, rhs)?;
+            self.eq.assign(region, 0, Some(eq))?;
+            Ok(())
+        }
+    }
+
+    fn verify_is_equal(lhs: Word, rhs: Word, eq: bool) -> bool {
+        verify::<IsEqualGadgetContainer>(vec![lhs, rhs, Word::from(eq as u64)])
+    }
+
+    /// Constrains `lt` to be whether `lhs < rhs`, for 8 bytes values.
+    #[derive(Clone)]
+    struct LtGadgetContainer {
+        lhs: Cell<Fr>,
+        rhs: Cell<Fr>,
+        lt: Cell<Fr>,
+        gadget: LtGadget<Fr, 8>,
+    }
+
+    impl MathGadgetContainer for LtGadgetContainer {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self {
+            let lhs = cb.query_cell();
+            let rhs = cb.query_cell();
+            let gadget = LtGadget::construct(cb, lhs.expr(), rhs.expr());
+            let lt = cb.query_cell();
+            cb.require_equal("lt is whether lhs < rhs", lt.expr(), gadget.expr());
+            Self {
+                lhs,
+                rhs,
+                lt,
+                gadget,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error> {
+            let [lhs, rhs, lt] = [0, 1, 2].map(|idx| Fr::from(witnesses[idx].as_u64()));
+            self.lhs.assign(region, 0, Some(lhs))?;
+            self.rhs.assign(region, 0, Some(rhs))?;
+            self.gadget.assign(region, 0, lhs, rhs)?;
+            self.lt.assign(region, 0, Some(lt))?;
+            Ok(())
+        }
+    }
+
+    fn verify_lt(lhs: u64, rhs: u64, lt: bool) -> bool {
+        verify::<LtGadgetContainer>(vec![
+            Word::from(lhs),
+            Word::from(rhs),
+            Word::from(lt as u64),
+        ])
+    }
+
+    /// Constrains `lt` to be whether `lhs < rhs`, for words.
+    #[derive(Clone)]
+    struct LtWordGadgetContainer {
+        lhs: util::Word<Fr>,
+        rhs: util::Word<Fr>,
+        lt: Cell<Fr>,
+        gadget: LtWordGadget<Fr>,
+    }
+
+    impl MathGadgetContainer for LtWordGadgetContainer {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self {
+            let lhs = cb.query_word();
+            let rhs = cb.query_word();
+            let gadget = LtWordGadget::construct(cb, &lhs, &rhs);
+            let lt = cb.query_cell();
+            cb.require_equal("lt is whether lhs < rhs", lt.expr(), gadget.expr());
+            Self {
+                lhs,
+                rhs,
+                lt,
+                gadget,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error> {
+            let [lhs, rhs, lt] = [witnesses[0], witnesses[1], witnesses[2]];
+            self.lhs.assign(region, 0, Some(lhs.to_le_bytes()))?;
+            self.rhs.assign(region, 0, Some(rhs.to_le_bytes()))?;
+            self.gadget.assign(region, 0, lhs, rhs)?;
+            self.lt.assign(region, 0, Some(Fr::from(lt.as_u64())))?;
+            Ok(())
+        }
+    }
+
+    fn verify_lt_word(lhs: Word, rhs: Word, lt: bool) -> bool {
+        verify::<LtWordGadgetContainer>(vec![lhs, rhs, Word::from(lt as u64)])
+    }
+
+    /// Constrains `is_a` to be whether `value == a`, with `value` either `a`
+    /// or `b`.
+    #[derive(Clone)]
+    struct PairSelectGadgetContainer {
+        value: Cell<Fr>,
+        a: Cell<Fr>,
+        b: Cell<Fr>,
+        is_a: Cell<Fr>,
+        gadget: PairSelectGadget<Fr>,
+    }
+
+    impl MathGadgetContainer for PairSelectGadgetContainer {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self {
+            let value = cb.query_cell();
+            let a = cb.query_cell();
+            let b = cb.query_cell();
+            let gadget = PairSelectGadget::construct(cb, value.expr(), a.expr(), b.expr());
+            let is_a = cb.query_cell();
+            let (gadget_is_a, gadget_is_b) = gadget.expr();
+            cb.require_equal("is_a is whether value == a", is_a.expr(), gadget_is_a);
+            cb.require_equal(
+                "is_b is whether value != a",
+                1.expr() - is_a.expr(),
+                gadget_is_b,
+            );
+            Self {
+                value,
+                a,
+                b,
+                is_a,
+                gadget,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error> {
+            let [value, a, b, is_a] = [0, 1, 2, 3].map(|idx| Fr::from(witnesses[idx].as_u64()));
+            self.value.assign(region, 0, Some(value))?;
+            self.a.assign(region, 0, Some(a))?;
+            self.b.assign(region, 0, Some(b))?;
+            self.gadget.assign(region, 0, value, a, b)?;
+            self.is_a.assign(region, 0, Some(is_a))?;
+            Ok(())
+        }
+    }
+
+    fn verify_pair_select(value: u64, a: u64, b: u64, is_a: bool) -> bool {
+        verify::<PairSelectGadgetContainer>(
+            [value, a, b, is_a as u64]
+                .iter()
+                .copied()
+                .map(Word::from)
+                .collect(),
+        )
+    }
+
+    /// Constrains `value` to be the value composed by the byte cells of
+    /// [`ConstraintBuilder::bytes_to_value`].
+    #[derive(Clone)]
+    struct BytesToValueContainer {
+        bytes: [Cell<Fr>; 8],
+        value: Cell<Fr>,
+    }
+
+    impl MathGadgetContainer for BytesToValueContainer {
+        fn configure_gadget_container(cb: &mut ConstraintBuilder<Fr>) -> Self {
+            let (value_expr, bytes) = cb.bytes_to_value();
+            let value = cb.query_cell();
+            cb.require_equal("value is composed by the bytes", value.expr(), value_expr);
+            Self { bytes, value }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            region: &mut CachedRegion<'_, '_, Fr>,
+            witnesses: &[Word],
+        ) -> Result<(), Error> {
+            for (byte, witness) in self.bytes.iter().zip(witnesses) {
+                byte.assign(region, 0, Some(Fr::from(witness.as_u64())))?;
+            }
+            self.value
+                .assign(region, 0, Some(Fr::from(witnesses[8].as_u64())))?;
+            Ok(())
+        }
+    }
+
+    /// The bytes are assigned as they are, so they can be out of the byte
+    /// range.
+    fn verify_bytes_to_value(bytes: [u64; 8], value: u64) -> bool {
+        verify::<BytesToValueContainer>(
+            bytes
+                .iter()
+                .chain(std::iter::once(&value))
+                .copied()
+                .map(Word::from)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn bytes_to_value_little_endian() {
+        let value = 0x0102030405060708u64;
+        assert!(verify_bytes_to_value([8, 7, 6, 5, 4, 3, 2, 1], value));
+        assert!(!verify_bytes_to_value([1, 2, 3, 4, 5, 6, 7, 8], value));
+        assert!(verify_bytes_to_value([0xff; 8], u64::MAX));
+    }
+
+    #[test]
+    fn bytes_to_value_byte_out_of_range() {
+        // 0x100 composes the same value as bytes [0, 1], but isn't a byte
+        assert!(verify_bytes_to_value([0, 1, 0, 0, 0, 0, 0, 0], 0x100));
+        assert!(!verify_bytes_to_value([0x100, 0, 0, 0, 0, 0, 0, 0], 0x100));
+    }
+
+    #[test]
+    fn byte_size_gadget_zero() {
+        assert!(verify_byte_size(Word::zero(), 0));
+        assert!(!verify_byte_size(Word::zero(), 1));
+    }
+
+    #[test]
+    fn byte_size_gadget_one_byte() {
+        assert!(verify_byte_size(Word::one(), 1));
+        assert!(verify_byte_size(Word::from(0xff), 1));
+        assert!(!verify_byte_size(Word::from(0xff), 0));
+        assert!(!verify_byte_size(Word::from(0xff), 2));
+        assert!(verify_byte_size(Word::from(0x100), 2));
+    }
+
+    #[test]
+    fn byte_size_gadget_32_bytes() {
+        assert!(verify_byte_size(Word::MAX, 32));
+        assert!(verify_byte_size(Word::one() << 255, 32));
+        assert!(verify_byte_size(Word::one() << 248, 32));
+        assert!(verify_byte_size((Word::one() << 248) - 1, 31));
+        assert!(!verify_byte_size(Word::one() << 255, 31));
+    }
+
+    #[test]
+    fn lt_gadget_equal() {
+        assert!(verify_lt(0, 0, false));
+        assert!(verify_lt(5, 5, false));
+        assert!(verify_lt(u64::MAX, u64::MAX, false));
+        assert!(!verify_lt(5, 5, true));
+    }
+
+    #[test]
+    fn lt_gadget_off_by_one() {
+        assert!(verify_lt(4, 5, true));
+        assert!(!verify_lt(4, 5, false));
+        assert!(verify_lt(6, 5, false));
+        assert!(!verify_lt(6, 5, true));
+        assert!(verify_lt(u64::MAX - 1, u64::MAX, true));
+        assert!(verify_lt(u64::MAX, u64::MAX - 1, false));
+        assert!(verify_lt(0, u64::MAX, true));
+    }
+
+    #[test]
+    fn lt_word_gadget_equal() {
+        assert!(verify_lt_word(Word::zero(), Word::zero(), false));
+        assert!(verify_lt_word(Word::MAX, Word::MAX, false));
+        assert!(!verify_lt_word(Word::MAX, Word::MAX, true));
+    }
+
+    #[test]
+    fn lt_word_gadget_off_by_one() {
+        // Differ in the low 128 bits
+        assert!(verify_lt_word(Word::from(4), Word::from(5), true));
+        assert!(!verify_lt_word(Word::from(4), Word::from(5), false));
+        assert!(verify_lt_word(Word::from(6), Word::from(5), false));
+        assert!(verify_lt_word(Word::MAX - 1, Word::MAX, true));
+        assert!(verify_lt_word(Word::MAX, Word::MAX - 1, false));
+        // Differ in the high 128 bits
+        let hi = Word::one() << 128;
+        assert!(verify_lt_word(hi - 1, hi, true));
+        assert!(!verify_lt_word(hi - 1, hi, false));
+        assert!(verify_lt_word(hi, hi - 1, false));
+        assert!(!verify_lt_word(hi, hi - 1, true));
+    }
+
+    #[test]
+    fn pair_select_gadget_both_branches() {
+        assert!(verify_pair_select(3, 3, 7, true));
+        assert!(!verify_pair_select(3, 3, 7, false));
+        assert!(verify_pair_select(7, 3, 7, false));
+        assert!(!verify_pair_select(7, 3, 7, true));
+        // The value must be one of the pair
+        assert!(!verify_pair_select(5, 3, 7, true));
+        assert!(!verify_pair_select(5, 3, 7, false));
+    }
+
+    #[test]
+    fn is_equal_gadget_equal() {
+        assert!(verify_is_equal(Word::zero(), Word::zero(), true));
+        assert!(verify_is_equal(Word::from(42), Word::from(42), true));
+        assert!(!verify_is_equal(Word::from(42), Word::from(42), false));
+    }
+
+    #[test]
+    fn is_equal_gadget_not_equal() {
+        assert!(verify_is_equal(Word::from(42), Word::from(43), false));
+        assert!(!verify_is_equal(Word::from(42), Word::from(43), true));
+        assert!(verify_is_equal(Word::from(43), Word::from(42), false));
+        assert!(!verify_is_equal(Word::from(43), Word::from(42), true));
+    }
+
+    #[test]
+    fn is_equal_gadget_large_difference() {
+        // `lhs - rhs` wraps around to `-1`, the largest field element
+        assert!(verify_is_equal(Word::zero(), Word::one(), false));
+        assert!(!verify_is_equal(Word::zero(), Word::one(), true));
+        let large = Word::one() << 250;
+        assert!(verify_is_equal(large, Word::zero(), false));
+        assert!(!verify_is_equal(large, Word::zero(), true));
+        assert!(verify_is_equal(large, large, true));
+    }
+}
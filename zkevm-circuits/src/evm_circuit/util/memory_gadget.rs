@@ -190,6 +190,8 @@ impl<F: Field> MemoryWordSizeGadget<F> {
 /// This gas cost is the difference between the next and current memory costs:
 /// `memory_cost = Gmem * memory_word_size + floor(memory_word_size *
 /// memory_word_size / 512)`
+/// A step accessing `N` ranges, like CALL with its args and return regions,
+/// expands the memory once to the largest of their end addresses.
 #[derive(Clone, Debug)]
 pub(crate) struct MemoryExpansionGadget<F, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize> {
     memory_word_sizes: [MemoryWordSizeGadget<F>; N],
@@ -12,17 +12,23 @@ use crate::evm_circuit::{
 use bus_mapping::{
     circuit_input_builder::{self, StepAuxiliaryData},
     error::{ExecError, OogError},
-    operation::{self, AccountField, CallContextField, TxReceiptField},
+    operation::{self, AccountField, CallContextField, TxLogField, TxReceiptField},
 };
 
-use eth_types::evm_types::OpcodeId;
+use eth_types::evm_types::{gas_utils::memory_expansion_gas_cost, HardFork, OpcodeId};
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, ToWord, Word};
 use eth_types::{ToAddress, U256};
 use halo2_proofs::arithmetic::{BaseExt, FieldExt};
 use halo2_proofs::pairing::bn256::Fr;
 use itertools::Itertools;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
 use sha3::{Digest, Keccak256};
-use std::{collections::HashMap, convert::TryInto, iter};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryInto,
+    iter,
+};
 
 #[derive(Debug, Default, Clone)]
 pub struct Block<F> {
@@ -36,6 +42,481 @@ pub struct Block<F> {
     pub bytecodes: Vec<Bytecode>,
     /// The block context
     pub context: BlockContext,
+    /// Calls to the ecrecover precompile in the block
+    pub ecrecovers: Vec<Ecrecover>,
+    /// Number of rows the EvmCircuit can assign steps to, where 0 means
+    /// unlimited
+    pub max_evm_rows: usize,
+    /// Hard fork of the block, which the fork dependent gadgets follow
+    pub fork: HardFork,
+}
+
+impl<F: Field> Block<F> {
+    /// Accounts in the access list from the start of each transaction, besides
+    /// its caller and callee, which have no rw of their own: the precompiles
+    /// and the coinbase from Shanghai.
+    pub fn prewarmed_addresses(&self) -> Vec<Address> {
+        let mut addresses: Vec<_> = circuit_input_builder::precompile_addresses().collect();
+        if self.fork.is_coinbase_warm() {
+            addresses.push(self.context.coinbase);
+        }
+        addresses
+    }
+
+    /// Rows of the StateCircuit for the block, which are its rws plus the
+    /// seeds of the committed storage values, of the prewarmed accounts and
+    /// of the access lists of the transactions. The seeds aren't looked up by
+    /// the EvmCircuit.
+    pub fn state_circuit_rws(&self) -> RwMap {
+        let mut rws = self.rws.clone();
+        rws.insert_committed_storage_seeds();
+        rws.insert_access_list_seeds(&self.prewarmed_addresses());
+        rws.insert_tx_access_list_seeds(&self.txs);
+        rws
+    }
+
+    /// Check that the gas used reported by the block header equals the sum of
+    /// the gas consumed by each transaction, i.e. its gas limit minus the gas
+    /// left at its `EndTx` step, minus the refund read by that step capped to
+    /// a fraction of the gas consumed as `EndTxGadget` pays it back.
+    pub fn check_gas_used(&self) -> Result<(), GasUsedMismatch> {
+        let max_refund_quotient = self.fork.max_refund_quotient_of_gas_used();
+        let txs_gas_used = self
+            .txs
+            .iter()
+            .map(|tx| {
+                tx.steps
+                    .iter()
+                    .find(|step| step.execution_state == ExecutionState::EndTx)
+                    .map_or(0, |step| {
+                        let gas_used = tx.gas - step.gas_left;
+                        let refund = step
+                            .rw_indices
+                            .iter()
+                            .find(|(tag, _)| *tag == RwTableTag::TxRefund)
+                            .map_or(0, |idx| self.rws[*idx].tx_refund_value_pair().0);
+                        gas_used - refund.min(gas_used / max_refund_quotient)
+                    })
+            })
+            .sum::<u64>();
+        if txs_gas_used == self.context.gas_used {
+            Ok(())
+        } else {
+            Err(GasUsedMismatch {
+                block_gas_used: self.context.gas_used,
+                txs_gas_used,
+            })
+        }
+    }
+
+    /// Check that the refund read by the `EndTx` step of each transaction is
+    /// the sum of the refund deltas of its SSTOREs, and of their reversions
+    /// which are in the steps they revert, i.e. that the refund chained by the
+    /// TxRefund rows is the one `EndTxGadget` pays back.
+    pub fn check_tx_refunds(&self) -> Result<(), TxRefundMismatch> {
+        for tx in self.txs.iter() {
+            let end_tx_refund = tx
+                .steps
+                .iter()
+                .filter(|step| step.execution_state == ExecutionState::EndTx)
+                .flat_map(|step| step.rw_indices.iter())
+                .find(|(tag, _)| *tag == RwTableTag::TxRefund)
+                .map(|idx| self.rws[*idx].tx_refund_value_pair().0);
+            let refund = match end_tx_refund {
+                Some(refund) => refund,
+                None => continue,
+            };
+            let refund_deltas: i64 = tx
+                .steps
+                .iter()
+                .flat_map(|step| step.rw_indices.iter())
+                .filter_map(|idx| match self.rws[*idx] {
+                    Rw::TxRefund {
+                        is_write: true,
+                        value,
+                        value_prev,
+                        ..
+                    } => Some(value as i64 - value_prev as i64),
+                    _ => None,
+                })
+                .sum();
+            if refund as i64 != refund_deltas {
+                return Err(TxRefundMismatch {
+                    tx_id: tx.id,
+                    refund,
+                    refund_deltas,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that each step continuing in the same call context hands over to
+    /// the next step the state its gadget transitions to, i.e. the rw_counter
+    /// advanced by its rw lookups, the program counter past the opcode and its
+    /// pushed bytes, the stack pointer past the popped and pushed items, and
+    /// the gas left minus its gas cost. The steps with a variable number of rw
+    /// lookups, and the ones switching or failing the call context, are
+    /// skipped, and so is the program counter of JUMP and JUMPI. This rejects
+    /// an inconsistent trace before proving it.
+    pub fn check_step_transitions(&self) -> Result<(), StepTransitionMismatch> {
+        for tx in self.txs.iter() {
+            for (idx, (step, next_step)) in tx.steps.iter().tuple_windows().enumerate() {
+                let rw_counter_delta = match step.execution_state.rw_counter_deltas().as_deref() {
+                    Some([rw_counter_delta]) => *rw_counter_delta as usize,
+                    _ => continue,
+                };
+                let opcode = match step.opcode {
+                    Some(opcode) => opcode,
+                    None => continue,
+                };
+                if next_step.call_index != step.call_index
+                    || step.execution_state.halts()
+                    || matches!(
+                        step.execution_state,
+                        ExecutionState::ErrorDepth | ExecutionState::ErrorInsufficientBalance
+                    )
+                {
+                    continue;
+                }
+
+                // Operands are read from the current stack pointer upwards,
+                // and the result is written to the top of the next one
+                let stack_pointers = |is_write: bool| {
+                    step.rw_indices
+                        .iter()
+                        .filter_map(move |idx| match self.rws[*idx] {
+                            Rw::Stack {
+                                is_write: rw_is_write,
+                                stack_pointer,
+                                ..
+                            } if rw_is_write == is_write => Some(stack_pointer),
+                            _ => None,
+                        })
+                };
+                let next_stack_pointer = stack_pointers(true)
+                    .min()
+                    .or_else(|| stack_pointers(false).max().map(|sp| sp + 1))
+                    .unwrap_or(step.stack_pointer);
+                let next_program_counter = if opcode.is_push() {
+                    step.program_counter + 1 + (opcode.as_u64() - OpcodeId::PUSH1.as_u64() + 1)
+                } else {
+                    step.program_counter + 1
+                };
+
+                let mismatch = |field, expected: u64, actual: u64| {
+                    (expected != actual).then(|| StepTransitionMismatch {
+                        tx_id: tx.id,
+                        step_index: idx,
+                        field,
+                        expected,
+                        actual,
+                    })
+                };
+                let is_jump = matches!(opcode, OpcodeId::JUMP | OpcodeId::JUMPI);
+                let mismatches = [
+                    mismatch(
+                        "rw_counter",
+                        (step.rw_counter + rw_counter_delta) as u64,
+                        next_step.rw_counter as u64,
+                    ),
+                    (!is_jump)
+                        .then(|| {
+                            mismatch(
+                                "program_counter",
+                                next_program_counter,
+                                next_step.program_counter,
+                            )
+                        })
+                        .flatten(),
+                    mismatch(
+                        "stack_pointer",
+                        next_stack_pointer as u64,
+                        next_step.stack_pointer as u64,
+                    ),
+                    // A gas cost over the gas left can't be transitioned to, so
+                    // it's reported against the gas left instead.
+                    match step.gas_left.checked_sub(step.gas_cost) {
+                        Some(gas_left) => mismatch("gas_left", gas_left, next_step.gas_left),
+                        None => Some(StepTransitionMismatch {
+                            tx_id: tx.id,
+                            step_index: idx,
+                            field: "gas_cost",
+                            expected: step.gas_left,
+                            actual: step.gas_cost,
+                        }),
+                    },
+                ];
+                if let Some(mismatch) = IntoIterator::into_iter(mismatches).flatten().next() {
+                    return Err(mismatch);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The steps the EvmCircuit will constrain, in order, as `(tx_id,
+    /// program_counter, execution_state, opcode, gas_left)`, for eyeballing a
+    /// block before running the circuit.
+    pub fn execution_trace(&self) -> Vec<(usize, u64, ExecutionState, Option<OpcodeId>, u64)> {
+        self.txs
+            .iter()
+            .flat_map(|tx| {
+                tx.steps.iter().map(move |step| {
+                    (
+                        tx.id,
+                        step.program_counter,
+                        step.execution_state,
+                        step.opcode,
+                        step.gas_left,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the randomness with the one sampled from a RNG seeded by
+    /// `seed`, so the circuit assignments are reproducible.
+    pub fn with_fixed_randomness(mut self, seed: u64) -> Self {
+        self.randomness = F::random(XorShiftRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Insert `Padding` steps before the final `EndBlock` until the block has
+    /// `n_steps` steps in total, so the EvmCircuit lays out the same number of
+    /// steps for any trace shorter than that. A block without txs gets a
+    /// placeholder tx with id 0 and a single call holding its padding and its
+    /// `EndBlock`. On error `self` is left unchanged.
+    pub fn pad_to(&mut self, n_steps: usize) -> Result<(), PaddingError> {
+        let num_steps = match self.txs.last() {
+            Some(tx) => {
+                if tx.steps.last().map(|step| step.execution_state)
+                    != Some(ExecutionState::EndBlock)
+                {
+                    return Err(PaddingError::MissingEndBlock);
+                }
+                self.txs.iter().map(|tx| tx.steps.len()).sum()
+            }
+            // The EndBlock of the placeholder tx
+            None => 1,
+        };
+        if num_steps > n_steps {
+            return Err(PaddingError::StepBudgetExceeded {
+                needed: num_steps,
+                budget: n_steps,
+            });
+        }
+
+        if self.txs.is_empty() {
+            self.txs.push(Transaction {
+                calls: vec![Call::default()],
+                steps: vec![ExecStep {
+                    rw_counter: 1,
+                    execution_state: ExecutionState::EndBlock,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            });
+        }
+        let steps = &mut self.txs.last_mut().unwrap().steps;
+        let end_block = steps.pop().unwrap();
+        // Padding does nothing, so it has the state of the EndBlock it precedes
+        let padding = ExecStep {
+            execution_state: ExecutionState::Padding,
+            ..end_block.clone()
+        };
+        steps.extend(iter::repeat(padding).take(n_steps - num_steps));
+        steps.push(end_block);
+        Ok(())
+    }
+}
+
+/// Error returned by [`Block::check_gas_used`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GasUsedMismatch {
+    /// The gas used reported by the block header
+    pub block_gas_used: u64,
+    /// The sum of the gas consumed by the transactions
+    pub txs_gas_used: u64,
+}
+
+impl std::fmt::Display for GasUsedMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "block gas used {} doesn't match the sum of the gas used by txs {}",
+            self.block_gas_used, self.txs_gas_used
+        )
+    }
+}
+
+impl std::error::Error for GasUsedMismatch {}
+
+/// Error returned by [`Block::pad_to`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaddingError {
+    /// The last step of the block isn't `EndBlock`
+    MissingEndBlock,
+    /// The block already has more steps than the target
+    StepBudgetExceeded {
+        /// Number of steps of the block
+        needed: usize,
+        /// Number of steps to pad the block to
+        budget: usize,
+    },
+}
+
+impl std::fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingEndBlock => write!(f, "the last step of the block isn't EndBlock"),
+            Self::StepBudgetExceeded { needed, budget } => write!(
+                f,
+                "block has {} steps, more than the {} to pad it to",
+                needed, budget
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaddingError {}
+
+/// Error returned by [`Block::check_tx_refunds`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxRefundMismatch {
+    /// The id of the transaction
+    pub tx_id: usize,
+    /// The refund read by the `EndTx` step
+    pub refund: u64,
+    /// The sum of the refund deltas of the steps
+    pub refund_deltas: i64,
+}
+
+impl std::fmt::Display for TxRefundMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "refund {} of tx {} doesn't match the sum of its refund deltas {}",
+            self.refund, self.tx_id, self.refund_deltas
+        )
+    }
+}
+
+impl std::error::Error for TxRefundMismatch {}
+
+/// Error returned by [`Block::check_step_transitions`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepTransitionMismatch {
+    /// The id of the transaction of the step
+    pub tx_id: usize,
+    /// The index of the step in the transaction
+    pub step_index: usize,
+    /// The field of the step state which doesn't match
+    pub field: &'static str,
+    /// The value the step transitions to
+    pub expected: u64,
+    /// The value of the next step
+    pub actual: u64,
+}
+
+impl std::fmt::Display for StepTransitionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "step {} of tx {} transitions to {} {}, but the next step has {}",
+            self.step_index, self.tx_id, self.field, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for StepTransitionMismatch {}
+
+/// Build a minimal [`Block`] with a single transaction whose root call (with
+/// id 1) executes one `opcode` step in `execution_state`, followed by a `STOP`
+/// terminator. The step accesses `setup_rws` in the given order, so their
+/// `rw_counter`s are expected to start from 1. The stack pointers around the
+/// step are inferred from its stack reads and writes, which fits the steps
+/// that pop their operands and push at most one result.
+pub fn single_step_block<F: Field>(
+    execution_state: ExecutionState,
+    opcode: OpcodeId,
+    setup_rws: Vec<Rw>,
+) -> Block<F> {
+    let bytecode = Bytecode::new(vec![opcode.as_u8(), OpcodeId::STOP.as_u8()]);
+
+    let stack_pointers = |is_write: bool| {
+        setup_rws.iter().filter_map(move |rw| match rw {
+            Rw::Stack {
+                is_write: rw_is_write,
+                stack_pointer,
+                ..
+            } if *rw_is_write == is_write => Some(*stack_pointer),
+            _ => None,
+        })
+    };
+    // Operands are read from the current stack pointer upwards, and the result
+    // is written to the top of the next one
+    let stack_pointer = stack_pointers(false)
+        .min()
+        .or_else(|| stack_pointers(true).max().map(|sp| sp + 1))
+        .unwrap_or(STACK_CAPACITY);
+    let next_stack_pointer = stack_pointers(true)
+        .min()
+        .or_else(|| stack_pointers(false).max().map(|sp| sp + 1))
+        .unwrap_or(STACK_CAPACITY);
+
+    let mut rws = RwMap(HashMap::new());
+    let rw_indices = setup_rws
+        .iter()
+        .map(|rw| {
+            let rws = rws.0.entry(rw.tag()).or_insert_with(Vec::new);
+            rws.push(*rw);
+            (rw.tag(), rws.len() - 1)
+        })
+        .collect();
+
+    let gas_left = 1_000_000;
+    let gas_cost = opcode.constant_gas_cost().as_u64();
+    let steps = vec![
+        ExecStep {
+            execution_state,
+            rw_indices,
+            rw_counter: 1,
+            program_counter: 0,
+            stack_pointer,
+            gas_left,
+            gas_cost,
+            opcode: Some(opcode),
+            ..Default::default()
+        },
+        ExecStep {
+            execution_state: ExecutionState::STOP,
+            rw_counter: 1 + setup_rws.len(),
+            program_counter: 1,
+            stack_pointer: next_stack_pointer,
+            gas_left: gas_left - gas_cost,
+            opcode: Some(OpcodeId::STOP),
+            ..Default::default()
+        },
+    ];
+
+    Block {
+        randomness: F::random(rand::thread_rng()),
+        txs: vec![Transaction {
+            id: 1,
+            calls: vec![Call {
+                id: 1,
+                is_root: true,
+                code_source: CodeSource::Account(bytecode.hash),
+                ..Default::default()
+            }],
+            steps,
+            ..Default::default()
+        }],
+        rws,
+        bytecodes: vec![bytecode],
+        ..Default::default()
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -48,7 +529,8 @@ pub struct BlockContext {
     pub number: Word,
     /// The timestamp of the block
     pub timestamp: Word,
-    /// The difficulty of the blcok
+    /// The difficulty of the block, or its PREVRANDAO from the Merge, which is
+    /// what DIFFICULTY returns
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
@@ -56,6 +538,8 @@ pub struct BlockContext {
     pub history_hashes: Vec<Word>,
     /// The chain id
     pub chain_id: Word,
+    /// The gas used by all the transactions, as reported by the block header
+    pub gas_used: u64,
 }
 
 impl BlockContext {
@@ -150,6 +634,10 @@ pub struct Transaction {
     pub call_data_length: usize,
     /// The gas cost for transaction call data
     pub call_data_gas_cost: u64,
+    /// The accounts and their storage keys in the access list (EIP-2930)
+    pub access_list: Vec<(Address, Vec<Word>)>,
+    /// The gas cost for the access list
+    pub access_list_gas_cost: u64,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -220,6 +708,12 @@ impl Transaction {
                     F::zero(),
                     F::from(self.call_data_gas_cost),
                 ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::AccessListGasCost as u64),
+                    F::zero(),
+                    F::from(self.access_list_gas_cost),
+                ],
             ],
             self.call_data
                 .iter()
@@ -327,6 +821,44 @@ impl ExecStep {
         assert_eq!(self.memory_size % N_BYTES_WORD as u64, 0);
         self.memory_size / N_BYTES_WORD as u64
     }
+
+    /// Split the gas cost of the step into its components for debugging,
+    /// where `next_step` is the following step in the same call context.
+    pub fn gas_breakdown(&self, next_step: &ExecStep) -> GasBreakdown {
+        let constant = self
+            .opcode
+            .map(|opcode| opcode.constant_gas_cost().as_u64())
+            .unwrap_or_default();
+        let memory_expansion =
+            memory_expansion_gas_cost(self.memory_word_size(), next_step.memory_word_size());
+        let dynamic = self
+            .gas_cost
+            .checked_sub(constant + memory_expansion)
+            .expect("constant and memory expansion gas cost should not exceed the gas cost");
+        GasBreakdown {
+            constant,
+            dynamic,
+            memory_expansion,
+        }
+    }
+}
+
+/// Components of the gas cost of an execution step
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasBreakdown {
+    /// The constant gas cost of the opcode
+    pub constant: u64,
+    /// The dynamic gas cost other than memory expansion
+    pub dynamic: u64,
+    /// The memory expansion gas cost
+    pub memory_expansion: u64,
+}
+
+impl GasBreakdown {
+    /// The total gas cost
+    pub fn total(&self) -> u64 {
+        self.constant + self.dynamic + self.memory_expansion
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -376,6 +908,47 @@ impl Bytecode {
     }
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct Ecrecover {
+    /// The id of the call to the precompile
+    pub call_id: usize,
+    /// The hash of the signed message
+    pub msg_hash: Word,
+    /// The recovery id of the signature
+    pub v: Word,
+    /// The r component of the signature
+    pub r: Word,
+    /// The s component of the signature
+    pub s: Word,
+    /// The recovered address, which is zero when the recovery fails
+    pub recovered_address: Address,
+}
+
+impl Ecrecover {
+    pub fn table_assignment<F: Field>(&self, randomness: F) -> [F; 5] {
+        [
+            RandomLinearCombination::random_linear_combine(self.msg_hash.to_le_bytes(), randomness),
+            RandomLinearCombination::random_linear_combine(self.v.to_le_bytes(), randomness),
+            RandomLinearCombination::random_linear_combine(self.r.to_le_bytes(), randomness),
+            RandomLinearCombination::random_linear_combine(self.s.to_le_bytes(), randomness),
+            self.recovered_address.to_scalar().unwrap(),
+        ]
+    }
+}
+
+impl From<&circuit_input_builder::EcrecoverEvent> for Ecrecover {
+    fn from(event: &circuit_input_builder::EcrecoverEvent) -> Self {
+        Self {
+            call_id: event.call_id,
+            msg_hash: event.msg_hash,
+            v: event.v,
+            r: event.r,
+            s: event.s,
+            recovered_address: event.recovered_address.unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct RwMap(pub HashMap<RwTableTag, Vec<Rw>>);
 
@@ -427,8 +1000,248 @@ impl RwMap {
         });
         sorted
     }
+
+    /// Append the rows of `other`, e.g. the rw set of the next transaction,
+    /// with its rw_counters offset by the current max so they keep increasing.
+    /// On error `self` is left unchanged.
+    pub fn append(&mut self, other: RwMap) -> Result<(), RwMapAppendError> {
+        let offset = self
+            .0
+            .values()
+            .flatten()
+            .map(Rw::rw_counter)
+            .max()
+            .unwrap_or_default();
+        for (tag, rows) in other.0.iter() {
+            let ids = self
+                .0
+                .get(tag)
+                .into_iter()
+                .flatten()
+                .filter_map(Rw::id)
+                .collect::<HashSet<_>>();
+            for row in rows {
+                if row.rw_counter() == 0 {
+                    return Err(RwMapAppendError::ZeroRwCounter(*tag));
+                }
+                if let Some(id) = row.id().filter(|id| ids.contains(id)) {
+                    return Err(RwMapAppendError::IdCollision { tag: *tag, id });
+                }
+            }
+        }
+
+        for (tag, rows) in other.0 {
+            self.0
+                .entry(tag)
+                .or_insert_with(Vec::new)
+                .extend(rows.into_iter().map(|mut row| {
+                    *row.rw_counter_mut() += offset;
+                    row
+                }));
+        }
+        Ok(())
+    }
+
+    /// Append an AccountStorage write with rw_counter 0 for the first access to
+    /// each (account_address, storage_key), which sets up the block-original
+    /// value, i.e. the committed value for the first transaction touching the
+    /// slot. The rows are only appended, so the `rw_indices` of the steps
+    /// remain valid. This is meant for the state circuit, the evm circuit
+    /// doesn't expect any row with rw_counter 0.
+    pub fn insert_committed_storage_seeds(&mut self) {
+        let storage = self
+            .0
+            .entry(RwTableTag::AccountStorage)
+            .or_insert_with(Vec::new);
+        let mut seeds = BTreeMap::new();
+        for rw in storage.iter() {
+            if let Rw::AccountStorage {
+                rw_counter,
+                account_address,
+                storage_key,
+                tx_id,
+                committed_value,
+                ..
+            } = *rw
+            {
+                let seed = seeds.entry((account_address, storage_key)).or_insert(Some((
+                    rw_counter,
+                    tx_id,
+                    committed_value,
+                )));
+                match *seed {
+                    // The slot has already been seeded
+                    _ if rw_counter == 0 => *seed = None,
+                    Some((first_rw_counter, ..)) if rw_counter < first_rw_counter => {
+                        *seed = Some((rw_counter, tx_id, committed_value))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        storage.extend(
+            seeds
+                .into_iter()
+                .filter_map(|((account_address, storage_key), seed)| {
+                    seed.map(|(_, tx_id, committed_value)| Rw::AccountStorage {
+                        rw_counter: 0,
+                        is_write: true,
+                        account_address,
+                        storage_key,
+                        value: committed_value,
+                        value_prev: committed_value,
+                        tx_id,
+                        committed_value,
+                    })
+                }),
+        );
+    }
+
+    /// Insert a TxAccessListAccount write with rw_counter 0 for each of the
+    /// `prewarmed_addresses` accessed in a transaction, which warms it from
+    /// the start of the transaction as its first access. Like the committed
+    /// storage seeds, this is meant for the state circuit only.
+    pub fn insert_access_list_seeds(&mut self, prewarmed_addresses: &[Address]) {
+        let access_list = self
+            .0
+            .entry(RwTableTag::TxAccessListAccount)
+            .or_insert_with(Vec::new);
+        let mut seeds = BTreeMap::new();
+        for rw in access_list.iter() {
+            if let Rw::TxAccessListAccount {
+                rw_counter,
+                tx_id,
+                account_address,
+                ..
+            } = *rw
+            {
+                if prewarmed_addresses.contains(&account_address) {
+                    let seed = seeds.entry((tx_id, account_address)).or_insert(true);
+                    // The account has already been seeded
+                    if rw_counter == 0 {
+                        *seed = false;
+                    }
+                }
+            }
+        }
+        access_list.extend(seeds.into_iter().filter(|(_, seed)| *seed).map(
+            |((tx_id, account_address), _)| Rw::TxAccessListAccount {
+                rw_counter: 0,
+                is_write: true,
+                tx_id,
+                account_address,
+                is_warm: true,
+                is_warm_prev: false,
+            },
+        ));
+    }
+
+    /// Insert a TxAccessListAccount or TxAccessListAccountStorage write with
+    /// rw_counter 0 for each account or storage slot in the access list
+    /// (EIP-2930) of `txs` which is accessed in its transaction, the same as
+    /// [`Self::insert_access_list_seeds`] does for the prewarmed accounts.
+    pub fn insert_tx_access_list_seeds(&mut self, txs: &[Transaction]) {
+        let access_lists: HashMap<_, _> = txs.iter().map(|tx| (tx.id, &tx.access_list)).collect();
+        let is_listed = |tx_id: usize, address: Address, storage_key: Option<Word>| {
+            access_lists.get(&tx_id).map_or(false, |access_list| {
+                access_list.iter().any(|(listed_address, storage_keys)| {
+                    *listed_address == address
+                        && storage_key.map_or(true, |key| storage_keys.contains(&key))
+                })
+            })
+        };
+
+        let mut account_seeds = BTreeMap::new();
+        let mut storage_seeds = BTreeMap::new();
+        for rw in self.0.values().flatten() {
+            match *rw {
+                Rw::TxAccessListAccount {
+                    rw_counter,
+                    tx_id,
+                    account_address,
+                    ..
+                } if is_listed(tx_id, account_address, None) => {
+                    let seed = account_seeds
+                        .entry((tx_id, account_address))
+                        .or_insert(true);
+                    // The account has already been seeded
+                    if rw_counter == 0 {
+                        *seed = false;
+                    }
+                }
+                Rw::TxAccessListAccountStorage {
+                    rw_counter,
+                    tx_id,
+                    account_address,
+                    storage_key,
+                    ..
+                } if is_listed(tx_id, account_address, Some(storage_key)) => {
+                    let seed = storage_seeds
+                        .entry((tx_id, account_address, storage_key))
+                        .or_insert(true);
+                    // The storage slot has already been seeded
+                    if rw_counter == 0 {
+                        *seed = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.0
+            .entry(RwTableTag::TxAccessListAccount)
+            .or_insert_with(Vec::new)
+            .extend(account_seeds.into_iter().filter(|(_, seed)| *seed).map(
+                |((tx_id, account_address), _)| Rw::TxAccessListAccount {
+                    rw_counter: 0,
+                    is_write: true,
+                    tx_id,
+                    account_address,
+                    is_warm: true,
+                    is_warm_prev: false,
+                },
+            ));
+        self.0
+            .entry(RwTableTag::TxAccessListAccountStorage)
+            .or_insert_with(Vec::new)
+            .extend(storage_seeds.into_iter().filter(|(_, seed)| *seed).map(
+                |((tx_id, account_address, storage_key), _)| Rw::TxAccessListAccountStorage {
+                    rw_counter: 0,
+                    is_write: true,
+                    tx_id,
+                    account_address,
+                    storage_key,
+                    is_warm: true,
+                    is_warm_prev: false,
+                },
+            ));
+    }
+}
+
+/// Error returned by [`RwMap::append`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RwMapAppendError {
+    /// A row has rw_counter 0 (e.g. `Start` or a committed storage seed), so it
+    /// can't be renumbered
+    ZeroRwCounter(RwTableTag),
+    /// Both rw sets have rows with the same id (call_id or tx_id) under the
+    /// same tag, which would interleave their accesses
+    IdCollision { tag: RwTableTag, id: usize },
+}
+
+impl std::fmt::Display for RwMapAppendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroRwCounter(tag) => write!(f, "{:?} row with rw_counter 0", tag),
+            Self::IdCollision { tag, id } => {
+                write!(f, "{:?} rows of both rw sets have id {}", tag, id)
+            }
+        }
+    }
 }
 
+impl std::error::Error for RwMapAppendError {}
+
 #[derive(Clone, Copy, Debug)]
 pub enum Rw {
     Start,
@@ -559,6 +1372,76 @@ impl<F: FieldExt> From<[F; 11]> for RwRow<F> {
 }
 
 impl Rw {
+    /// An AccountStorage read of `value` at `storage_key` of `account_address`
+    /// in the tx `tx_id`, whose value before the tx was `committed_value`. Use
+    /// [`Rw::into_write`] to turn it into a write over `value`.
+    pub fn storage(
+        rw_counter: usize,
+        tx_id: usize,
+        account_address: Address,
+        storage_key: Word,
+        value: Word,
+        committed_value: Word,
+    ) -> Self {
+        Self::AccountStorage {
+            rw_counter,
+            is_write: false,
+            account_address,
+            storage_key,
+            value,
+            value_prev: value,
+            tx_id,
+            committed_value,
+        }
+    }
+
+    /// A TxAccessListAccountStorage write which marks `storage_key` of
+    /// `account_address` as warm in the tx `tx_id`.
+    pub fn tx_access_list_account_storage(
+        rw_counter: usize,
+        tx_id: usize,
+        account_address: Address,
+        storage_key: Word,
+        is_warm_prev: bool,
+    ) -> Self {
+        Self::TxAccessListAccountStorage {
+            rw_counter,
+            is_write: true,
+            tx_id,
+            account_address,
+            storage_key,
+            is_warm: true,
+            is_warm_prev,
+        }
+    }
+
+    /// Turn an AccountStorage read into a write of `value`, with the value read
+    /// as `value_prev`.
+    pub fn into_write(self, value: Word) -> Self {
+        match self {
+            Self::AccountStorage {
+                rw_counter,
+                is_write: false,
+                account_address,
+                storage_key,
+                value: value_prev,
+                tx_id,
+                committed_value,
+                ..
+            } => Self::AccountStorage {
+                rw_counter,
+                is_write: true,
+                account_address,
+                storage_key,
+                value,
+                value_prev,
+                tx_id,
+                committed_value,
+            },
+            _ => unreachable!("only an AccountStorage read can be turned into a write"),
+        }
+    }
+
     pub fn tx_access_list_value_pair(&self) -> (bool, bool) {
         match self {
             Self::TxAccessListAccount {
@@ -666,7 +1549,10 @@ impl Rw {
             ),
             value: self.value_assignment(randomness),
             value_prev: self.value_prev_assignment(randomness).unwrap_or_default(),
-            aux1: F::zero(), // only used for AccountStorage::tx_id, which moved to key1.
+            aux1: match self {
+                Self::AccountStorage { tx_id, .. } => F::from(*tx_id as u64),
+                _ => F::zero(),
+            },
             aux2: self
                 .committed_value_assignment(randomness)
                 .unwrap_or_default(),
@@ -690,6 +1576,23 @@ impl Rw {
         }
     }
 
+    fn rw_counter_mut(&mut self) -> &mut usize {
+        match self {
+            Self::Start => unreachable!("Start has no rw_counter"),
+            Self::Memory { rw_counter, .. }
+            | Self::Stack { rw_counter, .. }
+            | Self::AccountStorage { rw_counter, .. }
+            | Self::TxAccessListAccount { rw_counter, .. }
+            | Self::TxAccessListAccountStorage { rw_counter, .. }
+            | Self::TxRefund { rw_counter, .. }
+            | Self::Account { rw_counter, .. }
+            | Self::AccountDestructed { rw_counter, .. }
+            | Self::CallContext { rw_counter, .. }
+            | Self::TxLog { rw_counter, .. }
+            | Self::TxReceipt { rw_counter, .. } => rw_counter,
+        }
+    }
+
     pub fn is_write(&self) -> bool {
         match self {
             Self::Start => false,
@@ -726,8 +1629,7 @@ impl Rw {
 
     pub fn id(&self) -> Option<usize> {
         match self {
-            Self::AccountStorage { tx_id, .. }
-            | Self::TxAccessListAccount { tx_id, .. }
+            Self::TxAccessListAccount { tx_id, .. }
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
@@ -735,7 +1637,13 @@ impl Rw {
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
-            Self::Start | Self::Account { .. } | Self::AccountDestructed { .. } => None,
+            // The storage is shared by the transactions, so the accesses to a
+            // slot are grouped across them and the tx_id of AccountStorage is in
+            // aux1 instead.
+            Self::Start
+            | Self::Account { .. }
+            | Self::AccountStorage { .. }
+            | Self::AccountDestructed { .. } => None,
         }
     }
 
@@ -824,10 +1732,17 @@ impl Rw {
             }
             Self::Account { value, .. }
             | Self::AccountStorage { value, .. }
-            | Self::Stack { value, .. }
-            | Self::TxLog { value, .. } => {
+            | Self::Stack { value, .. } => {
                 RandomLinearCombination::random_linear_combine(value.to_le_bytes(), randomness)
             }
+            Self::TxLog {
+                field_tag, value, ..
+            } => match field_tag {
+                TxLogFieldTag::Topic => {
+                    RandomLinearCombination::random_linear_combine(value.to_le_bytes(), randomness)
+                }
+                _ => value.to_scalar().unwrap(),
+            },
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => F::from(*is_warm as u64),
             Self::AccountDestructed { is_destructed, .. } => F::from(*is_destructed as u64),
@@ -881,10 +1796,15 @@ impl From<&circuit_input_builder::Block> for BlockContext {
             gas_limit: block.gas_limit,
             number: block.number,
             timestamp: block.timestamp,
-            difficulty: block.difficulty,
+            difficulty: if block.fork.has_prevrandao() {
+                block.prevrandao
+            } else {
+                block.difficulty
+            },
             base_fee: block.base_fee,
             history_hashes: block.history_hashes.clone(),
             chain_id: block.chain_id,
+            gas_used: block.gas_used,
         }
     }
 }
@@ -1085,6 +2005,26 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::TxLog,
+            container
+                .tx_log
+                .iter()
+                .map(|op| Rw::TxLog {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    log_id: op.op().log_id as u64,
+                    field_tag: match op.op().field {
+                        TxLogField::Address => TxLogFieldTag::Address,
+                        TxLogField::Topic => TxLogFieldTag::Topic,
+                        TxLogField::Data => TxLogFieldTag::Data,
+                    },
+                    index: op.op().index,
+                    value: op.op().value,
+                })
+                .collect(),
+        );
 
         Self(rws)
     }
@@ -1133,75 +2073,88 @@ impl From<&ExecError> for ExecutionState {
     }
 }
 
+/// The execution state of a successful step of `op`, or `None` when no gadget
+/// handles it yet.
+pub(crate) fn opcode_execution_state(op: OpcodeId) -> Option<ExecutionState> {
+    if op.is_dup() {
+        return Some(ExecutionState::DUP);
+    }
+    if op.is_push() {
+        return Some(ExecutionState::PUSH);
+    }
+    if op.is_swap() {
+        return Some(ExecutionState::SWAP);
+    }
+    if op.is_log() {
+        return Some(ExecutionState::LOG);
+    }
+    let state = match op {
+        OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
+        OpcodeId::MUL | OpcodeId::DIV | OpcodeId::MOD => ExecutionState::MUL_DIV_MOD,
+        OpcodeId::EQ | OpcodeId::LT | OpcodeId::GT => ExecutionState::CMP,
+        OpcodeId::SLT | OpcodeId::SGT => ExecutionState::SCMP,
+        OpcodeId::SIGNEXTEND => ExecutionState::SIGNEXTEND,
+        // TODO: Convert REVERT and RETURN to their own ExecutionState.
+        OpcodeId::STOP | OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::STOP,
+        OpcodeId::AND => ExecutionState::BITWISE,
+        OpcodeId::XOR => ExecutionState::BITWISE,
+        OpcodeId::OR => ExecutionState::BITWISE,
+        OpcodeId::POP => ExecutionState::POP,
+        OpcodeId::PUSH32 => ExecutionState::PUSH,
+        OpcodeId::BYTE => ExecutionState::BYTE,
+        OpcodeId::MLOAD => ExecutionState::MEMORY,
+        OpcodeId::MSTORE => ExecutionState::MEMORY,
+        OpcodeId::MSTORE8 => ExecutionState::MEMORY,
+        OpcodeId::JUMPDEST => ExecutionState::JUMPDEST,
+        OpcodeId::JUMP => ExecutionState::JUMP,
+        OpcodeId::JUMPI => ExecutionState::JUMPI,
+        OpcodeId::GASPRICE => ExecutionState::GASPRICE,
+        OpcodeId::PC => ExecutionState::PC,
+        OpcodeId::MSIZE => ExecutionState::MSIZE,
+        OpcodeId::CALLER => ExecutionState::CALLER,
+        OpcodeId::CALLVALUE => ExecutionState::CALLVALUE,
+        OpcodeId::EXTCODEHASH => ExecutionState::EXTCODEHASH,
+        OpcodeId::TIMESTAMP | OpcodeId::NUMBER | OpcodeId::GASLIMIT => ExecutionState::BLOCKCTXU64,
+        OpcodeId::COINBASE => ExecutionState::BLOCKCTXU160,
+        OpcodeId::DIFFICULTY | OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
+        OpcodeId::GAS => ExecutionState::GAS,
+        OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
+        OpcodeId::SLOAD => ExecutionState::SLOAD,
+        OpcodeId::SSTORE => ExecutionState::SSTORE,
+        OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
+        OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
+        OpcodeId::CHAINID => ExecutionState::CHAINID,
+        OpcodeId::ISZERO => ExecutionState::ISZERO,
+        OpcodeId::CALL => ExecutionState::CALL,
+        OpcodeId::ORIGIN => ExecutionState::ORIGIN,
+        OpcodeId::CODECOPY => ExecutionState::CODECOPY,
+        OpcodeId::CALLDATALOAD => ExecutionState::CALLDATALOAD,
+        OpcodeId::RETURNDATASIZE => ExecutionState::RETURNDATASIZE,
+        OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
+        _ => return None,
+    };
+    Some(state)
+}
+
 impl From<&circuit_input_builder::ExecStep> for ExecutionState {
     fn from(step: &circuit_input_builder::ExecStep) -> Self {
         if let Some(error) = step.error.as_ref() {
             return error.into();
         }
         match step.exec_state {
-            circuit_input_builder::ExecState::Op(op) => {
-                if op.is_dup() {
-                    return ExecutionState::DUP;
-                }
-                if op.is_push() {
-                    return ExecutionState::PUSH;
-                }
-                if op.is_swap() {
-                    return ExecutionState::SWAP;
-                }
-                if op.is_log() {
-                    return ExecutionState::LOG;
-                }
-                match op {
-                    OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
-                    OpcodeId::MUL | OpcodeId::DIV | OpcodeId::MOD => ExecutionState::MUL_DIV_MOD,
-                    OpcodeId::EQ | OpcodeId::LT | OpcodeId::GT => ExecutionState::CMP,
-                    OpcodeId::SLT | OpcodeId::SGT => ExecutionState::SCMP,
-                    OpcodeId::SIGNEXTEND => ExecutionState::SIGNEXTEND,
-                    // TODO: Convert REVERT and RETURN to their own ExecutionState.
-                    OpcodeId::STOP | OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::STOP,
-                    OpcodeId::AND => ExecutionState::BITWISE,
-                    OpcodeId::XOR => ExecutionState::BITWISE,
-                    OpcodeId::OR => ExecutionState::BITWISE,
-                    OpcodeId::POP => ExecutionState::POP,
-                    OpcodeId::PUSH32 => ExecutionState::PUSH,
-                    OpcodeId::BYTE => ExecutionState::BYTE,
-                    OpcodeId::MLOAD => ExecutionState::MEMORY,
-                    OpcodeId::MSTORE => ExecutionState::MEMORY,
-                    OpcodeId::MSTORE8 => ExecutionState::MEMORY,
-                    OpcodeId::JUMPDEST => ExecutionState::JUMPDEST,
-                    OpcodeId::JUMP => ExecutionState::JUMP,
-                    OpcodeId::JUMPI => ExecutionState::JUMPI,
-                    OpcodeId::GASPRICE => ExecutionState::GASPRICE,
-                    OpcodeId::PC => ExecutionState::PC,
-                    OpcodeId::MSIZE => ExecutionState::MSIZE,
-                    OpcodeId::CALLER => ExecutionState::CALLER,
-                    OpcodeId::CALLVALUE => ExecutionState::CALLVALUE,
-                    OpcodeId::EXTCODEHASH => ExecutionState::EXTCODEHASH,
-                    OpcodeId::TIMESTAMP | OpcodeId::NUMBER | OpcodeId::GASLIMIT => {
-                        ExecutionState::BLOCKCTXU64
-                    }
-                    OpcodeId::COINBASE => ExecutionState::BLOCKCTXU160,
-                    OpcodeId::DIFFICULTY | OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
-                    OpcodeId::GAS => ExecutionState::GAS,
-                    OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
-                    OpcodeId::SLOAD => ExecutionState::SLOAD,
-                    OpcodeId::SSTORE => ExecutionState::SSTORE,
-                    OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
-                    OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
-                    OpcodeId::CHAINID => ExecutionState::CHAINID,
-                    OpcodeId::ISZERO => ExecutionState::ISZERO,
-                    OpcodeId::CALL => ExecutionState::CALL,
-                    OpcodeId::ORIGIN => ExecutionState::ORIGIN,
-                    OpcodeId::CODECOPY => ExecutionState::CODECOPY,
-                    OpcodeId::CALLDATALOAD => ExecutionState::CALLDATALOAD,
-                    _ => unimplemented!("unimplemented opcode {:?}", op),
-                }
-            }
+            circuit_input_builder::ExecState::Op(op) => opcode_execution_state(op)
+                .unwrap_or_else(|| unimplemented!("unimplemented opcode {:?}", op)),
             circuit_input_builder::ExecState::BeginTx => ExecutionState::BeginTx,
             circuit_input_builder::ExecState::EndTx => ExecutionState::EndTx,
             circuit_input_builder::ExecState::CopyToMemory => ExecutionState::CopyToMemory,
             circuit_input_builder::ExecState::CopyCodeToMemory => ExecutionState::CopyCodeToMemory,
+            circuit_input_builder::ExecState::CopyToLog => ExecutionState::CopyToLog,
+            circuit_input_builder::ExecState::PrecompileEcrecover => {
+                ExecutionState::PrecompileEcrecover
+            }
+            circuit_input_builder::ExecState::PrecompileIdentity => {
+                ExecutionState::PrecompileIdentity
+            }
         }
     }
 }
@@ -1215,12 +2168,16 @@ impl From<&eth_types::bytecode::Bytecode> for Bytecode {
 fn step_convert(step: &circuit_input_builder::ExecStep) -> ExecStep {
     ExecStep {
         call_index: step.call_index,
+        // The memory word operations aren't supported by the circuits, which
+        // only prove the byte granular memory, so they are left out and a
+        // step relying on them doesn't verify.
         rw_indices: step
             .bus_mapping_instance
             .iter()
-            .map(|x| {
+            .filter_map(|x| {
                 let tag = match x.target() {
                     operation::Target::Memory => RwTableTag::Memory,
+                    operation::Target::MemoryWord => return None,
                     operation::Target::Stack => RwTableTag::Stack,
                     operation::Target::Storage => RwTableTag::AccountStorage,
                     operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
@@ -1232,14 +2189,17 @@ fn step_convert(step: &circuit_input_builder::ExecStep) -> ExecStep {
                     operation::Target::AccountDestructed => RwTableTag::AccountDestructed,
                     operation::Target::CallContext => RwTableTag::CallContext,
                     operation::Target::TxReceipt => RwTableTag::TxReceipt,
+                    operation::Target::TxLog => RwTableTag::TxLog,
                 };
-                (tag, x.as_usize())
+                Some((tag, x.as_usize()))
             })
             .collect(),
         execution_state: ExecutionState::from(step),
         rw_counter: usize::from(step.rwc),
         program_counter: usize::from(step.pc) as u64,
-        stack_pointer: STACK_CAPACITY - step.stack_size,
+        stack_pointer: STACK_CAPACITY
+            .checked_sub(step.stack_size)
+            .expect("stack size should not exceed the stack capacity"),
         gas_left: step.gas_left.0,
         gas_cost: step.gas_cost.as_u64(),
         opcode: match step.exec_state {
@@ -1269,6 +2229,8 @@ fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize, is_last_tx: bo
             .input
             .iter()
             .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 }),
+        access_list: tx.access_list.clone(),
+        access_list_gas_cost: tx.access_list_gas_cost(),
         calls: tx
             .calls()
             .iter()
@@ -1348,5 +2310,39 @@ pub fn block_convert(
                     .map(|code_hash| Bytecode::new(code_db.0.get(&code_hash).unwrap().to_vec()))
             })
             .collect(),
+        ecrecovers: block.ecrecover_events.iter().map(Ecrecover::from).collect(),
+        max_evm_rows: 0,
+        fork: block.fork,
+    }
+}
+
+/// Witnesses of the EvmCircuit and the StateCircuit for the same block, which
+/// share the rw numbering and the randomness.
+#[derive(Debug, Clone)]
+pub struct Witnesses<F> {
+    /// Witness block of the EvmCircuit
+    pub block: Block<F>,
+    /// Rows of the StateCircuit, which are the ones of `block` plus the seeds
+    /// of the committed storage values, of the prewarmed accounts and of the
+    /// access lists of the transactions
+    pub rws: RwMap,
+}
+
+impl Witnesses<Fr> {
+    /// Convert the output of a [`CircuitInputBuilder`] into the witnesses of
+    /// both circuits with `randomness`, rejecting a block whose steps don't
+    /// transition into each other as checked by
+    /// [`Block::check_step_transitions`].
+    ///
+    /// [`CircuitInputBuilder`]: circuit_input_builder::CircuitInputBuilder
+    pub fn from_builder(
+        builder: &circuit_input_builder::CircuitInputBuilder,
+        randomness: Fr,
+    ) -> Result<Self, StepTransitionMismatch> {
+        let mut block = block_convert(&builder.block, &builder.code_db);
+        block.randomness = randomness;
+        block.check_step_transitions()?;
+        let rws = block.state_circuit_rws();
+        Ok(Self { block, rws })
     }
 }
@@ -11,7 +11,9 @@
 #![deny(unsafe_code)]
 #![deny(clippy::debug_assert_with_mut_call)]
 
+pub mod block_table;
 pub mod bytecode_circuit;
+pub mod bytecode_table;
 pub mod evm_circuit;
 pub mod rw_table;
 pub mod state_circuit;
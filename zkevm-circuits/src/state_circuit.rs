@@ -2,18 +2,22 @@
 mod constraint_builder;
 mod lexicographic_ordering;
 mod lookups;
+mod mpt;
 mod multiple_precision_integer;
 mod random_linear_combination;
 #[cfg(test)]
 mod test;
 
-use crate::evm_circuit::{
-    param::N_BYTES_WORD,
-    util::RandomLinearCombination,
-    witness::{Rw, RwMap},
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::RandomLinearCombination,
+        witness::{Rw, RwMap},
+    },
+    rw_table::RwTable,
 };
 use constraint_builder::{ConstraintBuilder, Queries};
-use eth_types::{Address, Field, ToLittleEndian};
+use eth_types::{Address, Field, ToLittleEndian, Word};
 use gadgets::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -26,11 +30,12 @@ use lexicographic_ordering::{
     Chip as LexicographicOrderingChip, Config as LexicographicOrderingConfig,
 };
 use lookups::{Chip as LookupsChip, Config as LookupsConfig, Queries as LookupsQueries};
+use mpt::{Config as MptConfig, MptUpdate, Queries as MptQueries};
 use multiple_precision_integer::{Chip as MpiChip, Config as MpiConfig, Queries as MpiQueries};
 use random_linear_combination::{Chip as RlcChip, Config as RlcConfig, Queries as RlcQueries};
 #[cfg(test)]
 use std::collections::HashMap;
-use std::iter::once;
+use std::{collections::HashSet, iter::once};
 
 const N_LIMBS_RW_COUNTER: usize = 2;
 const N_LIMBS_ACCOUNT_ADDRESS: usize = 10;
@@ -41,92 +46,65 @@ const N_LIMBS_ID: usize = 2;
 pub struct StateConfig<F: Field> {
     selector: Column<Fixed>, // Figure out why you get errors when this is Selector.
     // https://github.com/appliedzkp/zkevm-circuits/issues/407
+    rw_table: RwTable,
     rw_counter: MpiConfig<u32, N_LIMBS_RW_COUNTER>,
-    is_write: Column<Advice>,
-    tag: Column<Advice>,
+    is_rw_counter_zero: IsZeroConfig<F>,
     id: MpiConfig<u32, N_LIMBS_ID>,
     is_id_unchanged: IsZeroConfig<F>,
     address: MpiConfig<Address, N_LIMBS_ACCOUNT_ADDRESS>,
-    field_tag: Column<Advice>,
     storage_key: RlcConfig<N_BYTES_WORD>,
     is_storage_key_unchanged: IsZeroConfig<F>,
-    value: Column<Advice>,
+    is_storage_reversion: Column<Advice>,
+    is_first_access: Column<Advice>,
+    mpt: MptConfig,
     lookups: LookupsConfig,
     power_of_randomness: [Column<Instance>; N_BYTES_WORD - 1],
     lexicographic_ordering: LexicographicOrderingConfig<F>,
 }
 
-type Lookup<F> = (&'static str, Expression<F>, Expression<F>);
-
-/// State Circuit for proving RwTable is valid
-#[derive(Default)]
-pub struct StateCircuit<F: Field> {
-    pub(crate) randomness: F,
-    pub(crate) rows: Vec<Rw>,
-    #[cfg(test)]
-    overrides: HashMap<(test::AdviceColumn, usize), F>,
-}
-
-impl<F: Field> StateCircuit<F> {
-    /// make a new state circuit from an RwMap
-    pub fn new(randomness: F, rw_map: RwMap) -> Self {
-        let mut rows: Vec<_> = rw_map.0.into_values().flatten().collect();
-        rows.sort_by_key(|row| {
-            (
-                row.tag() as u64,
-                row.field_tag().unwrap_or_default(),
-                row.id().unwrap_or_default(),
-                row.address().unwrap_or_default(),
-                row.storage_key().unwrap_or_default(),
-                row.rw_counter(),
-            )
-        });
-        Self {
-            randomness,
-            rows,
-            #[cfg(test)]
-            overrides: HashMap::new(),
-        }
+impl<F: Field> StateConfig<F> {
+    /// Configure the state circuit on top of the given rw table, so that it
+    /// can be shared with the evm circuit.
+    pub fn configure(meta: &mut ConstraintSystem<F>, rw_table: RwTable) -> Self {
+        Self::configure_with_monotone_check(meta, rw_table, true)
     }
 
-    /// powers of randomness for instance columns
-    pub fn instance(&self) -> Vec<Vec<F>> {
-        (1..32)
-            .map(|exp| vec![self.randomness.pow(&[exp, 0, 0, 0]); self.rows.len()])
-            .collect()
-    }
-}
-
-impl<F: Field> Circuit<F> for StateCircuit<F> {
-    type Config = StateConfig<F>;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    fn configure_with_monotone_check(
+        meta: &mut ConstraintSystem<F>,
+        rw_table: RwTable,
+        check_monotone: bool,
+    ) -> Self {
         let selector = meta.fixed_column();
         let lookups = LookupsChip::configure(meta);
         let power_of_randomness = [0; N_BYTES_WORD - 1].map(|_| meta.instance_column());
 
-        let [is_write, tag, field_tag, value, is_id_unchanged_column, is_storage_key_unchanged_column] =
-            [0; 6].map(|_| meta.advice_column());
+        let [is_id_unchanged_column, is_storage_key_unchanged_column, is_rw_counter_zero_column] =
+            [0; 3].map(|_| meta.advice_column());
+        let is_storage_reversion = meta.advice_column();
+        let is_first_access = meta.advice_column();
+        let mpt = MptConfig::configure(meta);
 
-        let id = MpiChip::configure(meta, selector, lookups.u16);
-        let address = MpiChip::configure(meta, selector, lookups.u16);
-        let storage_key = RlcChip::configure(meta, selector, lookups.u8, power_of_randomness);
-        let rw_counter = MpiChip::configure(meta, selector, lookups.u16);
+        let id = MpiChip::configure(meta, selector, rw_table.key1, lookups.u16);
+        let address = MpiChip::configure(meta, selector, rw_table.key2, lookups.u16);
+        let storage_key = RlcChip::configure(
+            meta,
+            selector,
+            rw_table.key4,
+            lookups.u8,
+            power_of_randomness,
+        );
+        let rw_counter = MpiChip::configure(meta, selector, rw_table.rw_counter, lookups.u16);
 
         let lexicographic_ordering = LexicographicOrderingChip::configure(
             meta,
-            tag,
-            field_tag,
+            rw_table.tag,
+            rw_table.key3,
             id.limbs,
             address.limbs,
             storage_key.bytes,
             rw_counter.limbs,
             lookups.u16,
+            check_monotone,
         );
 
         let is_id_unchanged = IsZeroChip::configure(
@@ -148,19 +126,27 @@ impl<F: Field> Circuit<F> for StateCircuit<F> {
             is_storage_key_unchanged_column,
         );
 
-        let config = Self::Config {
+        let is_rw_counter_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_fixed(selector, Rotation::cur()),
+            |meta| meta.query_advice(rw_table.rw_counter, Rotation::cur()),
+            is_rw_counter_zero_column,
+        );
+
+        let config = Self {
             selector,
+            rw_table,
             rw_counter,
-            is_write,
-            tag,
+            is_rw_counter_zero,
             id,
             is_id_unchanged,
             address,
-            field_tag,
             storage_key,
-            value,
             lexicographic_ordering,
             is_storage_key_unchanged,
+            is_storage_reversion,
+            is_first_access,
+            mpt,
             lookups,
             power_of_randomness,
         };
@@ -172,11 +158,84 @@ impl<F: Field> Circuit<F> for StateCircuit<F> {
             constraint_builder.gate(queries.selector)
         });
         for (name, expressions) in constraint_builder.lookups() {
-            meta.lookup_any(name, |_| vec![expressions]);
+            meta.lookup_any(name, |_| expressions);
         }
+        // With the strict feature, a circuit relying on the incomplete
+        // constraints of some tags can't be configured at all.
+        #[cfg(feature = "strict")]
+        assert!(
+            constraint_builder.stub_tags().is_empty(),
+            "missing constraints for the rw table tags {:?}",
+            constraint_builder.stub_tags()
+        );
 
         config
     }
+}
+
+type Lookup<F> = (&'static str, Expression<F>, Expression<F>);
+
+/// State Circuit for proving RwTable is valid. With `CHECK_MONOTONE` off, the
+/// rows don't have to be sorted, which tells ordering failures apart from value
+/// consistency ones when debugging a bad rw set.
+#[derive(Default)]
+pub struct StateCircuitBase<F: Field, const CHECK_MONOTONE: bool> {
+    pub(crate) randomness: F,
+    pub(crate) rows: Vec<Rw>,
+    #[cfg(test)]
+    overrides: HashMap<(test::AdviceColumn, usize), F>,
+}
+
+/// State Circuit which checks that the rows are sorted
+pub type StateCircuit<F> = StateCircuitBase<F, true>;
+
+impl<F: Field, const CHECK_MONOTONE: bool> StateCircuitBase<F, CHECK_MONOTONE> {
+    /// make a new state circuit from an RwMap
+    pub fn new(randomness: F, rw_map: RwMap) -> Self {
+        let mut rows: Vec<_> = rw_map.0.into_values().flatten().collect();
+        rows.sort_by_key(|row| (key_prefix(row), row.rw_counter()));
+        Self {
+            randomness,
+            rows,
+            #[cfg(test)]
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// powers of randomness for instance columns, on the Start row and on each
+    /// of the rows, so that an empty rw set still has its Start row
+    pub fn instance(&self) -> Vec<Vec<F>> {
+        (1..32)
+            .map(|exp| vec![self.randomness.pow(&[exp, 0, 0, 0]); self.rows.len() + 1])
+            .collect()
+    }
+}
+
+impl<F: Field> StateCircuitBase<F, false> {
+    /// make a new state circuit from rows kept in the given order, since the
+    /// ordering isn't checked
+    pub fn from_rows(randomness: F, rows: Vec<Rw>) -> Self {
+        Self {
+            randomness,
+            rows,
+            #[cfg(test)]
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl<F: Field, const CHECK_MONOTONE: bool> Circuit<F> for StateCircuitBase<F, CHECK_MONOTONE> {
+    type Config = StateConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let rw_table = RwTable::construct(meta);
+        StateConfig::configure_with_monotone_check(meta, rw_table, CHECK_MONOTONE)
+    }
 
     fn synthesize(
         &self,
@@ -185,33 +244,92 @@ impl<F: Field> Circuit<F> for StateCircuit<F> {
     ) -> Result<(), Error> {
         LookupsChip::construct(config.lookups).load(&mut layouter)?;
 
+        let is_rw_counter_zero = IsZeroChip::construct(config.is_rw_counter_zero.clone());
         let is_id_unchanged = IsZeroChip::construct(config.is_id_unchanged.clone());
         let is_storage_key_unchanged =
             IsZeroChip::construct(config.is_storage_key_unchanged.clone());
         let lexicographic_ordering_chip =
             LexicographicOrderingChip::construct(config.lexicographic_ordering.clone());
 
+        // The rw counters of the TxAccessListAccountStorage writes, to find out
+        // whether a storage access is the reversion of an SSTORE
+        let access_list_rw_counters: HashSet<_> = self
+            .rows
+            .iter()
+            .filter_map(|row| match row {
+                Rw::TxAccessListAccountStorage {
+                    rw_counter,
+                    tx_id,
+                    account_address,
+                    storage_key,
+                    ..
+                } => Some((*tx_id, *account_address, *storage_key, *rw_counter)),
+                _ => None,
+            })
+            .collect();
+
         layouter.assign_region(
             || "rw table",
             |mut region| {
                 let rows = once(&Rw::Start).chain(&self.rows);
                 let prev_rows = once(&Rw::Start).chain(rows.clone());
-                for (offset, (row, prev_row)) in rows.zip(prev_rows).enumerate() {
+                // The Start row isn't an access
+                let is_first_accesses = once(false).chain(
+                    first_and_last_accesses(&self.rows)
+                        .into_iter()
+                        .map(|(is_first_access, _)| is_first_access),
+                );
+                for (offset, ((row, prev_row), is_first_access)) in
+                    rows.zip(prev_rows).zip(is_first_accesses).enumerate()
+                {
                     region.assign_fixed(|| "selector", config.selector, offset, || Ok(F::one()))?;
+                    config.rw_table.assign(
+                        &mut region,
+                        offset,
+                        &row.table_assignment(self.randomness),
+                    )?;
                     config
                         .rw_counter
                         .assign(&mut region, offset, row.rw_counter() as u32)?;
+                    is_rw_counter_zero.assign(
+                        &mut region,
+                        offset,
+                        Some(F::from(row.rw_counter() as u64)),
+                    )?;
+                    let is_storage_reversion = match *row {
+                        Rw::AccountStorage {
+                            rw_counter,
+                            tx_id,
+                            account_address,
+                            storage_key,
+                            ..
+                        } => {
+                            let has_access_list_write = |rw_counter| {
+                                access_list_rw_counters.contains(&(
+                                    tx_id,
+                                    account_address,
+                                    storage_key,
+                                    rw_counter,
+                                ))
+                            };
+                            !has_access_list_write(rw_counter + 1)
+                                && rw_counter
+                                    .checked_sub(1)
+                                    .map_or(false, has_access_list_write)
+                        }
+                        _ => false,
+                    };
                     region.assign_advice(
-                        || "is_write",
-                        config.is_write,
+                        || "is_storage_reversion",
+                        config.is_storage_reversion,
                         offset,
-                        || Ok(if row.is_write() { F::one() } else { F::zero() }),
+                        || Ok(F::from(is_storage_reversion as u64)),
                     )?;
                     region.assign_advice(
-                        || "tag",
-                        config.tag,
+                        || "is_first_access",
+                        config.is_first_access,
                         offset,
-                        || Ok(F::from(row.tag() as u64)),
+                        || Ok(F::from(is_first_access as u64)),
                     )?;
                     if let Some(id) = row.id() {
                         config.id.assign(&mut region, offset, id as u32)?;
@@ -219,21 +337,10 @@ impl<F: Field> Circuit<F> for StateCircuit<F> {
                     if let Some(address) = row.address() {
                         config.address.assign(&mut region, offset, address)?;
                     }
-                    if let Some(field_tag) = row.field_tag() {
-                        region.assign_advice(
-                            || "field_tag",
-                            config.field_tag,
-                            offset,
-                            || Ok(F::from(field_tag as u64)),
-                        )?;
-                    }
                     if let Some(storage_key) = row.storage_key() {
-                        config.storage_key.assign(
-                            &mut region,
-                            offset,
-                            self.randomness,
-                            storage_key,
-                        )?;
+                        config
+                            .storage_key
+                            .assign(&mut region, offset, storage_key)?;
                     }
 
                     if offset != 0 {
@@ -258,6 +365,12 @@ impl<F: Field> Circuit<F> for StateCircuit<F> {
                     }
                 }
 
+                for (offset, update) in MptUpdate::from_rows(&self.rows).iter().enumerate() {
+                    config
+                        .mpt
+                        .assign(&mut region, offset, update, self.randomness)?;
+                }
+
                 #[cfg(test)]
                 for ((column, offset), &f) in &self.overrides {
                     let advice_column = column.value(&config);
@@ -270,19 +383,56 @@ impl<F: Field> Circuit<F> for StateCircuit<F> {
     }
 }
 
+/// The fields by which the rows are sorted, except for the rw_counter, so that
+/// the accesses to the same key are next to each other.
+fn key_prefix(row: &Rw) -> (u64, u64, usize, Address, Word) {
+    (
+        row.tag() as u64,
+        row.field_tag().unwrap_or_default(),
+        row.id().unwrap_or_default(),
+        row.address().unwrap_or_default(),
+        row.storage_key().unwrap_or_default(),
+    )
+}
+
+/// Whether each of the sorted `rows` is the first and the last access to its
+/// key, i.e. the rows before and after it have another key prefix.
+fn first_and_last_accesses(rows: &[Rw]) -> Vec<(bool, bool)> {
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let is_other_key = |other: &Rw| key_prefix(other) != key_prefix(row);
+            (
+                i.checked_sub(1)
+                    .map_or(true, |prev| is_other_key(&rows[prev])),
+                rows.get(i + 1).map_or(true, is_other_key),
+            )
+        })
+        .collect()
+}
+
 fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateConfig<F>) -> Queries<F> {
     Queries {
         selector: meta.query_fixed(c.selector, Rotation::cur()),
+        next_selector: meta.query_fixed(c.selector, Rotation::next()),
         rw_counter: MpiQueries::new(meta, c.rw_counter),
-        is_write: meta.query_advice(c.is_write, Rotation::cur()),
-        tag: meta.query_advice(c.tag, Rotation::cur()),
-        prev_tag: meta.query_advice(c.tag, Rotation::prev()),
+        is_rw_counter_zero: c.is_rw_counter_zero.is_zero_expression.clone(),
+        is_write: meta.query_advice(c.rw_table.is_write, Rotation::cur()),
+        tag: meta.query_advice(c.rw_table.tag, Rotation::cur()),
+        prev_tag: meta.query_advice(c.rw_table.tag, Rotation::prev()),
         id: MpiQueries::new(meta, c.id),
         is_id_unchanged: c.is_id_unchanged.is_zero_expression.clone(),
         address: MpiQueries::new(meta, c.address),
-        field_tag: meta.query_advice(c.field_tag, Rotation::cur()),
+        field_tag: meta.query_advice(c.rw_table.key3, Rotation::cur()),
         storage_key: RlcQueries::new(meta, c.storage_key),
-        value: meta.query_advice(c.value, Rotation::cur()),
+        value: meta.query_advice(c.rw_table.value, Rotation::cur()),
+        value_prev: meta.query_advice(c.rw_table.value_prev, Rotation::cur()),
+        prev_value: meta.query_advice(c.rw_table.value, Rotation::prev()),
+        aux1: meta.query_advice(c.rw_table.aux1, Rotation::cur()),
+        is_storage_reversion: meta.query_advice(c.is_storage_reversion, Rotation::cur()),
+        is_first_access: meta.query_advice(c.is_first_access, Rotation::cur()),
+        next_is_first_access: meta.query_advice(c.is_first_access, Rotation::next()),
+        mpt: MptQueries::new(meta, c.mpt),
         lookups: LookupsQueries::new(meta, c.lookups),
         power_of_randomness: c
             .power_of_randomness
@@ -293,5 +443,7 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateConfig<F>) -> Quer
             .upper_limb_difference_is_zero
             .is_zero_expression
             .clone(),
+        lexicographic_ordering_selector: meta
+            .query_fixed(c.lexicographic_ordering.selector, Rotation::cur()),
     }
 }
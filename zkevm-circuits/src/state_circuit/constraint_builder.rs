@@ -1,5 +1,6 @@
 use super::{
-    lookups::Queries as LookupsQueries, multiple_precision_integer::Queries as MpiQueries,
+    lookups::Queries as LookupsQueries, mpt::Queries as MptQueries,
+    multiple_precision_integer::Queries as MpiQueries,
     random_linear_combination::Queries as RlcQueries, N_LIMBS_ACCOUNT_ADDRESS, N_LIMBS_ID,
     N_LIMBS_RW_COUNTER,
 };
@@ -16,7 +17,9 @@ use strum::IntoEnumIterator;
 #[derive(Clone)]
 pub struct Queries<F: Field> {
     pub selector: Expression<F>,
+    pub next_selector: Expression<F>,
     pub rw_counter: MpiQueries<F, N_LIMBS_RW_COUNTER>,
+    pub is_rw_counter_zero: Expression<F>,
     pub is_write: Expression<F>,
     pub tag: Expression<F>,
     pub prev_tag: Expression<F>,
@@ -26,19 +29,28 @@ pub struct Queries<F: Field> {
     pub field_tag: Expression<F>,
     pub storage_key: RlcQueries<F, N_BYTES_WORD>,
     pub value: Expression<F>,
+    pub value_prev: Expression<F>,
+    pub prev_value: Expression<F>,
+    pub aux1: Expression<F>,
+    pub is_storage_reversion: Expression<F>,
+    pub is_first_access: Expression<F>,
+    pub next_is_first_access: Expression<F>,
+    pub mpt: MptQueries<F>,
     pub lookups: LookupsQueries<F>,
     pub power_of_randomness: [Expression<F>; N_BYTES_WORD - 1],
     pub is_storage_key_unchanged: Expression<F>,
     pub lexicographic_ordering_upper_limb_difference_is_zero: Expression<F>,
+    pub lexicographic_ordering_selector: Expression<F>,
 }
 
 type Constraint<F> = (&'static str, Expression<F>);
-type Lookup<F> = (&'static str, (Expression<F>, Expression<F>));
+type Lookup<F> = (&'static str, Vec<(Expression<F>, Expression<F>)>);
 
 pub struct ConstraintBuilder<F: Field> {
     pub constraints: Vec<Constraint<F>>,
     lookups: Vec<Lookup<F>>,
     condition: Expression<F>,
+    stub_tags: Vec<RwTableTag>,
 }
 
 impl<F: Field> ConstraintBuilder<F> {
@@ -47,6 +59,7 @@ impl<F: Field> ConstraintBuilder<F> {
             constraints: vec![],
             lookups: vec![],
             condition: 1.expr(),
+            stub_tags: vec![],
         }
     }
 
@@ -62,6 +75,12 @@ impl<F: Field> ConstraintBuilder<F> {
         self.lookups.clone()
     }
 
+    /// Tags whose constraints are known to be incomplete, so the circuit isn't
+    /// sound for their rows yet.
+    pub fn stub_tags(&self) -> &[RwTableTag] {
+        &self.stub_tags
+    }
+
     pub fn build(&mut self, q: &Queries<F>) {
         self.build_general_constraints(q);
         self.condition(q.tag_matches(RwTableTag::Start), |cb| {
@@ -100,10 +119,21 @@ impl<F: Field> ConstraintBuilder<F> {
     fn build_general_constraints(&mut self, q: &Queries<F>) {
         self.require_in_set("tag in RwTableTag range", q.tag(), set::<F, RwTableTag>());
         self.require_boolean("is_write is boolean", q.is_write());
+        self.require_zero(
+            "is_first_access matches the lexicographic ordering",
+            q.is_first_access.clone() - q.first_access(),
+        );
     }
 
     fn build_start_constraints(&mut self, q: &Queries<F>) {
         self.require_zero("rw_counter is 0 for Start", q.rw_counter.value.clone());
+        // Start can only be at the beginning of the table, so it can't follow a
+        // row with other tags.
+        self.require_zero(
+            "Start is not preceded by other tags",
+            q.lexicographic_ordering_selector.clone()
+                * (q.prev_tag.clone() - RwTableTag::Start.expr()),
+        );
     }
 
     fn build_memory_constraints(&mut self, q: &Queries<F>) {
@@ -144,18 +174,73 @@ impl<F: Field> ConstraintBuilder<F> {
     }
 
     fn build_account_storage_constraints(&mut self, q: &Queries<F>) {
-        // TODO: cold VS warm
-        // TODO: connection to MPT on first and last access for each (address, key)
-        // No longer true because we moved id from aux to here.
-        // self.require_zero("id is 0 for AccountStorage", q.id());
+        // The tx_id is in aux1, so that the accesses to a slot in all the
+        // transactions are grouped together.
+        self.require_zero("id is 0 for AccountStorage", q.id());
         self.require_zero("field_tag is 0 for AccountStorage", q.field_tag());
         // for every first access, we add an AccountStorage write to setup the
-        // value from the previous block with rw_counter = 0
+        // value from the previous block with rw_counter = 0, see
+        // `RwMap::insert_committed_storage_seeds`
         // needs some work...
         // self.condition(q.first_access(), |cb| {
         //     cb.require_zero("first access is a write", q.is_write());
         //     // cb.require_zero("first access rw_counter is 0",
         // q.rw_counter.value.clone()); })
+
+        // Every storage access warms the slot with a TxAccessListAccountStorage
+        // write in the same step, which is right after the access, or right
+        // before it for the reversion of an SSTORE, since reversions are done
+        // in the reverse order. The seeds with rw_counter 0 aren't accesses.
+        // TODO: is_storage_reversion is a free witness, so a prover can set it
+        // to look up the TxAccessListAccountStorage write before any access and
+        // skip the warm/cold link. It should be derived from the rw_counter of
+        // the access against the reversion counters of its call, which needs
+        // the CallContext constraints. Until then the link isn't sound.
+        self.require_boolean(
+            "is_storage_reversion is boolean",
+            q.is_storage_reversion.clone(),
+        );
+        self.stub_tags.push(RwTableTag::AccountStorage);
+        self.condition(not::expr(q.is_rw_counter_zero.clone()), |cb| {
+            cb.add_tuple_lookup(
+                "storage access has a TxAccessListAccountStorage write",
+                vec![
+                    (
+                        q.rw_counter.value.clone() + 1.expr()
+                            - 2.expr() * q.is_storage_reversion.clone(),
+                        q.rw_counter.value.clone(),
+                    ),
+                    (1.expr(), q.is_write()),
+                    (RwTableTag::TxAccessListAccountStorage.expr(), q.tag()),
+                    (q.aux1.clone(), q.id()),
+                    (q.address.value.clone(), q.address.value.clone()),
+                    (q.storage_key.encoded.clone(), q.storage_key.encoded.clone()),
+                ],
+            )
+        });
+
+        // The MPT updates each slot from the value before its first access in
+        // the block to the value after its last access in the block.
+        self.condition(q.is_first_access.clone(), |cb| {
+            cb.add_tuple_lookup(
+                "mpt update starts from the value before the first access",
+                vec![
+                    (q.address.value.clone(), q.mpt.address.clone()),
+                    (q.storage_key.encoded.clone(), q.mpt.storage_key.clone()),
+                    (q.value_prev.clone(), q.mpt.value_prev.clone()),
+                ],
+            )
+        });
+        self.condition(q.is_last_access(), |cb| {
+            cb.add_tuple_lookup(
+                "mpt update ends at the value after the last access",
+                vec![
+                    (q.address.value.clone(), q.mpt.address.clone()),
+                    (q.storage_key.encoded.clone(), q.mpt.storage_key.clone()),
+                    (q.value.clone(), q.mpt.value.clone()),
+                ],
+            )
+        });
     }
     fn build_tx_access_list_account_constraints(&mut self, q: &Queries<F>) {
         self.require_zero("field_tag is 0 for TxAccessListAccount", q.field_tag());
@@ -163,7 +248,28 @@ impl<F: Field> ConstraintBuilder<F> {
             "storage_key is 0 for TxAccessListAccount",
             q.storage_key.encoded.clone(),
         );
-        // TODO: Missing constraints
+        // The accounts are cold at the start of each transaction, except for the
+        // prewarmed ones and the ones in the access list of the transaction,
+        // which are seeded with rw_counter 0, see
+        // `RwMap::insert_access_list_seeds` and
+        // `RwMap::insert_tx_access_list_seeds`. So the first access to such an
+        // account in the steps is reported warm.
+        // TODO: only allow the seeds of the precompiles, of the coinbase from
+        // Shanghai and of the access list, and require them for their first
+        // access. Until then a seed can warm any account.
+        self.condition(q.is_rw_counter_zero.clone(), |cb| {
+            cb.require_zero("access list seed is warm", 1.expr() - q.value())
+        });
+        self.stub_tags.push(RwTableTag::TxAccessListAccount);
+        self.condition(q.is_first_access.clone(), |cb| {
+            cb.require_zero("first access to an account is cold", q.value_prev.clone())
+        });
+        self.condition(not::expr(q.is_first_access.clone()), |cb| {
+            cb.require_zero(
+                "is_warm_prev is the previous is_warm",
+                q.value_prev.clone() - q.prev_value.clone(),
+            )
+        });
     }
 
     fn build_tx_access_list_account_storage_constraints(&mut self, q: &Queries<F>) {
@@ -172,6 +278,7 @@ impl<F: Field> ConstraintBuilder<F> {
             q.field_tag(),
         );
         // TODO: Missing constraints
+        self.stub_tags.push(RwTableTag::TxAccessListAccountStorage);
     }
 
     fn build_tx_refund_constraints(&mut self, q: &Queries<F>) {
@@ -181,7 +288,23 @@ impl<F: Field> ConstraintBuilder<F> {
             "storage_key is 0 for TxRefund",
             q.storage_key.encoded.clone(),
         );
-        // TODO: Missing constraints
+        // The refund of each tx accumulates from 0 the refund deltas of its
+        // SSTOREs and of their reversions, and is read by EndTx.
+        self.condition(q.is_first_access.clone(), |cb| {
+            cb.require_zero("tx refund starts from 0", q.value_prev.clone())
+        });
+        self.condition(not::expr(q.is_first_access.clone()), |cb| {
+            cb.require_zero(
+                "tx refund prev is the previous tx refund",
+                q.value_prev.clone() - q.prev_value.clone(),
+            )
+        });
+        self.condition(q.is_read(), |cb| {
+            cb.require_zero(
+                "tx refund read doesn't change it",
+                q.value() - q.value_prev.clone(),
+            )
+        });
     }
 
     fn build_account_constraints(&mut self, q: &Queries<F>) {
@@ -211,6 +334,7 @@ impl<F: Field> ConstraintBuilder<F> {
             q.storage_key.encoded.clone(),
         );
         // TODO: Missing constraints
+        self.stub_tags.push(RwTableTag::AccountDestructed);
     }
 
     fn build_call_context_constraints(&mut self, q: &Queries<F>) {
@@ -224,9 +348,12 @@ impl<F: Field> ConstraintBuilder<F> {
             (q.field_tag(), q.lookups.call_context_field_tag.clone()),
         );
         // TODO: Missing constraints
+        self.stub_tags.push(RwTableTag::CallContext);
     }
 
     fn require_zero(&mut self, name: &'static str, e: Expression<F>) {
+        #[cfg(test)]
+        let e = coverage::record(name, e);
         self.constraints.push((name, self.condition.clone() * e));
     }
 
@@ -244,8 +371,18 @@ impl<F: Field> ConstraintBuilder<F> {
     }
 
     fn add_lookup(&mut self, name: &'static str, lookup: (Expression<F>, Expression<F>)) {
-        let mut lookup = lookup;
-        lookup.0 = lookup.0 * self.condition.clone();
+        self.add_tuple_lookup(name, vec![lookup]);
+    }
+
+    fn add_tuple_lookup(
+        &mut self,
+        name: &'static str,
+        lookup: Vec<(Expression<F>, Expression<F>)>,
+    ) {
+        let lookup = lookup
+            .into_iter()
+            .map(|(input, table)| (input * self.condition.clone(), table))
+            .collect();
         self.lookups.push((name, lookup));
     }
 
@@ -257,6 +394,41 @@ impl<F: Field> ConstraintBuilder<F> {
     }
 }
 
+/// Test-mode instrumentation to find the constraints which are never active,
+/// i.e. their condition is 0 on every row.
+#[cfg(test)]
+pub mod coverage {
+    use crate::util::Expr;
+    use eth_types::Field;
+    use halo2_proofs::plonk::Expression;
+    use std::{cell::RefCell, collections::BTreeSet};
+
+    thread_local! {
+        static NAMES: RefCell<Option<BTreeSet<&'static str>>> = RefCell::new(None);
+    }
+
+    /// Run `f` with every constraint configured meanwhile replaced by its
+    /// condition, so that `MockProver` reports each row on which a constraint
+    /// is active as a failure. Returns the output of `f` and the names of all
+    /// the configured constraints.
+    pub fn run<T>(f: impl FnOnce() -> T) -> (T, BTreeSet<&'static str>) {
+        NAMES.with(|names| *names.borrow_mut() = Some(BTreeSet::new()));
+        let output = f();
+        let names = NAMES.with(|names| names.borrow_mut().take().unwrap());
+        (output, names)
+    }
+
+    pub(super) fn record<F: Field>(name: &'static str, e: Expression<F>) -> Expression<F> {
+        NAMES.with(|names| match names.borrow_mut().as_mut() {
+            Some(names) => {
+                names.insert(name);
+                1.expr()
+            }
+            None => e,
+        })
+    }
+}
+
 impl<F: Field> Queries<F> {
     fn selector(&self) -> Expression<F> {
         self.selector.clone()
@@ -298,6 +470,8 @@ impl<F: Field> Queries<F> {
         )
     }
 
+    // The key prefix, i.e. all the sorted fields but the rw_counter, differs
+    // from the one of the previous row.
     fn first_access(&self) -> Expression<F> {
         or::expr(&[
             not::expr(
@@ -308,6 +482,12 @@ impl<F: Field> Queries<F> {
         ])
     }
 
+    // The last access to a key is followed by the first access to another key,
+    // or is the last row of the table.
+    fn is_last_access(&self) -> Expression<F> {
+        1.expr() - self.next_selector.clone() * not::expr(self.next_is_first_access.clone())
+    }
+
     fn address_change(&self) -> Expression<F> {
         self.address.value.clone() - self.address.value_prev.clone()
     }
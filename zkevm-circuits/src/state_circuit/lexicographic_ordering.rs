@@ -104,6 +104,7 @@ impl<F: Field> Chip<F> {
         storage_key_bytes: [Column<Advice>; N_BYTES_WORD],
         rw_counter_limbs: [Column<Advice>; N_LIMBS_RW_COUNTER],
         u16_range: Column<Fixed>,
+        check_monotone: bool,
     ) -> Config<F> {
         let selector = meta.fixed_column();
         let [upper_limb_difference, upper_limb_difference_inverse, lower_limb_difference, lower_limb_difference_inverse] =
@@ -183,29 +184,34 @@ impl<F: Field> Chip<F> {
             ]
         });
         assert!(meta.degree() <= 16);
-        meta.lookup_any("upper_limb_difference fits into u16", |meta| {
-            let upper_limb_difference = meta.query_advice(upper_limb_difference, Rotation::cur());
-            vec![(
-                upper_limb_difference,
-                meta.query_fixed(u16_range, Rotation::cur()),
-            )]
-        });
-        meta.lookup_any(
-            "upper_limb_difference is zero or lower_limb_difference fits into u16",
-            |meta| {
-                let lower_limb_difference =
-                    meta.query_advice(lower_limb_difference, Rotation::cur());
+        // Without the checks that the differences are positive, the rows can be
+        // in any order.
+        if check_monotone {
+            meta.lookup_any("upper_limb_difference fits into u16", |meta| {
+                let upper_limb_difference =
+                    meta.query_advice(upper_limb_difference, Rotation::cur());
                 vec![(
-                    upper_limb_difference_is_zero * lower_limb_difference,
+                    upper_limb_difference,
                     meta.query_fixed(u16_range, Rotation::cur()),
                 )]
-            },
-        );
-        assert!(meta.degree() <= 16);
-        meta.create_gate("lower_limb_difference is not zero", |meta| {
-            let selector = meta.query_fixed(selector, Rotation::cur());
-            vec![(selector * lower_limb_difference_is_zero)]
-        });
+            });
+            meta.lookup_any(
+                "upper_limb_difference is zero or lower_limb_difference fits into u16",
+                |meta| {
+                    let lower_limb_difference =
+                        meta.query_advice(lower_limb_difference, Rotation::cur());
+                    vec![(
+                        upper_limb_difference_is_zero * lower_limb_difference,
+                        meta.query_fixed(u16_range, Rotation::cur()),
+                    )]
+                },
+            );
+            assert!(meta.degree() <= 16);
+            meta.create_gate("lower_limb_difference is not zero", |meta| {
+                let selector = meta.query_fixed(selector, Rotation::cur());
+                vec![(selector * lower_limb_difference_is_zero)]
+            });
+        }
         assert!(meta.degree() <= 16);
 
         config
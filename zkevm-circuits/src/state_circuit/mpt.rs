@@ -0,0 +1,126 @@
+use super::first_and_last_accesses;
+use crate::evm_circuit::{util::RandomLinearCombination as RLC, witness::Rw};
+use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+// TODO: The MPT circuit doesn't exist yet, so this table is a stub which the
+// state circuit assigns itself from its own rows. Once the MPT circuit is
+// there, it should assign the table and prove that its updates go from the
+// state root before the block to the one after.
+
+/// The update of a storage slot by the accesses with the same (address,
+/// storage_key) in all the transactions of the block, from the value before the
+/// first access to the value after the last one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MptUpdate {
+    pub address: Address,
+    pub storage_key: Word,
+    pub value_prev: Word,
+    pub value: Word,
+}
+
+impl MptUpdate {
+    /// The updates of the AccountStorage rows, which must be sorted as in the
+    /// state circuit.
+    pub fn from_rows(rows: &[Rw]) -> Vec<Self> {
+        let mut updates = vec![];
+        for (row, (is_first_access, is_last_access)) in
+            rows.iter().zip(first_and_last_accesses(rows))
+        {
+            if let Rw::AccountStorage {
+                account_address,
+                storage_key,
+                value,
+                value_prev,
+                ..
+            } = *row
+            {
+                if is_first_access {
+                    updates.push(Self {
+                        address: account_address,
+                        storage_key,
+                        value_prev,
+                        value,
+                    });
+                }
+                if is_last_access {
+                    updates.last_mut().unwrap().value = value;
+                }
+            }
+        }
+        updates
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Config {
+    pub address: Column<Advice>,
+    pub storage_key: Column<Advice>,
+    pub value_prev: Column<Advice>,
+    pub value: Column<Advice>,
+}
+
+#[derive(Clone)]
+pub struct Queries<F> {
+    pub address: Expression<F>,
+    pub storage_key: Expression<F>,
+    pub value_prev: Expression<F>,
+    pub value: Expression<F>,
+}
+
+impl<F: Field> Queries<F> {
+    pub fn new(meta: &mut VirtualCells<'_, F>, c: Config) -> Self {
+        Self {
+            address: meta.query_advice(c.address, Rotation::cur()),
+            storage_key: meta.query_advice(c.storage_key, Rotation::cur()),
+            value_prev: meta.query_advice(c.value_prev, Rotation::cur()),
+            value: meta.query_advice(c.value, Rotation::cur()),
+        }
+    }
+}
+
+impl Config {
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [address, storage_key, value_prev, value] = [0; 4].map(|_| meta.advice_column());
+        Self {
+            address,
+            storage_key,
+            value_prev,
+            value,
+        }
+    }
+
+    pub fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        update: &MptUpdate,
+        randomness: F,
+    ) -> Result<(), Error> {
+        for (name, column, value) in [
+            ("address", self.address, update.address.to_scalar().unwrap()),
+            (
+                "storage_key",
+                self.storage_key,
+                RLC::random_linear_combine(update.storage_key.to_le_bytes(), randomness),
+            ),
+            (
+                "value_prev",
+                self.value_prev,
+                RLC::random_linear_combine(update.value_prev.to_le_bytes(), randomness),
+            ),
+            (
+                "value",
+                self.value,
+                RLC::random_linear_combine(update.value.to_le_bytes(), randomness),
+            ),
+        ] {
+            region.assign_advice(|| format!("mpt {}", name), column, offset, || Ok(value))?;
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use super::N_LIMBS_ACCOUNT_ADDRESS;
 use super::N_LIMBS_RW_COUNTER;
 use crate::util::Expr;
-use eth_types::{Address, Field, ToScalar};
+use eth_types::{Address, Field};
 use halo2_proofs::{
-    circuit::{AssignedCell, Layouter, Region},
+    circuit::{Layouter, Region},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
@@ -67,7 +67,7 @@ impl Config<Address, N_LIMBS_ACCOUNT_ADDRESS> {
         region: &mut Region<'_, F>,
         offset: usize,
         value: Address,
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<(), Error> {
         for (i, &limb) in value.to_limbs().iter().enumerate() {
             region.assign_advice(
                 || format!("limb[{}] in address mpi", i),
@@ -76,12 +76,7 @@ impl Config<Address, N_LIMBS_ACCOUNT_ADDRESS> {
                 || Ok(F::from(limb as u64)),
             )?;
         }
-        region.assign_advice(
-            || "value in u32 mpi",
-            self.value,
-            offset,
-            || Ok(value.to_scalar().unwrap()), // do this better
-        )
+        Ok(())
     }
 }
 
@@ -91,7 +86,7 @@ impl Config<u32, N_LIMBS_RW_COUNTER> {
         region: &mut Region<'_, F>,
         offset: usize,
         value: u32,
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<(), Error> {
         for (i, &limb) in value.to_limbs().iter().enumerate() {
             region.assign_advice(
                 || format!("limb[{}] in u32 mpi", i),
@@ -100,12 +95,7 @@ impl Config<u32, N_LIMBS_RW_COUNTER> {
                 || Ok(F::from(limb as u64)),
             )?;
         }
-        region.assign_advice(
-            || "value in u32 mpi",
-            self.value,
-            offset,
-            || Ok(F::from(value as u64)),
-        )
+        Ok(())
     }
 }
 
@@ -131,9 +121,9 @@ where
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         selector: Column<Fixed>,
+        value: Column<Advice>,
         u16_range: Column<Fixed>,
     ) -> Config<T, N> {
-        let value = meta.advice_column();
         let limbs = [0; N].map(|_| meta.advice_column());
 
         for &limb in &limbs {
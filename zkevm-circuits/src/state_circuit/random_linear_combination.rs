@@ -1,7 +1,7 @@
 use crate::evm_circuit::util::RandomLinearCombination as RLC;
 use eth_types::{Field, ToLittleEndian, U256};
 use halo2_proofs::{
-    circuit::{AssignedCell, Layouter, Region},
+    circuit::{Layouter, Region},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, VirtualCells},
     poly::Rotation,
 };
@@ -36,9 +36,8 @@ impl<const N: usize> Config<N> {
         &self,
         region: &mut Region<'_, F>,
         offset: usize,
-        randomness: F,
         value: U256,
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<(), Error> {
         let bytes = value.to_le_bytes();
         for (i, &byte) in bytes.iter().enumerate() {
             region.assign_advice(
@@ -48,12 +47,7 @@ impl<const N: usize> Config<N> {
                 || Ok(F::from(byte as u64)),
             )?;
         }
-        region.assign_advice(
-            || "encoded value in rlc",
-            self.encoded,
-            offset,
-            || Ok(RLC::random_linear_combine(bytes, randomness)),
-        )
+        Ok(())
     }
 }
 
@@ -73,10 +67,10 @@ impl<F: Field, const N: usize> Chip<F, N> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         selector: Column<Fixed>,
+        encoded: Column<Advice>,
         u8_lookup: Column<Fixed>,
         power_of_randomness: [Column<Instance>; 31],
     ) -> Config<N> {
-        let encoded = meta.advice_column();
         let bytes = [0; N].map(|_| meta.advice_column());
 
         for &byte in &bytes {
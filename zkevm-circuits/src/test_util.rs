@@ -1,5 +1,8 @@
 use crate::{
-    evm_circuit::{table::FixedTableTag, witness::Block},
+    evm_circuit::{
+        table::FixedTableTag,
+        witness::{Block, StepTransitionMismatch},
+    },
     state_circuit::StateCircuit,
 };
 use bus_mapping::mock::BlockData;
@@ -59,27 +62,52 @@ impl Default for BytecodeTestConfig {
     }
 }
 
+/// Error returned by [`test_circuits_using_witness_block`]
+#[derive(Debug, PartialEq)]
+pub enum TestCircuitsError {
+    /// The steps of the witness block don't transition into each other
+    InconsistentWitness(StepTransitionMismatch),
+    /// A circuit doesn't verify
+    Verify(Vec<VerifyFailure>),
+}
+
+impl From<Vec<VerifyFailure>> for TestCircuitsError {
+    fn from(failures: Vec<VerifyFailure>) -> Self {
+        Self::Verify(failures)
+    }
+}
+
 pub fn run_test_circuits<const NACC: usize, const NTX: usize>(
     test_ctx: TestContext<NACC, NTX>,
     config: Option<BytecodeTestConfig>,
-) -> Result<(), Vec<VerifyFailure>> {
-    let block: GethData = test_ctx.into();
+) -> Result<(), TestCircuitsError> {
+    let block = build_witness_block(test_ctx);
+
+    // finish required tests according to config using this witness block
+    test_circuits_using_witness_block(block, config.unwrap_or_default())
+}
+
+/// Handle the traces of `block` and build a witness block from the result.
+pub fn build_witness_block(block: impl Into<GethData>) -> Block<Fr> {
+    let block: GethData = block.into();
     let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
     builder
         .handle_block(&block.eth_block, &block.geth_traces)
         .unwrap();
 
     // build a witness block from trace result
-    let block = crate::evm_circuit::witness::block_convert(&builder.block, &builder.code_db);
-
-    // finish required tests according to config using this witness block
-    test_circuits_using_witness_block(block, config.unwrap_or_default())
+    crate::evm_circuit::witness::block_convert(&builder.block, &builder.code_db)
 }
 
 pub fn test_circuits_using_witness_block(
     block: Block<Fr>,
     config: BytecodeTestConfig,
-) -> Result<(), Vec<VerifyFailure>> {
+) -> Result<(), TestCircuitsError> {
+    let block = crate::evm_circuit::test::with_env_randomness_seed(block);
+    block
+        .check_step_transitions()
+        .map_err(TestCircuitsError::InconsistentWitness)?;
+
     // run evm circuit test
     if config.enable_evm_circuit_test {
         crate::evm_circuit::test::run_test_circuit(block.clone(), config.evm_circuit_lookup_tags)?;
@@ -89,10 +117,13 @@ pub fn test_circuits_using_witness_block(
     // TODO: use randomness as one of the circuit public input, since randomness in
     // state circuit and evm circuit must be same
     if config.enable_state_circuit_test {
-        let state_circuit = StateCircuit::new(block.randomness, block.rws);
+        let rws = block.state_circuit_rws();
+        let state_circuit = StateCircuit::new(block.randomness, rws);
         let power_of_randomness = state_circuit.instance();
         let prover = MockProver::<Fr>::run(18, &state_circuit, power_of_randomness).unwrap();
-        prover.verify_at_rows(0..state_circuit.rows.len(), 0..state_circuit.rows.len())?
+        // The Start row comes before the rows of the rw set
+        let n_rows = state_circuit.rows.len() + 1;
+        prover.verify_at_rows(0..n_rows, 0..n_rows)?
     }
 
     Ok(())